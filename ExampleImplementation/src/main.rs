@@ -1,83 +1,144 @@
-use event_bus::{dispatch_event, subscribe_event};
-use glam::{IVec2, Vec2, Vec3};
-use XGEngine::events::{Action, ActionEvent, InteractEvent, InteractType};
-use XGEngine::renderer::renderer::MoveDirection::{BACKWARDS, FORWARD, LEFT, RIGHT};
-use XGEngine::renderer::renderer::RenderPerspective;
+use event_bus::dispatch_event;
+use glam::{IVec2, Mat4, Quat, Vec2, Vec3};
+use uuid::Uuid;
+use XGEngine::events::{Action, ActionEvent, FrameEvent, InteractEvent, InteractType};
+use XGEngine::renderer::MoveDirection::{BACKWARDS, FORWARD, LEFT, RIGHT};
+use XGEngine::renderer::RenderPerspective;
 use XGEngine::scene::chunk::Chunk;
 use XGEngine::scene::object::{ColoredSceneObject, ColoredVertex};
+#[cfg(feature = "shaderc")]
+use XGEngine::scene::particle::ParticleEmitter;
+#[cfg(feature = "shaderc")]
 use XGEngine::shader::BgfxShaderContainer;
 use XGEngine::windowed::Windowed;
 
 static mut SURFACE: Option<Windowed> = None;
 
-fn on_key(event: &mut InteractEvent) {
+// cube turned by on_frame
+static mut SPINNING: Option<Uuid> = None;
 
-    match event.interact {
+// debug overlay state flipped by the toggle_debug action
+static mut DEBUG: bool = false;
 
-        InteractType::Keyboard(glfw::Key::Escape) => {
+// movement, zoom and debug line keys currently down, on_frame acts on them every frame
+static mut HELD: Vec<glfw::Key> = Vec::new();
 
-            unsafe {
-                SURFACE.as_mut().unwrap().close_window();
-            }
-        }
+// field of view last set for the Z zoom, so it only changes when Z goes down or up
+static mut ZOOMED: bool = false;
 
-        InteractType::Mouse() => {
+fn held(key: glfw::Key) -> bool {
+    unsafe { HELD.contains(&key) }
+}
 
-            let current_scene = XGEngine::current_scene();
+// repeats of a held key change nothing
+fn set_held(key: glfw::Key, action: glfw::Action) {
+    unsafe {
+        match action {
+            glfw::Action::Press if !HELD.contains(&key) => HELD.push(key),
+            glfw::Action::Release => HELD.retain(|held| *held != key),
+            _ => {}
+        }
+    }
+}
 
-            let scene = current_scene.unwrap();
+// game side action routed through the engine bus, K dispatches it
+fn on_toggle_debug(_event: &mut ActionEvent) {
+    unsafe {
+        DEBUG = !DEBUG;
+        XGEngine::set_debug(DEBUG);
+    }
+}
 
-            let mut scene_object = scene.borrow_mut();
+// applies the held keys, then turns the cube half a turn per second however fast frames come
+fn on_frame(event: &mut FrameEvent) {
 
-            let data = &event.data;
+    apply_held_keys(event.delta_seconds);
 
-            if data.delta.0 < 0.0 {
-                scene_object.camera.at.x += 0.1;
-            } else if data.delta.0 > 0.0 {
-                scene_object.camera.at.x -= 0.1;
-            }
+    let id = match unsafe { SPINNING } {
+        Some(id) => id,
+        None => return
+    };
 
-            if data.delta.1 < 0.0 {
-                scene_object.camera.at.y += 0.1;
-            } else if data.delta.1 > 0.0 {
-                scene_object.camera.at.y -= 0.1;
-            }
+    let scene = XGEngine::current_scene().unwrap();
+
+    scene.borrow().with_object_mut(id, |object| {
+        if let Some(cube) = object.as_any_mut().downcast_mut::<ColoredSceneObject>() {
+            cube.rotation *= Quat::from_rotation_y(std::f32::consts::PI * event.delta_seconds);
         }
+    });
+}
 
-        InteractType::Keyboard(glfw::Key::W) => {
+// distance the camera moves per second while a key is held, scaled by the debug ui slider when it is there
+#[cfg(feature = "debug-ui")]
+fn camera_speed() -> f32 {
+    3.0 * XGEngine::debug_ui_camera_speed().unwrap_or(1.0)
+}
 
-            let current_scene = XGEngine::current_scene();
+#[cfg(not(feature = "debug-ui"))]
+fn camera_speed() -> f32 {
+    3.0
+}
 
-            let scene = current_scene.unwrap();
+// W/A/S/D move the camera, Z zooms and F draws the debug lines for as long as the key is down
+fn apply_held_keys(delta_seconds: f32) {
 
-            let mut scene_object = scene.borrow_mut();
+    let step = camera_speed() * delta_seconds;
 
-            scene_object.camera.move_eye(0.1, FORWARD);
+    for (key, direction) in [(glfw::Key::W, FORWARD), (glfw::Key::S, BACKWARDS), (glfw::Key::A, RIGHT), (glfw::Key::D, LEFT)] {
+
+        if held(key) {
+            XGEngine::current_scene().unwrap().borrow_mut().camera.move_eye(step, direction);
         }
 
-        InteractType::Keyboard(glfw::Key::S) => {
+    }
 
-            let current_scene = XGEngine::current_scene();
+    let zoom = held(glfw::Key::Z);
 
-            let scene = current_scene.unwrap();
+    unsafe {
+        if zoom != ZOOMED {
+            ZOOMED = zoom;
+            XGEngine::set_fov(if zoom { 30.0 } else { 60.0 });
+        }
+    }
 
-            let mut scene_object = scene.borrow_mut();
+    // debug lines last one frame, so they are submitted again every frame
+    if held(glfw::Key::F) {
+        XGEngine::debug_axes(Mat4::IDENTITY);
+        XGEngine::debug_aabb(Vec3::new(5.0, 0.0, 0.0), Vec3::new(6.0, 1.0, 1.0), 0xff00ffff);
+        XGEngine::debug_line(Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0), 0xffffffff);
+    }
+}
 
-            scene_object.camera.move_eye(0.1, BACKWARDS)
-        }
+fn on_key(event: &mut InteractEvent) {
 
-        InteractType::Keyboard(glfw::Key::A) => {
+    match event.interact {
 
-            let current_scene = XGEngine::current_scene();
+        InteractType::Keyboard(glfw::Key::Escape, _) => {
 
-            let scene = current_scene.unwrap();
+            unsafe {
+                SURFACE.as_mut().unwrap().close_window();
+            }
+        }
+
+        // middle mouse drag orbits the camera around the point it looks at
+        InteractType::Drag { button: glfw::MouseButton::Button3, delta, .. } => {
+
+            let scene = XGEngine::current_scene().unwrap();
 
             let mut scene_object = scene.borrow_mut();
+            let camera = &mut scene_object.camera;
+
+            let offset = camera.eye - camera.at;
+            let right = offset.cross(camera.up).normalize_or_zero();
 
-            scene_object.camera.move_eye(0.1, RIGHT);
+            let yaw = Quat::from_rotation_y(-delta.0 as f32 * 0.01);
+            let pitch = if right == Vec3::ZERO { Quat::IDENTITY } else { Quat::from_axis_angle(right, delta.1 as f32 * 0.01) };
+
+            let at = camera.at;
+            camera.set_eye(at + yaw * pitch * offset);
         }
 
-        InteractType::Keyboard(glfw::Key::D) => {
+        InteractType::Mouse() => {
 
             let current_scene = XGEngine::current_scene();
 
@@ -85,10 +146,26 @@ fn on_key(event: &mut InteractEvent) {
 
             let mut scene_object = scene.borrow_mut();
 
-            scene_object.camera.move_eye(0.1, LEFT);
+            let data = &event.data;
+
+            if data.delta.0 < 0.0 {
+                scene_object.camera.at.x += 0.1;
+            } else if data.delta.0 > 0.0 {
+                scene_object.camera.at.x -= 0.1;
+            }
+
+            if data.delta.1 < 0.0 {
+                scene_object.camera.at.y += 0.1;
+            } else if data.delta.1 > 0.0 {
+                scene_object.camera.at.y -= 0.1;
+            }
+        }
+
+        InteractType::Keyboard(key @ (glfw::Key::W | glfw::Key::A | glfw::Key::S | glfw::Key::D | glfw::Key::Z | glfw::Key::F), action) => {
+            set_held(key, action);
         }
 
-        InteractType::Keyboard(glfw::Key::T) => {
+        InteractType::Keyboard(glfw::Key::T, _) => {
 
             let current_scene = XGEngine::current_scene();
 
@@ -106,7 +183,7 @@ fn on_key(event: &mut InteractEvent) {
 
         }
 
-        InteractType::Keyboard(glfw::Key::G) => {
+        InteractType::Keyboard(glfw::Key::G, _) => {
 
             let current_scene = XGEngine::current_scene();
 
@@ -124,12 +201,43 @@ fn on_key(event: &mut InteractEvent) {
 
         }
 
+        InteractType::Keyboard(glfw::Key::V, _) => {
+            XGEngine::draw_grid(true, 1.0, 20.0);
+            XGEngine::draw_axes(true);
+        }
+
+        InteractType::Keyboard(glfw::Key::C, _) => {
+            XGEngine::draw_grid(false, 1.0, 20.0);
+            XGEngine::draw_axes(false);
+        }
+
+        // U releases the mouse, I captures it again for the camera
+        InteractType::Keyboard(glfw::Key::U, _) => {
+            XGEngine::set_cursor_mode(glfw::CursorMode::Normal);
+        }
+
+        InteractType::Keyboard(glfw::Key::I, _) => {
+            XGEngine::set_cursor_mode(glfw::CursorMode::Disabled);
+        }
+
+        InteractType::Keyboard(glfw::Key::K, _) => {
+
+            let mut event = ActionEvent::custom("toggle_debug", ());
+
+            dispatch_event!("engine", &mut event);
+        }
+
+        #[cfg(feature = "debug-ui")]
+        InteractType::Keyboard(glfw::Key::H, _) => {
+            XGEngine::toggle_debug_ui();
+        }
+
         _ => {}
     }
 
 }
 
-fn create_object(size: f32, shader_id: i32, coordinates: Vec3, chunk: &mut Chunk) {
+fn create_object(size: f32, shader_id: i32, coordinates: Vec3, chunk: &mut Chunk) -> Uuid {
 
     let basic_object_vert: Box<[ColoredVertex]> = Box::new(
         [
@@ -169,36 +277,110 @@ fn create_object(size: f32, shader_id: i32, coordinates: Vec3, chunk: &mut Chunk
         coordinates
     );
 
+    let id = scene_object.id;
+
     chunk.add_object(Box::new(scene_object));
 
+    id
 }
 
 fn main() {
 
     let mut windowed = Windowed::new(1920, 1080, "Test", true, 60);
     windowed.add_key_handler(glfw::Key::Escape, glfw::Action::Press);
-    windowed.add_key_handler(glfw::Key::W, glfw::Action::Press);
-    windowed.add_key_handler(glfw::Key::S, glfw::Action::Press);
-    windowed.add_key_handler(glfw::Key::A, glfw::Action::Press);
-    windowed.add_key_handler(glfw::Key::D, glfw::Action::Press);
+
+    // held keys need their release too
+    for key in [glfw::Key::W, glfw::Key::S, glfw::Key::A, glfw::Key::D, glfw::Key::Z, glfw::Key::F] {
+        windowed.add_key_handler(key, glfw::Action::Press);
+        windowed.add_key_handler(key, glfw::Action::Release);
+    }
+
     windowed.add_key_handler(glfw::Key::T, glfw::Action::Press);
     windowed.add_key_handler(glfw::Key::G, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::V, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::C, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::U, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::I, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::K, glfw::Action::Press);
+    windowed.add_key_handler(glfw::Key::H, glfw::Action::Press);
 
     fn init_objects() {
 
         let mut chunk: Chunk = Chunk::new(IVec2::new(0,0));
 
-        // create bgfx shader container
-        let shader_container = BgfxShaderContainer::new(
-            std::fs::read("resources/shaders/metal/fs_cubes.bin").unwrap(),
-            std::fs::read("resources/shaders/metal/vs_cubes.bin").unwrap()
-        );
+        // built-in cubes shader, nothing has to be loaded from disk. there is none for direct3d and
+        // vulkan, those need the shaderc feature
+        #[cfg(not(feature = "shaderc"))]
+        let id = match XGEngine::default_colored_shader() {
+            Ok(id) => id,
+            Err(e) => {
+
+                eprintln!("{}, build the example with the shaderc feature for this renderer", e);
 
-        let id = XGEngine::add_shader(Box::new(shader_container));
+                unsafe {
+                    SURFACE.as_mut().unwrap().close_window();
+                }
 
-        create_object(1.0, id.clone(), Vec3::new(5.0, 0.0, 0.0), &mut chunk);
+                return;
+            }
+        };
+
+        // compile the cubes shader for whatever renderer bgfx picked, shaderc is taken
+        // from SHADERC and bgfx_shader.sh from SHADERC_INCLUDE
+        #[cfg(feature = "shaderc")]
+        let id = match BgfxShaderContainer::compile(
+            &std::fs::read_to_string("resources/shaders/src/vs_cubes.sc").unwrap(),
+            &std::fs::read_to_string("resources/shaders/src/fs_cubes.sc").unwrap(),
+            &std::fs::read_to_string("resources/shaders/src/varying.def.sc").unwrap(),
+            bgfx_rs::bgfx::get_renderer_type()
+        ) {
+            Ok(container) => XGEngine::add_shader(Box::new(container)),
+            Err(e) => panic!("{}", e)
+        };
+
+        // cubes shader consumes position and color, so it can draw the debug lines too
+        XGEngine::set_debug_shader(id.clone()).unwrap();
+
+        let spinning = create_object(1.0, id.clone(), Vec3::new(5.0, 0.0, 0.0), &mut chunk);
         create_object(2.0, id.clone(), Vec3::new(7.0, 0.0, 0.0), &mut chunk);
 
+        unsafe {
+            SPINNING = Some(spinning);
+        }
+
+        // particles are instanced quads, their vertex shader is only built from source
+        #[cfg(feature = "shaderc")]
+        {
+            let particle_id = match BgfxShaderContainer::compile(
+                &std::fs::read_to_string("resources/shaders/src/vs_particles.sc").unwrap(),
+                &std::fs::read_to_string("resources/shaders/src/fs_particles.sc").unwrap(),
+                &std::fs::read_to_string("resources/shaders/src/varying_particles.def.sc").unwrap(),
+                bgfx_rs::bgfx::get_renderer_type()
+            ) {
+                Ok(container) => XGEngine::add_shader(Box::new(container)),
+                Err(e) => panic!("{}", e)
+            };
+
+            // fountain shooting particles up and letting them fall back down
+            let mut fountain = ParticleEmitter::new(
+                120.0,
+                2.5,
+                Vec3::new(0.0, 4.0, 0.0),
+                Vec3::new(0.0, -3.0, 0.0),
+                400,
+                XGEngine::get_shader(particle_id).unwrap(),
+                Vec3::new(3.0, 0.0, 3.0)
+            );
+
+            fountain.spread = 0.8;
+            fountain.size = 0.08;
+            fountain.color_rgba = 0xffff9933;
+            // additive blending, fading to black fades the particle out
+            fountain.end_color_rgba = Some(0xff000000);
+
+            chunk.add_object(Box::new(fountain));
+        }
+
         let scene_binding = XGEngine::current_scene().unwrap();
 
         let mut current_scene = scene_binding.borrow_mut();
@@ -233,16 +415,30 @@ fn main() {
         scene_reference.camera.set_at(Vec3::new(0.0, 0.0, 0.0));
         scene_reference.camera.set_up(Vec3::new(0.0, 0.5, 0.0));
 
-        subscribe_event!("engine", on_key);
+        XGEngine::subscribe(on_key);
+        XGEngine::subscribe(on_frame);
+        XGEngine::subscribe_action("toggle_debug", on_toggle_debug);
 
         XGEngine::set_debug(false);
 
+        // overlay with camera speed, debug toggles and frame stats, H hides it
+        #[cfg(feature = "debug-ui")]
+        match BgfxShaderContainer::compile(
+            &std::fs::read_to_string("resources/shaders/src/vs_egui.sc").unwrap(),
+            &std::fs::read_to_string("resources/shaders/src/fs_egui.sc").unwrap(),
+            &std::fs::read_to_string("resources/shaders/src/varying_egui.def.sc").unwrap(),
+            bgfx_rs::bgfx::get_renderer_type()
+        ) {
+            Ok(container) => XGEngine::enable_debug_ui(XGEngine::add_shader(Box::new(container))).unwrap(),
+            Err(e) => panic!("{}", e)
+        };
+
     }
 
     let default_perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
 
     unsafe {
         SURFACE = Some(windowed);
-        SURFACE.as_mut().unwrap().run(default_perspective, &init_objects);
+        SURFACE.as_mut().unwrap().run(default_perspective, &init_objects).expect("Renderer failed");
     }
 }
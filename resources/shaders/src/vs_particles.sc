@@ -0,0 +1,13 @@
+$input a_position, i_data0, i_data1
+$output v_color0
+
+#include <bgfx_shader.sh>
+
+// a_position is a corner of the unit quad, i_data0 the particle position and size, i_data1 its color.
+// the corner is offset in view space so the quad always faces the camera
+void main()
+{
+	vec4 center = mul(u_modelView, vec4(i_data0.xyz, 1.0) );
+	gl_Position = mul(u_proj, vec4(center.xyz + a_position * i_data0.w, 1.0) );
+	v_color0 = i_data1;
+}
@@ -0,0 +1,253 @@
+use egui::epaint::{ImageData, Mesh16, Primitive};
+use egui::{Pos2, Rect, TexturesDelta};
+use crate::events::{InteractEvent, InteractType, PressAction};
+use crate::renderer::RenderStats;
+
+// priority the debug ui input handler is subscribed with. input routing: the ui sees every
+// InteractEvent before handlers subscribed through crate::subscribe with a lower priority and
+// cancels the ones it consumes, so the camera does not move while a slider is dragged. handlers
+// subscribed on the bus directly with subscribe_event! still get every event
+pub const DEBUG_UI_PRIORITY: i32 = 1000;
+
+// immediate mode overlay for tweaking the engine while it runs, drawn after the scene
+pub struct DebugUi {
+    context: egui::Context,
+    // input collected since the last frame
+    input: egui::RawInput,
+    pub pixels_per_point: f32,
+    pub visible: bool,
+    // multiplier for camera movement, read by the application
+    pub camera_speed: f32,
+    debug: bool,
+    wireframe: bool
+}
+
+// everything the renderer needs to draw one frame of the ui, and the toggles changed in it
+pub struct DebugUiFrame {
+    // meshes in points with the rectangle they are clipped to
+    pub meshes: Vec<(Rect, Mesh16)>,
+    // textures to upload before drawing and to free afterwards
+    pub textures: TexturesDelta,
+    pub pixels_per_point: f32,
+    pub debug: Option<bool>,
+    pub wireframe: Option<bool>
+}
+
+impl DebugUi {
+
+    pub fn new() -> Self {
+        Self {
+            context: egui::Context::default(),
+            input: egui::RawInput::default(),
+            pixels_per_point: 1.0,
+            visible: true,
+            camera_speed: 1.0,
+            debug: false,
+            wireframe: false
+        }
+    }
+
+    // queues the event for the next frame, true when the ui consumes it. the pointer belongs to
+    // the ui over its windows and while it drags, keys while a text field has the focus
+    pub fn handle_event(&mut self, event: &InteractEvent) -> bool {
+
+        match event.interact {
+
+            InteractType::Mouse() => {
+
+                let pos = Pos2::new(event.data.cursor.0 as f32 / self.pixels_per_point, event.data.cursor.1 as f32 / self.pixels_per_point);
+
+                self.input.events.push(egui::Event::PointerMoved(pos));
+
+                let button = match event.data.pressed {
+                    PressAction::PRESSED(button) => pointer_button(button).map(|button| (button, true)),
+                    PressAction::RELEASED(button) => pointer_button(button).map(|button| (button, false)),
+                    PressAction::NONE => None
+                };
+
+                if let Some((button, pressed)) = button {
+                    self.input.events.push(egui::Event::PointerButton { pos, button, pressed, modifiers: egui::Modifiers::default() });
+                }
+
+                self.visible && (self.context.wants_pointer_input() || self.context.layer_id_at(pos).is_some())
+            }
+
+            InteractType::Char(character) => {
+                self.input.events.push(egui::Event::Text(character.to_string()));
+                self.visible && self.context.wants_keyboard_input()
+            }
+
+            InteractType::Keyboard(_, _) => self.visible && self.context.wants_keyboard_input(),
+
+            InteractType::CursorEnter(entered) => {
+
+                if !entered {
+                    self.input.events.push(egui::Event::PointerGone);
+                }
+
+                false
+            }
+
+            // the press before it already told whether the pointer is over the ui
+            InteractType::DoubleClick(_) => self.visible && (self.context.wants_pointer_input() || self.context.is_pointer_over_area()),
+
+            // drags starting on a window belong to it, its button press was consumed already
+            InteractType::Drag { start, .. } => {
+
+                let start = Pos2::new(start.0 as f32 / self.pixels_per_point, start.1 as f32 / self.pixels_per_point);
+
+                self.visible && (self.context.wants_pointer_input() || self.context.layer_id_at(start).is_some())
+            }
+        }
+    }
+
+    // lays out the panel for a surface of width x height pixels and tessellates it
+    pub fn run(&mut self, (width, height): (u32, u32), stats: &RenderStats) -> DebugUiFrame {
+
+        let mut input = std::mem::take(&mut self.input);
+
+        input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, egui::vec2(width as f32, height as f32) / self.pixels_per_point));
+        input.pixels_per_point = Some(self.pixels_per_point);
+
+        let (mut debug, mut wireframe, mut camera_speed) = (self.debug, self.wireframe, self.camera_speed);
+        let visible = self.visible;
+
+        let output = self.context.run(input, |context| {
+
+            if !visible {
+                return;
+            }
+
+            egui::Window::new("XGEngine").default_pos([10.0, 10.0]).show(context, |ui| {
+
+                ui.add(egui::Slider::new(&mut camera_speed, 0.1..=10.0).text("camera speed"));
+                ui.checkbox(&mut debug, "debug text");
+                ui.checkbox(&mut wireframe, "wireframe");
+
+                ui.separator();
+
+                for line in stats.debug_lines() {
+                    ui.label(format!("{}: {}", line.key(), line.value()));
+                }
+            });
+        });
+
+        let mut meshes = Vec::new();
+
+        for primitive in self.context.tessellate(output.shapes) {
+            if let Primitive::Mesh(mesh) = primitive.primitive {
+                meshes.extend(mesh.split_to_u16().into_iter().map(|mesh| (primitive.clip_rect, mesh)));
+            }
+        }
+
+        let frame = DebugUiFrame {
+            meshes,
+            textures: output.textures_delta,
+            pixels_per_point: self.pixels_per_point,
+            debug: (debug != self.debug).then_some(debug),
+            wireframe: (wireframe != self.wireframe).then_some(wireframe)
+        };
+
+        self.debug = debug;
+        self.wireframe = wireframe;
+        self.camera_speed = camera_speed;
+
+        frame
+    }
+
+}
+
+impl Default for DebugUi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn pointer_button(button: glfw::MouseButton) -> Option<egui::PointerButton> {
+    match button {
+        glfw::MouseButton::Button1 => Some(egui::PointerButton::Primary),
+        glfw::MouseButton::Button2 => Some(egui::PointerButton::Secondary),
+        glfw::MouseButton::Button3 => Some(egui::PointerButton::Middle),
+        _ => None
+    }
+}
+
+// rgba8 pixels of a ui texture, the font atlas is coverage and gets expanded
+pub fn image_rgba(image: &ImageData) -> Vec<u8> {
+    match image {
+        ImageData::Color(image) => image.pixels.iter().flat_map(|pixel| pixel.to_array()).collect(),
+        ImageData::Font(image) => image.srgba_pixels(None).flat_map(|pixel| pixel.to_array()).collect()
+    }
+}
+
+// scissor rectangle in pixels for a clip rectangle in points, limited to the surface
+pub fn clip_to_pixels(clip: Rect, pixels_per_point: f32, (width, height): (u32, u32)) -> (u16, u16, u16, u16) {
+
+    let min_x = (clip.min.x * pixels_per_point).round().clamp(0.0, width as f32);
+    let min_y = (clip.min.y * pixels_per_point).round().clamp(0.0, height as f32);
+    let max_x = (clip.max.x * pixels_per_point).round().clamp(min_x, width as f32);
+    let max_y = (clip.max.y * pixels_per_point).round().clamp(min_y, height as f32);
+
+    (min_x as u16, min_y as u16, (max_x - min_x) as u16, (max_y - min_y) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(cursor: (f64, f64)) -> InteractEvent {
+
+        let mut event = InteractEvent::new(InteractType::Mouse());
+        event.data.cursor = cursor;
+
+        event
+    }
+
+    #[test]
+    fn input_routing_test() {
+
+        let mut ui = DebugUi::new();
+
+        // windows are placed during the first frames
+        ui.run((1280, 720), &RenderStats::default());
+        ui.run((1280, 720), &RenderStats::default());
+
+        assert!(ui.handle_event(&mouse((20.0, 20.0))));
+        assert!(!ui.handle_event(&mouse((1000.0, 700.0))));
+
+        // nothing has the keyboard focus
+        assert!(!ui.handle_event(&InteractEvent::new(InteractType::Char('w'))));
+
+        ui.visible = false;
+        ui.run((1280, 720), &RenderStats::default());
+
+        assert!(!ui.handle_event(&mouse((20.0, 20.0))));
+    }
+
+    #[test]
+    fn run_test() {
+
+        let mut ui = DebugUi::new();
+
+        let frame = ui.run((1280, 720), &RenderStats::default());
+
+        // the font atlas is uploaded with the first frame
+        assert!(!frame.textures.set.is_empty());
+        assert!(frame.debug.is_none());
+        assert!(frame.wireframe.is_none());
+    }
+
+    #[test]
+    fn clip_to_pixels_test() {
+
+        let clip = Rect::from_min_max(Pos2::new(10.0, 20.0), Pos2::new(110.0, 70.0));
+
+        assert_eq!(clip_to_pixels(clip, 1.0, (1280, 720)), (10, 20, 100, 50));
+        assert_eq!(clip_to_pixels(clip, 2.0, (1280, 720)), (20, 40, 200, 100));
+
+        // clipped to the surface
+        assert_eq!(clip_to_pixels(clip, 1.0, (50, 40)), (10, 20, 40, 20));
+        assert_eq!(clip_to_pixels(Rect::EVERYTHING, 1.0, (64, 32)), (0, 0, 64, 32));
+    }
+
+}
@@ -3,8 +3,9 @@ use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use event_bus::EventResult;
 use glam::Vec3;
-use log::error;
-use crate::renderer::renderer::{Renderer, RenderPerspective, RenderView};
+use crate::error::XgError;
+use crate::events::ErrorSource;
+use crate::renderer::{Renderer, RenderPerspective, RenderView};
 use crate::scene::manager::SceneManager;
 use crate::scene::scene::Scene;
 
@@ -16,14 +17,19 @@ pub struct EngineEnvironment {
 impl EngineEnvironment {
 
     pub fn new() -> Self {
+        Self::with_initial_scene(crate::DEFAULT_SCENE)
+    }
+
+    // environment starting in an empty scene with the given name
+    pub fn with_initial_scene(name: &str) -> Self {
 
-        let mut scene_manager = SceneManager::new();
+        let scene_manager = SceneManager::with_initial_scene(name);
 
-        let default_scene = scene_manager.get_scene(String::from("default")).unwrap();
+        let initial_scene = scene_manager.get_scene(String::from(name)).unwrap();
 
         Self {
             scene_manager,
-            current_scene: default_scene
+            current_scene: initial_scene
         }
     }
 
@@ -35,29 +41,27 @@ impl EngineEnvironment {
 
     }
 
-    pub fn get_scene(&self, name: String) -> std::io::Result<Rc<RefCell<Scene>>> {
+    pub fn get_scene(&self, name: String) -> Result<Rc<RefCell<Scene>>, XgError> {
 
         let scene = self.scene_manager.get_scene(name);
 
-        match scene {
-            Ok(scene) => Ok(Rc::clone(&scene)),
-            Err(e) => {
-                error!("Scene instance does not exist");
-                Err(std::io::Error::new(std::io::ErrorKind::Other, "Scene instance does not exist"))
-            }
+        if let Err(e) = &scene {
+            crate::report_error(ErrorSource::Scene, e.to_string(), true);
         }
 
+        scene
     }
 
-    pub fn render_scene(&mut self, name: String) -> std::io::Result<(EventResult)> {
-
-        let result = self.scene_manager.render_scene(name.clone());
+    // switches current_scene only when no handler cancelled the ChangeSceneEvent
+    pub fn render_scene(&mut self, name: String) -> Result<(), XgError> {
 
-        if result.is_ok() {
-            self.current_scene = self.get_scene(name.clone()).unwrap();
+        match self.scene_manager.render_scene(name.clone())? {
+            EventResult::EvPassed => {
+                self.current_scene = self.get_scene(name)?;
+                Ok(())
+            },
+            EventResult::EvCancelled(reason) => Err(XgError::SceneChangeCancelled(reason))
         }
-
-        result
     }
 }
 
@@ -104,4 +108,15 @@ mod tests {
         assert_eq!(result.is_ok(), true);
     }
 
+    #[test]
+    fn test_render_missing_scene() {
+
+        let mut environment = EngineEnvironment::new();
+
+        let result = environment.render_scene(String::from("typo"));
+
+        assert_eq!(result.unwrap_err(), XgError::SceneNotFound(String::from("typo")));
+        assert_eq!(environment.current_scene.borrow().name, "default");
+    }
+
 }
\ No newline at end of file
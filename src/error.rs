@@ -0,0 +1,110 @@
+use std::fmt::{Display, Formatter};
+use crate::renderer::RendererError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XgError {
+    // no scene is registered under the name
+    SceneNotFound(String),
+    // a ChangeSceneEvent handler cancelled the scene change, carries the reason
+    SceneChangeCancelled(String),
+    // no shader is registered under the id
+    ShaderNotFound(i32),
+    // no shader is registered under the name
+    NamedShaderNotFound(String),
+    // another shader is already registered under the name
+    DuplicateShaderName(String),
+    // uniforms an object sets that its shader does not declare with that type
+    UndeclaredUniforms(Vec<String>),
+    // a default value does not match the declared uniform type
+    UniformTypeMismatch(String),
+    // no built-in shader binary is embedded for the named renderer
+    NoBuiltinShader(String),
+    // no chunk covers the requested coordinates
+    ChunkNotFound,
+    // a chunk grid size that is zero, negative or not finite
+    InvalidChunkSize(String),
+    // no camera bookmark is saved under the name
+    CameraBookmarkNotFound(String),
+    // an index points past the vertices of an object
+    IndexOutOfRange { index: u16, vertices: usize },
+    // a triangle list with a count of indices, or of vertices without indices, that is not a multiple of 3
+    IncompleteTriangle(usize),
+    // the engine was used before create_engine
+    NotInitialized,
+    RendererError(RendererError)
+}
+
+impl Display for XgError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XgError::SceneNotFound(name) => write!(f, "Scene {} does not exist", name),
+            XgError::SceneChangeCancelled(reason) => write!(f, "Scene change cancelled: {}", reason),
+            XgError::ShaderNotFound(id) => write!(f, "Shader {} not found", id),
+            XgError::NamedShaderNotFound(name) => write!(f, "Shader {} not found", name),
+            XgError::DuplicateShaderName(name) => write!(f, "Shader {} already exists", name),
+            XgError::UndeclaredUniforms(names) => write!(f, "Uniforms not declared by the shader: {}", names.join(", ")),
+            XgError::UniformTypeMismatch(name) => write!(f, "Uniform {} is declared with another type", name),
+            XgError::NoBuiltinShader(renderer) => write!(f, "No built-in shader for {}", renderer),
+            XgError::ChunkNotFound => write!(f, "Chunk does not exist"),
+            XgError::InvalidChunkSize(size) => write!(f, "Chunk size {} is not a positive finite number", size),
+            XgError::CameraBookmarkNotFound(name) => write!(f, "Camera bookmark {} does not exist", name),
+            XgError::IndexOutOfRange { index, vertices } => write!(f, "Index {} is out of range for {} vertices", index, vertices),
+            XgError::IncompleteTriangle(count) => write!(f, "{} is not a whole number of triangles", count),
+            XgError::NotInitialized => write!(f, "Engine is not initialized"),
+            XgError::RendererError(e) => write!(f, "{}", e)
+        }
+    }
+
+}
+
+impl std::error::Error for XgError {}
+
+impl From<RendererError> for XgError {
+    fn from(e: RendererError) -> Self {
+        XgError::RendererError(e)
+    }
+}
+
+// callers still on io::Result keep working with ?
+impl From<XgError> for std::io::Error {
+    fn from(e: XgError) -> Self {
+
+        let kind = match e {
+            XgError::SceneNotFound(_)
+            | XgError::ShaderNotFound(_)
+            | XgError::NamedShaderNotFound(_)
+            | XgError::ChunkNotFound
+            | XgError::CameraBookmarkNotFound(_) => std::io::ErrorKind::NotFound,
+            XgError::DuplicateShaderName(_) => std::io::ErrorKind::AlreadyExists,
+            XgError::UndeclaredUniforms(_)
+            | XgError::UniformTypeMismatch(_)
+            | XgError::IndexOutOfRange { .. }
+            | XgError::IncompleteTriangle(_)
+            | XgError::InvalidChunkSize(_) => std::io::ErrorKind::InvalidInput,
+            XgError::NoBuiltinShader(_) => std::io::ErrorKind::Unsupported,
+            XgError::SceneChangeCancelled(_) => std::io::ErrorKind::Interrupted,
+            XgError::NotInitialized | XgError::RendererError(_) => std::io::ErrorKind::Other
+        };
+
+        std::io::Error::new(kind, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_error_test() {
+
+        let error: std::io::Error = XgError::SceneNotFound(String::from("typo")).into();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "Scene typo does not exist");
+
+        let error: std::io::Error = XgError::DuplicateShaderName(String::from("colored")).into();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    }
+
+}
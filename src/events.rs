@@ -1,18 +1,46 @@
+use std::any::Any;
+use std::path::PathBuf;
 use event_bus::Event;
 use glam::{Vec2, Vec3};
 use glfw::Key::S;
 use glfw::MouseButton;
 use crate::events::PressAction::NONE;
+use crate::renderer::RenderPerspective;
 use crate::scene::scene::Scene;
 
 pub enum InteractType {
-    Keyboard(glfw::Key),
-    Mouse()
+    // key and whether it was pressed, repeated while held or released
+    Keyboard(glfw::Key, glfw::Action),
+    // character typed, with keyboard layout and modifiers applied
+    Char(char),
+    Mouse(),
+    // second press of the button close in time and place to the first, after its Mouse event
+    DoubleClick(MouseButton),
+    // cursor entered (true) or left (false) the window, no mouse deltas arrive while it is
+    // outside unless the cursor is captured
+    CursorEnter(bool),
+    // mouse moved with a button held further than the drag threshold, start is where the
+    // button went down and delta the movement since the previous drag event
+    Drag {
+        button: MouseButton,
+        start: (f64, f64),
+        current: (f64, f64),
+        delta: (f64, f64),
+        phase: DragPhase
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragPhase {
+    Begin,
+    Move,
+    End
 }
 
 pub enum PressAction {
     NONE,
-    PRESSED(MouseButton)
+    PRESSED(MouseButton),
+    RELEASED(MouseButton)
 }
 
 pub struct MouseData {
@@ -35,8 +63,28 @@ impl MouseData {
 
 pub enum Action {
     ChangeScene(String),
-    ViewPortUpdate(Vec3, Vec3, Vec3, i32),
-    UpdateResolution(u32, u32)
+    // moves the camera to eye, looking at at with the up vector, in the named scene
+    // or in the current one when none is given. ViewPortUpdatedEvent follows
+    ViewPortUpdate(Vec3, Vec3, Vec3, Option<String>),
+    UpdateResolution(u32, u32),
+    UpdatePerspective(RenderPerspective),
+    // shows or hides the debug text
+    SetDebug(bool),
+    // background of the current scene as 0xRRGGBBAA, a sky gradient replaces it every update
+    SetClearColor(u32),
+    // presents frames in step with the display refresh
+    SetVsync(bool),
+    // title of the window Windowed runs, applied at the start of its next frame
+    SetWindowTitle(String),
+    // size of the window Windowed runs, the renderer follows with the ResizeEvent of the
+    // framebuffer, which is larger than the window on HiDPI displays
+    SetWindowSize(u32, u32),
+    // Normal shows the cursor, Hidden hides it over the window and Disabled captures it for
+    // camera control, applied by Windowed like SetWindowTitle
+    SetCursorMode(glfw::CursorMode),
+    // application defined action with a tag and any payload, run by the handlers subscribed
+    // for the tag with crate::subscribe_action. the engine ignores tags nobody subscribed to
+    Custom(String, Box<dyn Any + Send>)
 }
 
 pub struct InitEvent {
@@ -52,6 +100,96 @@ pub struct ShutdownEvent {
 pub struct InteractEvent {
     pub interact: InteractType,
     pub data: MouseData,
+    // shift, control, alt and super held when a key event happened, empty for other events
+    pub modifiers: glfw::Modifiers,
+    // platform specific code of the physical key of a key event, 0 for other events
+    pub scancode: glfw::Scancode,
+    // label of the key in the current keyboard layout, like "z" for the W position on azerty.
+    // none for keys without a printable name and for other events
+    pub key_name: Option<String>,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// the surface resolution differs from the previous one, dispatched after the renderer was reset
+pub struct ResolutionChangedEvent {
+    pub width: u32,
+    pub height: u32,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// the camera of a scene was moved by Action::ViewPortUpdate
+pub struct ViewPortUpdatedEvent {
+    pub scene: String,
+    pub eye: Vec3,
+    pub at: Vec3,
+    pub up: Vec3,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// start of a Windowed loop iteration, before input is handled and the frame is rendered
+pub struct FrameEvent {
+    // seconds since the previous iteration, measured and clamped by the window loop
+    pub delta_seconds: f32,
+    // iterations before this one
+    pub frame: u64,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// the window framebuffer size or content scale changed, dispatched before Action::UpdateResolution
+pub struct ResizeEvent {
+    pub old: (u32, u32),
+    pub new: (u32, u32),
+    // content scale of the monitor the window is on, 2.0 on most HiDPI displays
+    pub scale: (f32, f32),
+    cancelled: bool,
+    reason: Option<String>
+}
+
+pub enum WindowState {
+    // the window gained or lost the keyboard focus
+    Focused(bool),
+    // the window was minimized or brought back from the taskbar
+    Iconified(bool),
+    // a maximized window went back to its previous size
+    Restored
+}
+
+pub struct WindowStateEvent {
+    pub state: WindowState,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// files dropped onto the window, all files of one drop in the order the window manager lists them
+pub struct FileDropEvent {
+    pub paths: Vec<PathBuf>,
+    // cursor position the files were dropped at
+    pub cursor: (f64, f64),
+    cancelled: bool,
+    reason: Option<String>
+}
+
+// part of the engine a non-fatal error came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorSource {
+    Renderer,
+    Scene,
+    Shader
+}
+
+// something went wrong at runtime but the engine keeps going, e.g. a shader failed to load or
+// the camera is outside every chunk. the error is logged as well, handlers can show it to the
+// player or quit when it is not recoverable. errors found while rendering are dispatched once the
+// frame is done
+pub struct EngineErrorEvent {
+    pub source: ErrorSource,
+    pub message: String,
+    // false when the engine cannot do its work until the application steps in, e.g. sets a scene
+    pub recoverable: bool,
     cancelled: bool,
     reason: Option<String>
 }
@@ -73,6 +211,113 @@ impl ActionEvent {
         }
     }
 
+    pub fn custom<T: Any + Send>(tag: &str, payload: T) -> Self {
+        Self::new(Action::Custom(String::from(tag), Box::new(payload)))
+    }
+
+    // payload of a custom action, none for other actions or another payload type
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        match self.action {
+            Action::Custom(_, ref payload) => payload.downcast_ref::<T>(),
+            _ => None
+        }
+    }
+
+}
+
+impl ResolutionChangedEvent {
+
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width, height,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl ViewPortUpdatedEvent {
+
+    pub fn new(scene: String, eye: Vec3, at: Vec3, up: Vec3) -> Self {
+        Self {
+            scene, eye, at, up,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl FrameEvent {
+
+    pub fn new(delta_seconds: f32, frame: u64) -> Self {
+        Self {
+            delta_seconds, frame,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl ResizeEvent {
+
+    pub fn new(old: (u32, u32), new: (u32, u32), scale: (f32, f32)) -> Self {
+        Self {
+            old, new, scale,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl WindowStateEvent {
+
+    pub fn new(state: WindowState) -> Self {
+        Self {
+            state,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl FileDropEvent {
+
+    pub fn new(paths: Vec<PathBuf>, cursor: (f64, f64)) -> Self {
+        Self {
+            paths, cursor,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl EngineErrorEvent {
+
+    pub fn new(source: ErrorSource, message: String, recoverable: bool) -> Self {
+        Self {
+            source, message, recoverable,
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl ShutdownEvent {
+
+    pub fn new() -> Self {
+        Self {
+            cancelled: false,
+            reason: None
+        }
+    }
+
 }
 
 impl InitEvent {
@@ -94,7 +339,10 @@ impl InteractEvent {
             interact,
             cancelled: false,
             reason: None,
-            data: MouseData::new()
+            data: MouseData::new(),
+            modifiers: glfw::Modifiers::empty(),
+            scancode: 0,
+            key_name: None
         }
     }
 
@@ -179,6 +427,157 @@ impl Event for ActionEvent {
 
 }
 
+impl Event for FrameEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+impl Event for ResizeEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+// the drop already happened, so there is nothing to cancel
+impl Event for FileDropEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+// the error already happened, so there is nothing to cancel
+impl Event for EngineErrorEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+// the window manager already changed the window, so there is nothing to cancel
+impl Event for WindowStateEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+// the change already happened, so there is nothing to cancel
+impl Event for ResolutionChangedEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
+impl Event for ViewPortUpdatedEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,10 +613,13 @@ mod tests {
         subscribe_event!("test", test_sub_cancelled);
 
         let mut event = InteractEvent {
-            interact: Keyboard(glfw::Key::B),
+            interact: Keyboard(glfw::Key::B, glfw::Action::Press),
             cancelled: false,
             reason: None,
-            data: MouseData::new()
+            data: MouseData::new(),
+            modifiers: glfw::Modifiers::Control,
+            scancode: 0,
+            key_name: None
         };
 
         let mut init_event = InitEvent {
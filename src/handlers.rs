@@ -0,0 +1,275 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use crate::events::ActionEvent;
+
+// handle of a handler subscribed through the engine, see crate::subscribe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+struct Subscription {
+    id: SubscriptionId,
+    // fn(&mut E) of the event type the subscription is stored under
+    handler: Box<dyn Any>,
+    // scene the handler is bound to, removed when the scene is left
+    scene: Option<String>,
+    // higher runs first
+    priority: i32
+}
+
+// handlers subscribed through the engine. event_bus cannot remove a handler again, so the
+// engine subscribes a single dispatcher per event type and keeps the handlers here
+#[derive(Default)]
+pub struct HandlerRegistry {
+    next_id: u64,
+    // handlers by the TypeId of their event, in subscription order
+    handlers: HashMap<TypeId, Vec<Subscription>>,
+    // handlers of Action::Custom by the action tag, run by the engine's action handler
+    actions: HashMap<String, Vec<Subscription>>,
+    // event types the dispatcher is subscribed for on the bus, kept when handlers are cleared
+    dispatched: HashSet<TypeId>
+}
+
+impl HandlerRegistry {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // returns the id to remove the handler with and whether the dispatcher for the event type
+    // still has to be subscribed on the bus. handlers of equal priority run in subscription order
+    pub fn add<E: 'static>(&mut self, handler: fn(&mut E), scene: Option<String>, priority: i32) -> (SubscriptionId, bool) {
+
+        let id = self.next_id();
+
+        insert(self.handlers.entry(TypeId::of::<E>()).or_default(), Subscription { id, handler: Box::new(handler), scene, priority });
+
+        (id, self.dispatched.insert(TypeId::of::<E>()))
+    }
+
+    // handler for Action::Custom actions with the tag, ordered by priority like add
+    pub fn add_action(&mut self, tag: &str, handler: fn(&mut ActionEvent), scene: Option<String>, priority: i32) -> SubscriptionId {
+
+        let id = self.next_id();
+
+        insert(self.actions.entry(String::from(tag)).or_default(), Subscription { id, handler: Box::new(handler), scene, priority });
+
+        id
+    }
+
+    fn next_id(&mut self) -> SubscriptionId {
+
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+
+        id
+    }
+
+    fn subscriptions_mut(&mut self) -> impl Iterator<Item = &mut Vec<Subscription>> {
+        self.handlers.values_mut().chain(self.actions.values_mut())
+    }
+
+    pub fn remove(&mut self, id: SubscriptionId) -> bool {
+
+        for handlers in self.subscriptions_mut() {
+
+            if let Some(index) = handlers.iter().position(|subscription| subscription.id == id) {
+                handlers.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // removes the handlers bound to the scene, returns how many there were
+    pub fn remove_scene(&mut self, scene: &str) -> usize {
+
+        let before = self.len();
+
+        for handlers in self.subscriptions_mut() {
+            handlers.retain(|subscription| subscription.scene.as_deref() != Some(scene));
+        }
+
+        before - self.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.handlers.clear();
+        self.actions.clear();
+    }
+
+    // copy of the handlers for the event type in the order they run, so handlers can subscribe
+    // or clear while dispatching
+    pub fn handlers<E: 'static>(&self) -> Vec<fn(&mut E)> {
+        match self.handlers.get(&TypeId::of::<E>()) {
+            Some(handlers) => handlers.iter().filter_map(|subscription| subscription.handler.downcast_ref::<fn(&mut E)>().copied()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    // copy of the handlers for the custom action tag in the order they run, empty for unknown tags
+    pub fn action_handlers(&self, tag: &str) -> Vec<fn(&mut ActionEvent)> {
+        match self.actions.get(tag) {
+            Some(handlers) => handlers.iter().filter_map(|subscription| subscription.handler.downcast_ref::<fn(&mut ActionEvent)>().copied()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.values().chain(self.actions.values()).map(|handlers| handlers.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+}
+
+// inserts behind the subscriptions with the same or a higher priority
+fn insert(handlers: &mut Vec<Subscription>, subscription: Subscription) {
+
+    let index = handlers.iter().position(|other| other.priority < subscription.priority).unwrap_or(handlers.len());
+
+    handlers.insert(index, subscription);
+}
+
+// unsubscribes the handler when dropped, see crate::subscribe_scoped
+#[must_use = "the handler is unsubscribed as soon as the guard is dropped"]
+pub struct HandlerGuard {
+    id: SubscriptionId
+}
+
+impl HandlerGuard {
+
+    pub(crate) fn new(id: SubscriptionId) -> Self {
+        Self { id }
+    }
+
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+}
+
+impl Drop for HandlerGuard {
+    fn drop(&mut self) {
+        crate::unsubscribe(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counted(u32);
+    struct Other;
+
+    fn increment(event: &mut Counted) {
+        event.0 += 1;
+    }
+
+    fn double(event: &mut Counted) {
+        event.0 *= 2;
+    }
+
+    fn other(_event: &mut Other) {}
+
+    #[test]
+    fn registry_test() {
+
+        let mut registry = HandlerRegistry::new();
+
+        let (increment_id, subscribe) = registry.add(increment, None, 0);
+        assert!(subscribe);
+
+        // the dispatcher for the type is already on the bus
+        let (_, subscribe) = registry.add(double, None, 0);
+        assert!(!subscribe);
+
+        assert!(registry.add(other, None, 0).1);
+
+        let mut event = Counted(1);
+
+        for handler in registry.handlers::<Counted>() {
+            handler(&mut event);
+        }
+
+        assert_eq!(event.0, 4);
+
+        assert!(registry.remove(increment_id));
+        assert!(!registry.remove(increment_id));
+        assert_eq!(registry.handlers::<Counted>().len(), 1);
+
+        registry.clear();
+        assert!(registry.is_empty());
+
+        // cleared types keep their dispatcher
+        assert!(!registry.add(increment, None, 0).1);
+    }
+
+    struct Order(Vec<&'static str>);
+
+    #[test]
+    fn priority_test() {
+
+        let mut registry = HandlerRegistry::new();
+
+        registry.add(|event: &mut Order| event.0.push("gameplay"), None, 0);
+        registry.add(|event: &mut Order| event.0.push("ui"), None, 10);
+        registry.add(|event: &mut Order| event.0.push("late"), None, -5);
+        registry.add(|event: &mut Order| event.0.push("gameplay 2"), None, 0);
+
+        let mut event = Order(Vec::new());
+
+        for handler in registry.handlers::<Order>() {
+            handler(&mut event);
+        }
+
+        assert_eq!(event.0, vec!["ui", "gameplay", "gameplay 2", "late"]);
+    }
+
+    #[test]
+    fn scene_handlers_test() {
+
+        let mut registry = HandlerRegistry::new();
+
+        registry.add(increment, Some(String::from("menu")), 0);
+        registry.add(double, Some(String::from("level")), 0);
+        registry.add(other, None, 0);
+
+        assert_eq!(registry.remove_scene("menu"), 1);
+        assert_eq!(registry.remove_scene("menu"), 0);
+
+        let mut event = Counted(3);
+
+        for handler in registry.handlers::<Counted>() {
+            handler(&mut event);
+        }
+
+        // only the level handler is left
+        assert_eq!(event.0, 6);
+        assert_eq!(registry.len(), 2);
+    }
+
+    fn pause(event: &mut ActionEvent) {
+        event.cancelled = true;
+    }
+
+    #[test]
+    fn action_handlers_test() {
+
+        let mut registry = HandlerRegistry::new();
+
+        let id = registry.add_action("pause", pause, None, 0);
+        registry.add_action("inventory", pause, Some(String::from("level")), 0);
+
+        assert_eq!(registry.action_handlers("pause").len(), 1);
+        assert!(registry.action_handlers("unknown").is_empty());
+        assert_eq!(registry.len(), 2);
+
+        assert_eq!(registry.remove_scene("level"), 1);
+        assert!(registry.remove(id));
+        assert!(registry.is_empty());
+    }
+
+}
@@ -1,22 +1,37 @@
 use std::cell::RefCell;
-use std::rc::Rc;
-use event_bus::{dispatch_event, EventBus, subscribe_event};
-use glam::Vec3;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+use event_bus::{dispatch_event, Event, EventBus, subscribe_event};
+use glam::{Mat4, Vec3};
 use glfw::{FAIL_ON_ERRORS, Glfw};
 use glfw::Key::{B, N, P};
-use log::info;
+use log::{error, info};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+#[cfg(feature = "debug-ui")]
+use crate::debug_ui::{DEBUG_UI_PRIORITY, DebugUi};
 use crate::environment::EngineEnvironment;
-use crate::events::{Action, ActionEvent, InteractEvent, InteractType};
-use crate::renderer::renderer::{BgfxRenderer, Renderer, RenderPerspective, RenderView};
-use crate::scene::manager::{ChangeSceneEvent, SceneManager};
+pub use crate::error::XgError;
+use crate::handlers::{HandlerGuard, HandlerRegistry, SubscriptionId};
+use crate::queue::{EngineEventQueue, EventSender};
+use crate::events::{Action, ActionEvent, EngineErrorEvent, ErrorSource, InteractEvent, InteractType, ResolutionChangedEvent, ShutdownEvent, ViewPortUpdatedEvent, WindowState, WindowStateEvent};
+use crate::renderer::events::{ClearEvent, RefreshEvent};
+use crate::renderer::{BgfxRenderer, LayerProjection, Renderer, RendererError, RenderPerspective, RenderStats, RenderView, SceneLayer};
+use crate::scene::manager::{ChangeSceneEvent, SceneEnterEvent, SceneExitEvent, SceneManager, Transition};
 use crate::scene::scene::Scene;
-use crate::shader::{ShaderContainer, ShaderManager};
+use crate::shader::{ShaderContainer, ShaderInfo, ShaderManager};
+use crate::windowed::{DEFAULT_MAX_FRAME_DELTA, WindowRequest};
 
 mod core;
+#[cfg(feature = "debug-ui")]
+pub mod debug_ui;
+pub mod error;
 pub mod events;
 mod environment;
+pub mod handlers;
+pub mod queue;
 pub mod shader;
+#[cfg(feature = "shaderc")]
+pub mod shaderc;
 pub mod windowed;
 
 mod messaging {
@@ -25,27 +40,56 @@ mod messaging {
 //    pub mod message;
 }
 
-pub mod renderer {
-    pub mod renderer;
-    pub mod events;
-}
+pub mod renderer;
 
 pub mod scene {
+    pub mod animation;
+    pub mod bookmark;
+    pub mod bounds;
     pub mod chunk;
     pub mod manager;
+    pub mod mesh;
     pub mod object;
+    pub mod particle;
     pub mod scene;
+    pub mod sky;
+    pub mod spatial;
+    pub mod sprite;
 }
 
 pub struct Engine {
     renderer: Box<dyn Renderer>,
     environment: EngineEnvironment,
     shader_manager: ShaderManager,
-    bus: EventBus
+    bus: EventBus,
+    last_frame: Option<Instant>,
+    // clamped delta of the FrameEvent of the current window loop iteration, see set_frame_delta
+    frame_delta: Option<f32>,
+    // frames done since init and the time init finished
+    frame_count: u64,
+    started: Option<Instant>,
+    // registered key handlers skip printable keys while text is typed, see set_text_input
+    text_input: bool,
+    // surface resolution last applied, none until the first update
+    resolution: Option<(u32, u32)>,
+    // frames are skipped while the window is iconified, see set_pause_when_iconified
+    pause_when_iconified: bool,
+    iconified: bool,
+    // handlers subscribed with subscribe
+    handlers: HandlerRegistry,
+    // events queued by other threads, see event_sender
+    events: EngineEventQueue,
+    // window changes Windowed applies on its next frame
+    window_requests: Vec<WindowRequest>,
+    #[cfg(feature = "debug-ui")]
+    debug_ui: Option<DebugUi>
 }
 
 static mut ENGINE: Option<Engine> = None;
 
+// name of the scene the engine starts in unless another one is given
+pub const DEFAULT_SCENE: &str = "default";
+
 
 impl Engine {
 
@@ -54,40 +98,171 @@ impl Engine {
         Self {
             renderer, environment,
             shader_manager: ShaderManager::new(),
-            bus: EventBus::new("engine")
+            bus: EventBus::new("engine"),
+            last_frame: None,
+            frame_delta: None,
+            frame_count: 0,
+            started: None,
+            text_input: false,
+            resolution: None,
+            pause_when_iconified: false,
+            iconified: false,
+            handlers: HandlerRegistry::new(),
+            events: EngineEventQueue::new(),
+            window_requests: Vec::new(),
+            #[cfg(feature = "debug-ui")]
+            debug_ui: None
+        }
+    }
+
+    pub fn init(&mut self) -> Result<(), RendererError> {
+
+        self.renderer.init()?;
+
+        self.frame_count = 0;
+        self.started = Some(Instant::now());
+
+        Ok(())
+    }
+
+    pub fn do_frame(&mut self) -> Result<(), RendererError> {
+
+        // nothing is visible, objects continue from where they were once the window is back
+        if self.paused() {
+            self.last_frame = None;
+            self.frame_delta = None;
+            return Ok(());
+        }
+
+        self.update_objects();
+
+        // a zero sized framebuffer has nothing to render into
+        if self.surface_empty() {
+            return Ok(());
+        }
+
+        self.frame_count += 1;
+
+        #[cfg(feature = "debug-ui")]
+        self.run_debug_ui();
+
+        self.renderer.do_render_cycle()
+    }
+
+    // lays out the debug ui with the stats of the previous frame and hands it to the renderer
+    #[cfg(feature = "debug-ui")]
+    fn run_debug_ui(&mut self) {
+
+        let debug_ui = match self.debug_ui.as_mut() {
+            Some(debug_ui) => debug_ui,
+            None => return
+        };
+
+        let perspective = self.renderer.get_perspective();
+        let size = self.resolution.unwrap_or((perspective.width, perspective.height));
+
+        let frame = debug_ui.run(size, &self.renderer.get_stats());
+
+        if let Some(debug) = frame.debug {
+            self.renderer.do_debug(debug);
         }
+
+        if let Some(wireframe) = frame.wireframe {
+            self.renderer.set_wireframe(wireframe);
+        }
+
+        self.renderer.draw_debug_ui(frame);
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn paused(&self) -> bool {
+        self.pause_when_iconified && self.iconified
+    }
+
+    // the last resolution update reported a zero width or height
+    pub fn surface_empty(&self) -> bool {
+        matches!(self.resolution, Some((0, _)) | Some((_, 0)))
     }
 
-    pub fn init(&mut self) {
-        self.renderer.init();
+    // time since init, zero before it
+    pub fn elapsed(&self) -> Duration {
+        match self.started {
+            Some(started) => started.elapsed(),
+            None => Duration::ZERO
+        }
     }
 
-    pub fn do_frame(&mut self) {
-        self.renderer.do_render_cycle();
+    // advances the scene and objects of the current chunk by the delta FrameEvent handlers saw,
+    // frames done without a window loop measure the time since the previous frame and clamp it alike
+    fn update_objects(&mut self) {
+
+        let now = Instant::now();
+
+        let delta = match (self.frame_delta.take(), self.last_frame) {
+            (Some(delta), _) => delta,
+            (None, Some(last)) => now.saturating_duration_since(last).as_secs_f32().min(DEFAULT_MAX_FRAME_DELTA),
+            (None, None) => 0.0
+        };
+
+        self.last_frame = Some(now);
+
+        self.environment.current_scene.borrow_mut().update(delta);
+
+        let scene = self.environment.current_scene.borrow();
+
+        let chunk = match scene.get_current_chunk() {
+            Ok(chunk) => chunk,
+            Err(_) => return
+        };
+
+        for object in &mut chunk.iter_objects_mut() {
+            object.update(delta);
+        }
     }
 
     pub fn get_environment(&self) -> &EngineEnvironment {
         &self.environment
     }
 
-    fn update_resolution(&mut self, width: u32, height: u32) {
-        self.renderer.update_surface_resolution(width, height);
+    // resets the renderer surface, returns false without touching it when the resolution did not change.
+    // the real size is kept so frames are skipped while it is zero, the renderer gets at least 1x1
+    fn update_resolution(&mut self, width: u32, height: u32) -> bool {
+
+        if self.resolution == Some((width, height)) {
+            return false;
+        }
+
+        self.resolution = Some((width, height));
+        self.renderer.resize(width, height);
+
+        true
     }
 
 }
 
-fn create_engine(renderer: Box<dyn Renderer>) {
+fn create_engine(renderer: Box<dyn Renderer>, initial_scene: &str) {
 
     unsafe {
 
-        let environment = EngineEnvironment::new();
+        let environment = EngineEnvironment::with_initial_scene(initial_scene);
 
         ENGINE = Some(Engine::new(renderer, environment));
 
     }
 
+    // subscribed on the new bus right away, so actions dispatched before init are applied too
+    subscribe_event!("engine", change_scene_handler);
+    subscribe_event!("engine", action_event_handler);
+    subscribe_event!("engine", window_state_handler);
+    subscribe_event!("engine", refresh_handler);
+    subscribe_event!("engine", clear_handler);
+
 }
 
+// dispatches Action::SetDebug
 pub fn set_debug(debug: bool) {
     unsafe  {
 
@@ -95,165 +270,2044 @@ pub fn set_debug(debug: bool) {
             panic!("Cannot debug when ENGINE is not initialized");
         }
 
-        ENGINE.as_mut().unwrap().renderer.do_debug(debug);
+    }
+
+    let mut event = ActionEvent::new(Action::SetDebug(debug));
+
+    dispatch_event!("engine", &mut event);
+}
+
+// dispatches Action::SetClearColor, the color is 0xRRGGBBAA
+pub fn set_clear_color(rgba: u32) {
+    unsafe  {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set clear color when ENGINE is not initialized");
+        }
 
     }
+
+    let mut event = ActionEvent::new(Action::SetClearColor(rgba));
+
+    dispatch_event!("engine", &mut event);
 }
 
-// create scene in engine environment
-pub fn create_scene(name: String) {
+// dispatches Action::SetVsync
+pub fn set_vsync(enabled: bool) {
+    unsafe  {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set vsync when ENGINE is not initialized");
+        }
+
+    }
 
+    let mut event = ActionEvent::new(Action::SetVsync(enabled));
+
+    dispatch_event!("engine", &mut event);
+}
+
+// delta of the FrameEvent just dispatched, the next do_frame advances objects by it
+pub(crate) fn set_frame_delta(delta: f32) {
     unsafe {
+        if let Some(engine) = ENGINE.as_mut() {
+            engine.frame_delta = Some(delta);
+        }
+    }
+}
+
+// dispatches Action::SetWindowTitle
+pub fn set_window_title(title: &str) {
+    unsafe  {
 
         if ENGINE.is_none() {
-            panic!("Cannot create scene when ENGINE is not initialized");
+            panic!("Cannot set window title when ENGINE is not initialized");
         }
 
-        ENGINE.as_mut().unwrap().environment.create_scene(name);
+    }
+
+    let mut event = ActionEvent::new(Action::SetWindowTitle(String::from(title)));
+
+    dispatch_event!("engine", &mut event);
+}
+
+// dispatches Action::SetWindowSize
+pub fn set_window_size(width: u32, height: u32) {
+    unsafe  {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set window size when ENGINE is not initialized");
+        }
 
     }
 
+    let mut event = ActionEvent::new(Action::SetWindowSize(width, height));
+
+    dispatch_event!("engine", &mut event);
 }
 
-// get scene
-pub fn get_scene(name: String) -> std::io::Result<Rc<RefCell<Scene>>> {
+// dispatches Action::SetCursorMode
+pub fn set_cursor_mode(mode: glfw::CursorMode) {
+    unsafe  {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set cursor mode when ENGINE is not initialized");
+        }
+
+    }
+
+    let mut event = ActionEvent::new(Action::SetCursorMode(mode));
 
+    dispatch_event!("engine", &mut event);
+}
+
+// window changes requested since the last call, in the order of the actions
+pub(crate) fn take_window_requests() -> Vec<WindowRequest> {
+    unsafe {
+        match ENGINE.as_mut() {
+            Some(engine) => std::mem::take(&mut engine.window_requests),
+            None => Vec::new()
+        }
+    }
+}
+
+// draws triangles as outlines
+pub fn set_wireframe(enabled: bool) {
     unsafe {
 
         if ENGINE.is_none() {
-            panic!("Cannot get scene when ENGINE is not initialized");
+            panic!("Cannot set wireframe when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.set_wireframe(enabled);
+    }
+}
+
+// shows the debug ui drawn with the shader, see debug_ui::DEBUG_UI_PRIORITY for how it takes
+// input from the camera and other handlers
+#[cfg(feature = "debug-ui")]
+pub fn enable_debug_ui(shader_id: i32) -> Result<(), XgError> {
+
+    let shader = get_shader(shader_id)?;
+
+    let subscribed = unsafe {
+
+        let engine = ENGINE.as_mut().unwrap();
+
+        engine.renderer.set_debug_ui_shader(shader);
+
+        match engine.debug_ui.as_mut() {
+            Some(debug_ui) => {
+                debug_ui.visible = true;
+                true
+            }
+            None => {
+                engine.debug_ui = Some(DebugUi::new());
+                false
+            }
         }
+    };
+
+    if !subscribed {
+        subscribe_with_priority(debug_ui_input, DEBUG_UI_PRIORITY);
+    }
+
+    Ok(())
+}
 
-        ENGINE.as_mut().unwrap().environment.get_scene(name)
+// hides or shows the debug ui, a hidden ui consumes no input
+#[cfg(feature = "debug-ui")]
+pub fn toggle_debug_ui() {
+    unsafe {
+        if let Some(debug_ui) = ENGINE.as_mut().and_then(|engine| engine.debug_ui.as_mut()) {
+            debug_ui.visible = !debug_ui.visible;
+        }
+    }
+}
 
+// camera speed set in the debug ui, none without it
+#[cfg(feature = "debug-ui")]
+pub fn debug_ui_camera_speed() -> Option<f32> {
+    unsafe {
+        ENGINE.as_ref().and_then(|engine| engine.debug_ui.as_ref()).map(|debug_ui| debug_ui.camera_speed)
     }
+}
+
+// cancels input the debug ui consumed, so lower priority handlers skip it
+#[cfg(feature = "debug-ui")]
+fn debug_ui_input(event: &mut InteractEvent) {
+
+    let consumed = unsafe {
+        match ENGINE.as_mut().and_then(|engine| engine.debug_ui.as_mut()) {
+            Some(debug_ui) => debug_ui.handle_event(event),
+            None => false
+        }
+    };
 
+    if consumed {
+        event.set_cancelled(true, Some(String::from("consumed by the debug ui")));
+    }
 }
 
-// current scene
-pub fn current_scene() -> std::io::Result<Rc<RefCell<Scene>>> {
+// whether the renderer initialized successfully and was not shut down since
+pub fn renderer_initialized() -> bool {
+    unsafe {
+        match ENGINE.as_ref() {
+            Some(engine) => engine.renderer.is_initialized(),
+            None => false
+        }
+    }
+}
 
+// frames done since init, for time based effects and debugging
+pub fn frame_count() -> u64 {
     unsafe {
 
         if ENGINE.is_none() {
-            panic!("Cannot get scene when ENGINE is not initialized");
+            panic!("Cannot get frame count when ENGINE is not initialized");
         }
 
-        Ok(Rc::clone(&ENGINE.as_mut().unwrap().environment.current_scene))
+        ENGINE.as_ref().unwrap().frame_count()
 
     }
+}
 
+// seconds since init
+pub fn elapsed_seconds() -> f64 {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot get elapsed time when ENGINE is not initialized");
+        }
+
+        ENGINE.as_ref().unwrap().elapsed().as_secs_f64()
+
+    }
 }
 
-// add shader
-pub fn add_shader(shader: Box<dyn ShaderContainer>) -> i32 {
+// total seconds since init, same clock as elapsed_seconds
+pub fn time() -> f64 {
+    elapsed_seconds()
+}
 
+// statistics of the last rendered frame
+pub fn render_stats() -> RenderStats {
     unsafe {
 
         if ENGINE.is_none() {
-            panic!("Cannot add shader when ENGINE is not initialized");
+            panic!("Cannot get render stats when ENGINE is not initialized");
         }
 
-        ENGINE.as_mut().unwrap().shader_manager.add_shader(shader)
+        ENGINE.as_ref().unwrap().renderer.get_stats()
+
     }
+}
+
+// change field of view in degrees, keeping the current resolution and clip planes
+pub fn set_fov(fov: f32) {
+
+    let mut perspective = unsafe {
 
+        if ENGINE.is_none() {
+            panic!("Cannot set fov when ENGINE is not initialized");
+        }
+
+        ENGINE.as_ref().unwrap().renderer.get_perspective()
+
+    };
+
+    perspective.fov = fov * (std::f32::consts::PI / 180.0);
+
+    let mut event = ActionEvent::new(Action::UpdatePerspective(perspective));
+
+    dispatch_event!("engine", &mut event);
 }
 
-// get shader
-pub fn get_shader(id: i32) -> std::io::Result<Rc<RefCell<Box<dyn ShaderContainer>>>> {
+// draw the XZ grid at y = 0
+pub fn draw_grid(enabled: bool, spacing: f32, extent: f32) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot draw grid when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.draw_grid(enabled, spacing, extent);
+
+    }
+}
 
+// draw the world axes at the origin
+pub fn draw_axes(enabled: bool) {
     unsafe {
 
         if ENGINE.is_none() {
-            panic!("Cannot get shader when ENGINE is not initialized");
+            panic!("Cannot draw axes when ENGINE is not initialized");
         }
 
-        let shader = ENGINE.as_mut().unwrap().shader_manager.get_shader(id);
+        ENGINE.as_mut().unwrap().renderer.draw_axes(enabled);
 
-        if shader.is_none() {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Shader not found"));
+    }
+}
+
+// draw the basis of the scene camera, toggled like the grid and the axes
+pub fn show_camera_gizmo(enabled: bool) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot show camera gizmo when ENGINE is not initialized");
         }
 
-        Ok(shader.unwrap())
+        ENGINE.as_mut().unwrap().renderer.show_camera_gizmo(enabled);
 
     }
+}
+
+// handle for queueing actions from other threads, like an asset loader announcing a finished
+// texture. dispatch_event! is main thread only because handlers touch the scenes, queued events
+// are dispatched on the engine bus by dispatch_queued at the start of the next frame
+pub fn event_sender() -> EventSender {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot get event sender when ENGINE is not initialized");
+        }
 
+        ENGINE.as_ref().unwrap().events.sender()
+
+    }
 }
 
-fn change_scene_handler(event: &mut ChangeSceneEvent) {
+// dispatches the events queued through event_sender in the order they were sent, returns how
+// many there were. Windowed calls it every frame, other loops have to call it themselves
+pub fn dispatch_queued() -> usize {
 
-    unsafe {
+    let events = unsafe {
 
         if ENGINE.is_none() {
-            panic!("Cannot change event when RENDERER is not initialized");
+            panic!("Cannot dispatch queued events when ENGINE is not initialized");
         }
 
-        info!("Changing scene");
+        ENGINE.as_ref().unwrap().events.drain()
+    };
+
+    let count = events.len();
+
+    for event in events {
 
-        ENGINE.as_mut().unwrap().renderer.set_scene(Rc::clone(&event.scene));
+        let mut event = event.into_action_event();
 
+        dispatch_event!("engine", &mut event);
     }
+
+    count
 }
 
-fn action_event_handler(event: &mut ActionEvent) {
+// while active, Char events keep coming but key handlers no longer fire for printable keys,
+// so movement bindings stay quiet while a console or a name is typed
+pub fn set_text_input(active: bool) {
+    unsafe {
 
-    match event.action {
+        if ENGINE.is_none() {
+            panic!("Cannot set text input when ENGINE is not initialized");
+        }
 
-        Action::ChangeScene(ref scene) => {
+        ENGINE.as_mut().unwrap().text_input = active;
 
-            unsafe {
+    }
+}
 
-                ENGINE.as_mut().unwrap().environment.render_scene(scene.clone()).expect("TODO: panic message");
+// flips text input mode, returns whether it is active now
+pub fn toggle_text_input() -> bool {
 
-            }
+    let active = !text_input();
 
-        },
+    set_text_input(active);
 
-        Action::UpdateResolution(width, height) => {
-            unsafe {
+    active
+}
 
-                println!("Updating resolution: {}, {}", width, height);
+pub fn text_input() -> bool {
+    unsafe {
 
-                ENGINE.as_mut().unwrap().update_resolution(width, height);
-            }
+        if ENGINE.is_none() {
+            panic!("Cannot get text input when ENGINE is not initialized");
         }
 
-        _ => {}
+        ENGINE.as_ref().unwrap().text_input
+
     }
+}
 
+// subscribes the handler on the engine bus, unlike subscribe_event! it can be removed again
+// with unsubscribe or clear_handlers
+pub fn subscribe<E: Event + 'static>(handler: fn(&mut E)) -> SubscriptionId {
+    add_handler(handler, None, 0)
 }
 
-pub fn init() {
+// higher priorities run first, a handler cancelling the event stops the ones after it. ui
+// handlers can take a click this way before gameplay sees it
+pub fn subscribe_with_priority<E: Event + 'static>(handler: fn(&mut E), priority: i32) -> SubscriptionId {
+    add_handler(handler, None, priority)
+}
 
-    unsafe {
-        ENGINE.as_mut().unwrap().init();
+// subscribes the handler until the scene is left, for handlers that assume the scene is current
+pub fn subscribe_in_scene<E: Event + 'static>(scene: &str, handler: fn(&mut E)) -> SubscriptionId {
+    add_handler(handler, Some(String::from(scene)), 0)
+}
+
+fn add_handler<E: Event + 'static>(handler: fn(&mut E), scene: Option<String>, priority: i32) -> SubscriptionId {
+
+    let (id, dispatcher) = unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot subscribe when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().handlers.add(handler, scene, priority)
+    };
+
+    if dispatcher {
+        subscribe_event!("engine", dispatch_handlers::<E>);
     }
 
-    subscribe_event!("engine", change_scene_handler);
-    subscribe_event!("engine", action_event_handler);
+    id
+}
 
+// runs the handler for ActionEvents carrying Action::Custom with the tag, removed with
+// unsubscribe like other handlers
+pub fn subscribe_action(tag: &str, handler: fn(&mut ActionEvent)) -> SubscriptionId {
     unsafe {
-        ENGINE.as_mut().unwrap().environment.scene_manager.render_scene(String::from("default"));
+
+        if ENGINE.is_none() {
+            panic!("Cannot subscribe when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().handlers.add_action(tag, handler, None, 0)
     }
 }
 
-pub fn do_frame() {
+// subscribes the handler until the returned guard is dropped
+pub fn subscribe_scoped<E: Event + 'static>(handler: fn(&mut E)) -> HandlerGuard {
+    HandlerGuard::new(subscribe(handler))
+}
 
+// removes a handler added with subscribe, false when it is gone already. does nothing once
+// the engine is gone, so guards can outlive it
+pub fn unsubscribe(id: SubscriptionId) -> bool {
     unsafe {
+        match ENGINE.as_mut() {
+            Some(engine) => engine.handlers.remove(id),
+            None => false
+        }
+    }
+}
 
-        if ENGINE.as_mut().is_none() {
-            panic!("Cannot do frame when ENGINE is not initialized");
+// removes every handler added with subscribe, the engine handlers stay
+pub fn clear_handlers() {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot clear handlers when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().handlers.clear();
+
+    }
+}
+
+// subscribed once per event type, calls the handlers registered for it by priority until one cancels
+fn dispatch_handlers<E: Event + 'static>(event: &mut E) {
+
+    let handlers = unsafe {
+        match ENGINE.as_ref() {
+            Some(engine) => engine.handlers.handlers::<E>(),
+            None => return
         }
+    };
+
+    for handler in handlers {
 
-        ENGINE.as_mut().unwrap().renderer.do_render_cycle();
+        if event.cancelled() {
+            break;
+        }
 
+        handler(event);
     }
+}
+
+// skip updating and rendering frames while the window is iconified
+pub fn set_pause_when_iconified(enabled: bool) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set pause when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().pause_when_iconified = enabled;
 
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::*;
+// draw only objects in one of the layers of the mask, see scene::object::LAYER_WORLD
+pub fn set_layer_mask(mask: u32) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set layer mask when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.set_layer_mask(mask);
+
+    }
+}
+
+// draw a world space line for the next frame
+pub fn debug_line(start: Vec3, end: Vec3, color: u32) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot draw debug line when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.debug_line(start, end, color);
+
+    }
+}
+
+// draw the edges of an axis aligned box for the next frame
+pub fn debug_aabb(min: Vec3, max: Vec3, color: u32) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot draw debug box when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.debug_aabb(min, max, color);
+
+    }
+}
+
+// draw the axes of a transform for the next frame
+pub fn debug_axes(transform: Mat4) {
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot draw debug axes when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.debug_axes(transform);
+
+    }
+}
+
+// set shader used for grid, axes and debug lines. the engine ships none, without it the lines are
+// not drawn and an EngineErrorEvent says so once
+pub fn set_debug_shader(id: i32) -> Result<(), XgError> {
+
+    let shader = get_shader(id)?;
+
+    unsafe {
+        ENGINE.as_mut().unwrap().renderer.set_debug_shader(shader);
+    }
+
+    Ok(())
+}
+
+// create scene in engine environment
+pub fn create_scene(name: String) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot create scene when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().environment.create_scene(name);
+
+    }
+
+}
+
+// get scene
+pub fn get_scene(name: String) -> Result<Rc<RefCell<Scene>>, XgError> {
+
+    unsafe {
+
+        match ENGINE.as_ref() {
+            Some(engine) => engine.environment.get_scene(name),
+            None => Err(XgError::NotInitialized)
+        }
+
+    }
+
+}
+
+// transition used when changing scenes, instant by default
+pub fn set_transition(transition: Transition) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot set transition when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().environment.scene_manager.set_transition(transition);
+
+    }
+
+}
+
+// shader blending the outgoing scene during a fade
+pub fn set_transition_shader(id: i32) -> Result<(), XgError> {
+
+    let shader = get_shader(id)?;
+
+    unsafe {
+        ENGINE.as_mut().unwrap().renderer.set_transition_shader(shader);
+    }
+
+    Ok(())
+}
+
+// name of the current scene
+pub fn current_scene_name() -> String {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot get scene when ENGINE is not initialized");
+        }
+
+        ENGINE.as_ref().unwrap().environment.current_scene.borrow().name.clone()
+
+    }
+
+}
+
+// names of all scenes, sorted
+pub fn scene_names() -> Vec<String> {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot get scenes when ENGINE is not initialized");
+        }
+
+        ENGINE.as_ref().unwrap().environment.scene_manager.scene_names()
+
+    }
+
+}
+
+// current scene. the engine keeps the scene alive, so borrow it for the task at hand instead of
+// storing the Rc: a clone kept in a scene object or a handler's static state keeps the scene
+// alive after it is removed, and one stored inside the scene itself is a cycle that never frees.
+// store current_scene_weak instead and upgrade it when needed
+pub fn current_scene() -> Result<Rc<RefCell<Scene>>, XgError> {
+
+    unsafe {
+
+        match ENGINE.as_ref() {
+            Some(engine) => Ok(Rc::clone(&engine.environment.current_scene)),
+            None => Err(XgError::NotInitialized)
+        }
+
+    }
+
+}
+
+// weak reference to the current scene, for storing anywhere without keeping the scene alive.
+// upgrade gives none once the engine dropped the scene
+pub fn current_scene_weak() -> Result<Weak<RefCell<Scene>>, XgError> {
+    current_scene().map(|scene| Rc::downgrade(&scene))
+}
+
+// draws the named scenes over the current scene in the given order, like a ui scene with
+// LayerProjection::Screen over the world. scene changes only replace the current scene, the
+// overlays stay until set again. see Renderer::set_scene_layers for the depth handling
+pub fn set_overlay_scenes(overlays: &[(&str, LayerProjection)]) -> Result<(), XgError> {
+
+    let mut layers = vec![SceneLayer::new(current_scene()?, LayerProjection::Perspective)];
+
+    for (name, projection) in overlays {
+        layers.push(SceneLayer::new(get_scene(String::from(*name))?, *projection));
+    }
+
+    unsafe {
+        ENGINE.as_mut().unwrap().renderer.set_scene_layers(layers);
+    }
+
+    Ok(())
+}
+
+// saves the camera of the current scene under the name
+pub fn save_camera_bookmark(name: &str) -> Result<(), XgError> {
+    current_scene()?.borrow_mut().save_camera_bookmark(name);
+    Ok(())
+}
+
+// moves the camera of the current scene to the bookmark, eased over duration seconds
+// or instantly for 0
+pub fn goto_camera_bookmark(name: &str, duration: f32) -> Result<(), XgError> {
+    current_scene()?.borrow_mut().goto_camera_bookmark(name, duration)
+}
+
+// add shader
+pub fn add_shader(shader: Box<dyn ShaderContainer>) -> i32 {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot add shader when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().shader_manager.add_shader(shader)
+    }
+
+}
+
+// add shader, an identical shader added this way before is reused and its id returned
+pub fn add_shader_dedup(shader: Box<dyn ShaderContainer>) -> i32 {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot add shader when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().shader_manager.add_shader_dedup(shader)
+    }
+
+}
+
+// shader binaries for the renderer bgfx selected, loaded from the matching subdirectory of shader_dir
+pub fn add_bgfx_shader_auto(shader_dir: &str) -> std::io::Result<i32> {
+
+    unsafe {
+
+        match ENGINE.as_mut() {
+            Some(engine) => engine.shader_manager.add_bgfx_shader_auto(shader_dir),
+            None => Err(XgError::NotInitialized.into())
+        }
+    }
+
+}
+
+// built-in shader for ColoredSceneObject, registered on first use. binaries are only built in for
+// metal and opengl, other renderers get XgError::NoBuiltinShader
+#[cfg(feature = "builtin-shaders")]
+pub fn default_colored_shader() -> Result<i32, XgError> {
+
+    unsafe {
+
+        match ENGINE.as_mut() {
+            Some(engine) => engine.shader_manager.default_colored(),
+            None => Err(XgError::NotInitialized)
+        }
+    }
+
+}
+
+// add shader under a unique name
+pub fn add_named_shader(name: &str, shader: Box<dyn ShaderContainer>) -> Result<i32, XgError> {
+
+    unsafe {
+
+        match ENGINE.as_mut() {
+            Some(engine) => engine.shader_manager.add_named_shader(name, shader),
+            None => Err(XgError::NotInitialized)
+        }
+    }
+
+}
+
+// get shader by the name it was registered under
+pub fn get_shader_named(name: &str) -> Result<Rc<RefCell<Box<dyn ShaderContainer>>>, XgError> {
+
+    unsafe {
+
+        let engine = match ENGINE.as_ref() {
+            Some(engine) => engine,
+            None => return Err(XgError::NotInitialized)
+        };
+
+        match engine.shader_manager.get_shader_by_name(name) {
+            Some(shader) => Ok(shader),
+            None => Err(XgError::NamedShaderNotFound(String::from(name)))
+        }
+
+    }
+
+}
+
+// reload a shader created from files
+pub fn reload_shader(id: i32) -> std::io::Result<()> {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot reload shader when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().shader_manager.reload(id)
+    }
+
+}
+
+// reload all shaders created from files, returns how many were reloaded
+pub fn reload_shaders() -> usize {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot reload shaders when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().shader_manager.reload_all()
+    }
+
+}
+
+// remove shader, shaders still used by scene objects are kept
+pub fn remove_shader(id: i32) -> Option<Box<dyn ShaderContainer>> {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot remove shader when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().shader_manager.remove_shader(id)
+    }
+
+}
+
+// snapshot of every registered shader, for debug overlays and dumps
+pub fn shader_info() -> Result<Vec<ShaderInfo>, XgError> {
+
+    unsafe {
+
+        match ENGINE.as_ref() {
+            Some(engine) => Ok(engine.shader_manager.describe_all()),
+            None => Err(XgError::NotInitialized)
+        }
+
+    }
+
+}
+
+// get shader
+pub fn get_shader(id: i32) -> Result<Rc<RefCell<Box<dyn ShaderContainer>>>, XgError> {
+
+    unsafe {
+
+        let engine = match ENGINE.as_ref() {
+            Some(engine) => engine,
+            None => return Err(XgError::NotInitialized)
+        };
+
+        match engine.shader_manager.get_shader(id) {
+            Some(shader) => Ok(shader),
+            None => Err(XgError::ShaderNotFound(id))
+        }
+
+    }
+
+}
+
+fn change_scene_handler(event: &mut ChangeSceneEvent) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot change event when RENDERER is not initialized");
+        }
+
+        info!("Changing scene");
+
+        let engine = ENGINE.as_mut().unwrap();
+
+        // the environment still points at the scene being left
+        let previous = engine.environment.current_scene.borrow().name.clone();
+        let next = event.scene.borrow().name.clone();
+
+        engine.renderer.set_scene_with_transition(Rc::clone(&event.scene), event.transition);
+
+        if previous == next {
+            return;
+        }
+
+        engine.handlers.remove_scene(&previous);
+
+        let mut exit = SceneExitEvent::new(&previous);
+        dispatch_event!("engine", &mut exit);
+
+        let mut enter = SceneEnterEvent::new(&next);
+        dispatch_event!("engine", &mut enter);
+
+    }
+}
+
+fn window_state_handler(event: &mut WindowStateEvent) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot change window state when ENGINE is not initialized");
+        }
+
+        let engine = ENGINE.as_mut().unwrap();
+
+        match event.state {
+            WindowState::Iconified(iconified) => engine.iconified = iconified,
+            WindowState::Restored => engine.iconified = false,
+            WindowState::Focused(_) => {}
+        }
+    }
+}
+
+fn refresh_handler(_event: &mut RefreshEvent) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot refresh when ENGINE is not initialized");
+        }
+
+        ENGINE.as_mut().unwrap().renderer.refresh();
+    }
+}
+
+// shader programs live in their containers, unloaded ones are loaded again when drawn next
+fn clear_handler(_event: &mut ClearEvent) {
+
+    unsafe {
+
+        if ENGINE.is_none() {
+            panic!("Cannot clear gpu caches when ENGINE is not initialized");
+        }
+
+        let engine = ENGINE.as_mut().unwrap();
+
+        for (_, shader) in engine.shader_manager.iter() {
+            shader.borrow_mut().unload();
+        }
+
+        engine.renderer.clear_caches();
+    }
+}
+
+fn action_event_handler(event: &mut ActionEvent) {
+
+    match event.action {
+
+        Action::ChangeScene(ref scene) => {
+
+            unsafe {
+
+                match ENGINE.as_mut().unwrap().environment.render_scene(scene.clone()) {
+                    Ok(()) => {},
+                    Err(XgError::SceneChangeCancelled(reason)) => info!("Scene change cancelled: {}", reason),
+                    Err(e) => report_error(ErrorSource::Scene, format!("Failed to change scene: {}", e), true)
+                }
+
+            }
+
+        },
+
+        Action::ViewPortUpdate(eye, at, up, ref scene) => {
+
+            let environment = unsafe { &ENGINE.as_ref().unwrap().environment };
+
+            let scene = match scene {
+                Some(name) => environment.get_scene(name.clone()),
+                None => Ok(Rc::clone(&environment.current_scene))
+            };
+
+            match scene {
+                Ok(scene) => {
+
+                    scene.borrow_mut().camera = RenderView::new(eye, at, up);
+
+                    let name = scene.borrow().name.clone();
+
+                    let mut event = ViewPortUpdatedEvent::new(name, eye, at, up);
+                    dispatch_event!("engine", &mut event);
+                },
+                // the scene lookup already reported the error
+                Err(e) => error!("Failed to update viewport: {}", e)
+            }
+        },
+
+        Action::UpdateResolution(width, height) => {
+            // the window reports its size from polling and from the framebuffer callback,
+            // mostly with the same value
+            let changed = unsafe { ENGINE.as_mut().unwrap().update_resolution(width, height) };
+
+            if changed {
+
+                info!("Updating resolution: {}, {}", width, height);
+
+                let mut event = ResolutionChangedEvent::new(width, height);
+                dispatch_event!("engine", &mut event);
+            }
+        }
+
+        Action::UpdatePerspective(perspective) => {
+            unsafe {
+
+                info!("Updating perspective");
+
+                ENGINE.as_mut().unwrap().renderer.update_perspective(perspective);
+            }
+        }
+
+        Action::SetDebug(debug) => {
+            unsafe {
+                ENGINE.as_mut().unwrap().renderer.do_debug(debug);
+            }
+        }
+
+        Action::SetClearColor(rgba) => {
+            unsafe {
+                ENGINE.as_ref().unwrap().environment.current_scene.borrow_mut().clear_color = rgba;
+            }
+        }
+
+        Action::SetVsync(enabled) => {
+            unsafe {
+
+                info!("Setting vsync: {}", enabled);
+
+                ENGINE.as_mut().unwrap().renderer.set_vsync(enabled);
+            }
+        }
+
+        Action::SetWindowTitle(ref title) => {
+            unsafe {
+                ENGINE.as_mut().unwrap().window_requests.push(WindowRequest::Title(title.clone()));
+            }
+        }
+
+        Action::SetWindowSize(width, height) => {
+            unsafe {
+                ENGINE.as_mut().unwrap().window_requests.push(WindowRequest::Size(width, height));
+            }
+        }
+
+        Action::SetCursorMode(mode) => {
+            unsafe {
+                ENGINE.as_mut().unwrap().window_requests.push(WindowRequest::CursorMode(mode));
+            }
+        }
+
+        Action::Custom(ref tag, _) => {
+
+            let handlers = unsafe { ENGINE.as_ref().unwrap().handlers.action_handlers(tag) };
+
+            for handler in handlers {
+
+                if event.cancelled() {
+                    break;
+                }
+
+                handler(event);
+            }
+        }
+    }
+
+}
+
+// initializes the renderer and starts in the scene the engine was created with
+pub fn init() -> Result<(), XgError> {
+
+    let name = unsafe {
+        match ENGINE.as_ref() {
+            Some(engine) => engine.environment.current_scene.borrow().name.clone(),
+            None => return Err(XgError::NotInitialized)
+        }
+    };
+
+    init_with_scene(&name)
+}
+
+// initializes the renderer and starts in the named scene, which has to exist already
+pub fn init_with_scene(name: &str) -> Result<(), XgError> {
+
+    unsafe {
+
+        let engine = match ENGINE.as_mut() {
+            Some(engine) => engine,
+            None => return Err(XgError::NotInitialized)
+        };
+
+        engine.environment.get_scene(String::from(name))?;
+
+        engine.init()?;
+    }
+
+    // the environment already starts in the scene, a cancelled change keeps it
+    match unsafe { ENGINE.as_mut().unwrap().environment.render_scene(String::from(name)) } {
+        Err(XgError::SceneChangeCancelled(reason)) => {
+            info!("Initial scene change cancelled: {}", reason);
+            Ok(())
+        },
+        result => result
+    }
+}
+
+// dispatches RefreshEvent, the renderer resets its surface on the next frame. for hot reloading
+// of anything the renderer keeps sized to the window
+pub fn refresh() {
+
+    let mut event = RefreshEvent::new();
+
+    dispatch_event!("engine", &mut event);
+}
+
+// dispatches ClearEvent, every cached gpu resource is dropped and rebuilt on the next frame.
+// the standard invalidation for hot reloading shaders and textures
+pub fn clear_gpu_caches() {
+
+    let mut event = ClearEvent::new();
+
+    dispatch_event!("engine", &mut event);
+}
+
+// logs a non-fatal error and dispatches EngineErrorEvent for it. without an engine there is no
+// bus to dispatch on, the error is only logged then
+pub(crate) fn report_error(source: ErrorSource, message: String, recoverable: bool) {
+
+    error!("{}", message);
+
+    if unsafe { ENGINE.is_none() } {
+        return;
+    }
+
+    let mut event = EngineErrorEvent::new(source, message, recoverable);
+
+    dispatch_event!("engine", &mut event);
+}
+
+// dispatches ShutdownEvent, false when a handler cancelled it to keep running
+pub(crate) fn confirm_shutdown() -> bool {
+
+    let mut event = ShutdownEvent::new();
+
+    dispatch_event!("engine", &mut event);
+
+    if let Some(reason) = event.get_cancelled_reason() {
+        info!("Shutdown cancelled: {}", reason);
+    }
+
+    !event.cancelled()
+}
+
+pub fn do_frame() -> Result<(), RendererError> {
+
+    unsafe {
+
+        if ENGINE.as_mut().is_none() {
+            panic!("Cannot do frame when ENGINE is not initialized");
+        }
+
+        let engine = ENGINE.as_mut().unwrap();
+
+        let result = engine.do_frame();
+        let errors = engine.renderer.take_errors();
+
+        // dispatched once the renderer is done, so handlers can reload shaders or touch the scene
+        for mut event in errors {
+            dispatch_event!("engine", &mut event);
+        }
+
+        result
+
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::sync::{Mutex, MutexGuard};
+    use glam::{IVec2, Vec2};
+    use crate::renderer::{NullRenderer, NullRendererState};
+    use crate::scene::chunk::Chunk;
+    use crate::scene::bounds::Aabb;
+    use crate::scene::object::{ColoredSceneObject, ColoredVertex, LAYER_DEBUG, LAYER_UI, LAYER_WORLD, ObjectTypes, SceneObject, test_shaders};
+    use super::*;
+    use crate::*;
+
+    // the engine is a global, tests touching it must not run in parallel
+    static ENGINE_LOCK: Mutex<()> = Mutex::new(());
+
+    fn null_engine() -> (MutexGuard<'static, ()>, Rc<RefCell<NullRendererState>>) {
+
+        let guard = match ENGINE_LOCK.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        let renderer = NullRenderer::new(RenderPerspective::new(1280, 720, 60.0, 0.1, 100.0));
+        let state = renderer.state();
+
+        create_engine(Box::new(renderer), DEFAULT_SCENE);
+        init().unwrap();
+
+        (guard, state)
+    }
+
+    #[test]
+    fn init_sets_default_scene_test() {
+
+        let (_guard, state) = null_engine();
+
+        assert_eq!(state.borrow().init_calls, 1);
+        assert!(renderer_initialized());
+        assert_eq!(state.borrow().scenes, vec![String::from("default")]);
+
+        do_frame().unwrap();
+        do_frame().unwrap();
+
+        assert_eq!(state.borrow().frames, 2);
+    }
+
+    #[test]
+    fn frame_count_test() {
+
+        let (_guard, _state) = null_engine();
+
+        assert_eq!(frame_count(), 0);
+
+        let started = elapsed_seconds();
+
+        for _ in 0..3 {
+            do_frame().unwrap();
+        }
+
+        assert_eq!(frame_count(), 3);
+        assert!(elapsed_seconds() >= started);
+    }
+
+    #[test]
+    fn current_scene_weak_test() {
+
+        let (_guard, _state) = null_engine();
+
+        let weak = current_scene_weak().unwrap();
+
+        assert_eq!(weak.upgrade().unwrap().borrow().name, "default");
+
+        // the environment and the renderer hold the only strong references
+        unsafe {
+            ENGINE = None;
+        }
+
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn overlay_scenes_test() {
+
+        let (_guard, state) = null_engine();
+
+        let chunk = |objects: usize| {
+
+            let chunk = Chunk::new(IVec2::new(0, 0));
+
+            for _ in 0..objects {
+                chunk.add_object(Box::new(ColoredSceneObject::new(Box::new([]), Box::new([]), test_shaders(), Vec3::ZERO)));
+            }
+
+            chunk
+        };
+
+        current_scene().unwrap().borrow_mut().add_chunk(chunk(2), Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        create_scene(String::from("hud"));
+        get_scene(String::from("hud")).unwrap().borrow_mut().add_chunk(chunk(1), Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        assert!(matches!(set_overlay_scenes(&[("missing", LayerProjection::Screen)]), Err(XgError::SceneNotFound(_))));
+
+        set_overlay_scenes(&[("hud", LayerProjection::Screen)]).unwrap();
+
+        assert_eq!(state.borrow().layers, vec![vec![String::from("default"), String::from("hud")]]);
+
+        // both layers are drawn
+        do_frame().unwrap();
+        assert_eq!(state.borrow().drawn_objects, 3);
+
+        set_overlay_scenes(&[]).unwrap();
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().drawn_objects, 2);
+    }
+
+    #[test]
+    fn layer_mask_test() {
+
+        let (_guard, state) = null_engine();
+
+        let object = |layer: u32| {
+            let mut object = ColoredSceneObject::new(Box::new([]), Box::new([]), test_shaders(), Vec3::ZERO);
+            object.layer = layer;
+            Box::new(object)
+        };
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(object(LAYER_WORLD));
+        chunk.add_object(object(LAYER_UI));
+        chunk.add_object(object(LAYER_WORLD | LAYER_DEBUG));
+
+        current_scene().unwrap().borrow_mut().add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().drawn_objects, 3);
+
+        // ui layer off
+        set_layer_mask(LAYER_WORLD | LAYER_DEBUG);
+        do_frame().unwrap();
+        assert_eq!(state.borrow().drawn_objects, 2);
+
+        set_layer_mask(LAYER_DEBUG);
+        do_frame().unwrap();
+        assert_eq!(state.borrow().drawn_objects, 1);
+    }
+
+    #[test]
+    fn render_stats_test() {
+
+        let (_guard, _state) = null_engine();
+
+        // quad of two triangles
+        let quad = || {
+            let vertices: Box<[ColoredVertex]> = (0..4).map(|_| ColoredVertex { coordinates: Vec3::ZERO, color_rgba: 0xffffffff }).collect();
+            Box::new(ColoredSceneObject::new(vertices, Box::new([0, 1, 2, 0, 2, 3]), test_shaders(), Vec3::ZERO))
+        };
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(quad());
+        chunk.add_object(quad());
+        chunk.add_object(quad());
+
+        current_scene().unwrap().borrow_mut().add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        do_frame().unwrap();
+
+        let stats = render_stats();
+
+        assert_eq!(stats.num_draw_calls, 3);
+        assert_eq!(stats.vertices, 12);
+        assert_eq!(stats.triangles, 6);
+        assert_eq!(stats.chunks_visited, 1);
+        assert!(stats.frame_ms >= 0.0);
+    }
+
+    #[test]
+    fn non_indexed_draw_test() {
+
+        let (_guard, _state) = null_engine();
+
+        let vertices: Box<[ColoredVertex]> = Box::new([
+            ColoredVertex { coordinates: Vec3::ZERO, color_rgba: 0xff0000ff },
+            ColoredVertex { coordinates: Vec3::X, color_rgba: 0xff00ff00 },
+            ColoredVertex { coordinates: Vec3::Y, color_rgba: 0xffff0000 }
+        ]);
+
+        let triangle = ColoredSceneObject::new(vertices, Box::new([]), test_shaders(), Vec3::ZERO);
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(Box::new(triangle));
+
+        current_scene().unwrap().borrow_mut().add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        do_frame().unwrap();
+
+        let stats = render_stats();
+        assert_eq!((stats.num_draw_calls, stats.vertices, stats.triangles), (1, 3, 1));
+    }
+
+    #[test]
+    fn custom_initial_scene_test() {
+
+        let _guard = match ENGINE_LOCK.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        let renderer = NullRenderer::new(RenderPerspective::new(1280, 720, 60.0, 0.1, 100.0));
+        let state = renderer.state();
+
+        create_engine(Box::new(renderer), "menu");
+
+        assert_eq!(init_with_scene("typo"), Err(XgError::SceneNotFound(String::from("typo"))));
+        assert_eq!(state.borrow().init_calls, 0);
+        assert!(!renderer_initialized());
+
+        init().unwrap();
+
+        assert_eq!(current_scene_name(), "menu");
+        assert_eq!(scene_names(), vec![String::from("menu")]);
+        assert_eq!(state.borrow().scenes, vec![String::from("menu")]);
+    }
+
+    #[test]
+    fn change_scene_test() {
+
+        let (_guard, state) = null_engine();
+
+        create_scene(String::from("next"));
+
+        let mut event = ActionEvent::new(Action::ChangeScene(String::from("next")));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(state.borrow().scenes.last().unwrap(), "next");
+        assert_eq!(current_scene().unwrap().borrow().name, "next");
+    }
+
+    #[test]
+    fn scene_names_test() {
+
+        let (_guard, _state) = null_engine();
+
+        create_scene(String::from("menu"));
+        create_scene(String::from("level"));
+
+        assert_eq!(scene_names(), vec![String::from("default"), String::from("level"), String::from("menu")]);
+        assert_eq!(current_scene_name(), "default");
+
+        let mut event = ActionEvent::new(Action::ChangeScene(String::from("level")));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(current_scene_name(), "level");
+    }
+
+    #[test]
+    fn change_scene_transition_test() {
+
+        let (_guard, state) = null_engine();
+
+        create_scene(String::from("next"));
+        set_transition(Transition::Fade(1.5));
+
+        let mut event = ActionEvent::new(Action::ChangeScene(String::from("next")));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(state.borrow().transitions.last(), Some(&Transition::Fade(1.5)));
+    }
+
+    #[test]
+    fn change_to_missing_scene_test() {
+
+        let (_guard, state) = null_engine();
+
+        let mut event = ActionEvent::new(Action::ChangeScene(String::from("typo")));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(current_scene_name(), "default");
+        assert_eq!(state.borrow().scenes, vec![String::from("default")]);
+    }
+
+    #[test]
+    fn update_resolution_test() {
+
+        let (_guard, state) = null_engine();
+
+        let mut event = ActionEvent::new(Action::UpdateResolution(800, 600));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(state.borrow().resolutions, vec![(800, 600)]);
+
+        let perspective = unsafe { ENGINE.as_ref().unwrap().renderer.get_perspective() };
+        assert_eq!((perspective.width, perspective.height), (800, 600));
+    }
+
+    #[test]
+    fn zero_resolution_test() {
+
+        let (_guard, state) = null_engine();
+
+        let mut event = ActionEvent::new(Action::UpdateResolution(0, 0));
+        dispatch_event!("engine", &mut event);
+
+        // the renderer never sees an empty surface
+        assert_eq!(state.borrow().resolutions, vec![(1, 1)]);
+
+        let perspective = unsafe { ENGINE.as_ref().unwrap().renderer.get_perspective() };
+        assert!(perspective.aspect().is_finite());
+
+        // nothing is rendered until the window has a size again
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 0);
+
+        let mut event = ActionEvent::new(Action::UpdateResolution(800, 0));
+        dispatch_event!("engine", &mut event);
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 0);
+        assert_eq!(state.borrow().resolutions, vec![(1, 1), (800, 1)]);
+
+        let mut event = ActionEvent::new(Action::UpdateResolution(800, 600));
+        dispatch_event!("engine", &mut event);
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 1);
+    }
+
+    static RESOLUTION_CHANGES: Mutex<Vec<(u32, u32)>> = Mutex::new(Vec::new());
+
+    fn resolution_changed_handler(event: &mut ResolutionChangedEvent) {
+        RESOLUTION_CHANGES.lock().unwrap().push((event.width, event.height));
+    }
+
+    #[test]
+    fn resolution_changed_test() {
+
+        let (_guard, state) = null_engine();
+
+        RESOLUTION_CHANGES.lock().unwrap().clear();
+        subscribe_event!("engine", resolution_changed_handler);
+
+        for (width, height) in [(800, 600), (800, 600), (1024, 768), (1024, 768)] {
+            let mut event = ActionEvent::new(Action::UpdateResolution(width, height));
+            dispatch_event!("engine", &mut event);
+        }
+
+        // identical resolutions neither reset the renderer nor fire the event
+        assert_eq!(state.borrow().resolutions, vec![(800, 600), (1024, 768)]);
+        assert_eq!(*RESOLUTION_CHANGES.lock().unwrap(), vec![(800, 600), (1024, 768)]);
+    }
+
+    static VIEWPORT_UPDATES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn viewport_updated_handler(event: &mut ViewPortUpdatedEvent) {
+        VIEWPORT_UPDATES.lock().unwrap().push(event.scene.clone());
+    }
+
+    #[test]
+    fn viewport_update_test() {
+
+        let (_guard, _state) = null_engine();
+
+        VIEWPORT_UPDATES.lock().unwrap().clear();
+        subscribe_event!("engine", viewport_updated_handler);
+
+        create_scene(String::from("level"));
+
+        let mut event = ActionEvent::new(Action::ViewPortUpdate(Vec3::new(0.0, 2.0, -10.0), Vec3::ZERO, Vec3::Y, None));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(current_scene().unwrap().borrow().camera.eye, Vec3::new(0.0, 2.0, -10.0));
+
+        // a named scene is moved without switching to it
+        let mut event = ActionEvent::new(Action::ViewPortUpdate(Vec3::X, Vec3::ZERO, Vec3::Y, Some(String::from("level"))));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(current_scene_name(), "default");
+        assert_eq!(current_scene().unwrap().borrow().camera.eye, Vec3::new(0.0, 2.0, -10.0));
+        assert_eq!(get_scene(String::from("level")).unwrap().borrow().camera.eye, Vec3::X);
+
+        // missing scenes are logged and nothing is dispatched
+        let mut event = ActionEvent::new(Action::ViewPortUpdate(Vec3::X, Vec3::ZERO, Vec3::Y, Some(String::from("typo"))));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(*VIEWPORT_UPDATES.lock().unwrap(), vec![String::from("default"), String::from("level")]);
+    }
+
+    static TOGGLES: Mutex<Vec<bool>> = Mutex::new(Vec::new());
+
+    fn toggle_handler(event: &mut ActionEvent) {
+        TOGGLES.lock().unwrap().push(*event.payload::<bool>().unwrap());
+    }
+
+    #[test]
+    fn custom_action_test() {
+
+        let (_guard, _state) = null_engine();
+
+        TOGGLES.lock().unwrap().clear();
+
+        let id = subscribe_action("toggle_debug", toggle_handler);
+
+        let mut event = ActionEvent::custom("toggle_debug", true);
+        dispatch_event!("engine", &mut event);
+
+        // tags nobody subscribed to are ignored
+        let mut event = ActionEvent::custom("open_inventory", false);
+        dispatch_event!("engine", &mut event);
+
+        assert!(unsubscribe(id));
+
+        let mut event = ActionEvent::custom("toggle_debug", false);
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(*TOGGLES.lock().unwrap(), vec![true]);
+    }
+
+    static LOADED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn loaded_handler(event: &mut ActionEvent) {
+        LOADED.lock().unwrap().push(event.payload::<String>().unwrap().clone());
+    }
+
+    #[test]
+    fn queued_events_test() {
+
+        let (_guard, state) = null_engine();
+
+        LOADED.lock().unwrap().clear();
+        subscribe_action("loaded", loaded_handler);
+
+        let sender = event_sender();
+
+        let loader = std::thread::spawn(move || {
+            sender.custom("loaded", String::from("stone.png"));
+            sender.action(Action::UpdateResolution(640, 480));
+            sender.custom("loaded", String::from("grass.png"));
+        });
+
+        loader.join().unwrap();
+
+        // nothing runs on the loader thread
+        assert!(LOADED.lock().unwrap().is_empty());
+        assert!(state.borrow().resolutions.is_empty());
+
+        // the next frame tick
+        assert_eq!(dispatch_queued(), 3);
+
+        assert_eq!(*LOADED.lock().unwrap(), vec![String::from("stone.png"), String::from("grass.png")]);
+        assert_eq!(state.borrow().resolutions, vec![(640, 480)]);
+
+        assert_eq!(dispatch_queued(), 0);
+    }
+
+    static INVALIDATIONS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    fn refresh_listener(_event: &mut RefreshEvent) {
+        INVALIDATIONS.lock().unwrap().push("refresh");
+    }
+
+    fn clear_listener(_event: &mut ClearEvent) {
+        INVALIDATIONS.lock().unwrap().push("clear");
+    }
+
+    #[test]
+    fn gpu_invalidation_test() {
+
+        let (_guard, state) = null_engine();
+
+        INVALIDATIONS.lock().unwrap().clear();
+        subscribe(refresh_listener);
+        subscribe(clear_listener);
+
+        refresh();
+
+        assert_eq!(state.borrow().refresh_calls, 1);
+        assert_eq!(state.borrow().clear_cache_calls, 0);
+
+        clear_gpu_caches();
+        clear_gpu_caches();
+
+        assert_eq!(state.borrow().clear_cache_calls, 2);
+
+        // hot reload features can listen for the invalidation too
+        assert_eq!(*INVALIDATIONS.lock().unwrap(), vec!["refresh", "clear", "clear"]);
+    }
+
+    static ENGINE_ERRORS: Mutex<Vec<(ErrorSource, bool)>> = Mutex::new(Vec::new());
+
+    fn error_listener(event: &mut EngineErrorEvent) {
+        ENGINE_ERRORS.lock().unwrap().push((event.source, event.recoverable));
+    }
+
+    #[test]
+    fn missing_chunk_error_test() {
+
+        let (_guard, state) = null_engine();
+
+        ENGINE_ERRORS.lock().unwrap().clear();
+        subscribe(error_listener);
+
+        // the default scene has no chunk under the camera
+        do_frame().unwrap();
+
+        assert_eq!(state.borrow().drawn_objects, 0);
+        assert_eq!(*ENGINE_ERRORS.lock().unwrap(), vec![(ErrorSource::Scene, true)]);
+
+        current_scene().unwrap().borrow_mut().add_chunk(Chunk::new(IVec2::new(0, 0)), Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        do_frame().unwrap();
+
+        assert_eq!(ENGINE_ERRORS.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn wireframe_test() {
+
+        let (_guard, state) = null_engine();
+
+        set_wireframe(true);
+        set_wireframe(false);
+
+        assert_eq!(state.borrow().wireframe, vec![true, false]);
+    }
+
+    #[test]
+    fn pause_when_iconified_test() {
+
+        let (_guard, state) = null_engine();
+
+        let mut event = WindowStateEvent::new(WindowState::Iconified(true));
+        dispatch_event!("engine", &mut event);
+
+        // pausing is opt in
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 1);
+
+        set_pause_when_iconified(true);
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 1);
+        assert_eq!(frame_count(), 1);
+
+        let mut event = WindowStateEvent::new(WindowState::Iconified(false));
+        dispatch_event!("engine", &mut event);
+
+        do_frame().unwrap();
+        assert_eq!(state.borrow().frames, 2);
+    }
+
+    static HANDLED: Mutex<u32> = Mutex::new(0);
+
+    fn counting_handler(_event: &mut InteractEvent) {
+        *HANDLED.lock().unwrap() += 1;
+    }
+
+    fn dispatch_interact() {
+        let mut event = InteractEvent::new(InteractType::Mouse());
+        dispatch_event!("engine", &mut event);
+    }
+
+    #[test]
+    fn handlers_test() {
+
+        let (_guard, state) = null_engine();
+
+        *HANDLED.lock().unwrap() = 0;
+
+        subscribe(counting_handler);
+
+        // initializing again neither doubles the engine handlers nor the subscribed ones
+        init().unwrap();
+
+        dispatch_interact();
+        assert_eq!(*HANDLED.lock().unwrap(), 1);
+
+        let mut event = ActionEvent::new(Action::UpdateResolution(640, 480));
+        dispatch_event!("engine", &mut event);
+        assert_eq!(state.borrow().resolutions, vec![(640, 480)]);
+
+        clear_handlers();
+
+        dispatch_interact();
+        assert_eq!(*HANDLED.lock().unwrap(), 1);
+
+        // scoped handlers are only called while the guard lives
+        {
+            let _handler = subscribe_scoped(counting_handler);
+            dispatch_interact();
+        }
+
+        dispatch_interact();
+        assert_eq!(*HANDLED.lock().unwrap(), 2);
+    }
+
+    static TYPED: Mutex<String> = Mutex::new(String::new());
+
+    fn text_box_handler(event: &mut InteractEvent) {
+        match event.interact {
+            InteractType::Char(character) => TYPED.lock().unwrap().push(character),
+            InteractType::Keyboard(glfw::Key::Backspace, glfw::Action::Press) => { TYPED.lock().unwrap().pop(); },
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn char_event_test() {
+
+        let (_guard, _state) = null_engine();
+
+        TYPED.lock().unwrap().clear();
+        subscribe(text_box_handler);
+
+        set_text_input(true);
+
+        // shift and the layout are already applied to typed characters
+        for character in "Zoë!".chars() {
+            let mut event = InteractEvent::new(InteractType::Char(character));
+            dispatch_event!("engine", &mut event);
+        }
+
+        let mut event = InteractEvent::new(InteractType::Keyboard(glfw::Key::Backspace, glfw::Action::Press));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(*TYPED.lock().unwrap(), "Zoë");
+    }
+
+    static SCENE_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn scene_enter_handler(event: &mut SceneEnterEvent) {
+        SCENE_EVENTS.lock().unwrap().push(format!("enter {}", event.name));
+    }
+
+    fn scene_exit_handler(event: &mut SceneExitEvent) {
+        SCENE_EVENTS.lock().unwrap().push(format!("exit {}", event.name));
+    }
+
+    #[test]
+    fn unsubscribe_test() {
+
+        let (_guard, _state) = null_engine();
+
+        *HANDLED.lock().unwrap() = 0;
+        SCENE_EVENTS.lock().unwrap().clear();
+
+        let id = subscribe(counting_handler);
+
+        dispatch_interact();
+
+        assert!(unsubscribe(id));
+        assert!(!unsubscribe(id));
+
+        dispatch_interact();
+        assert_eq!(*HANDLED.lock().unwrap(), 1);
+
+        // bound to the default scene, gone once the next scene is entered
+        subscribe_in_scene(DEFAULT_SCENE, counting_handler);
+        subscribe(scene_enter_handler);
+        subscribe(scene_exit_handler);
+
+        create_scene(String::from("next"));
+
+        let mut event = ActionEvent::new(Action::ChangeScene(String::from("next")));
+        dispatch_event!("engine", &mut event);
+
+        dispatch_interact();
+        assert_eq!(*HANDLED.lock().unwrap(), 1);
+
+        assert_eq!(*SCENE_EVENTS.lock().unwrap(), vec![String::from("exit default"), String::from("enter next")]);
+    }
+
+    static ORDER: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn handler_priority_test() {
+
+        let (_guard, _state) = null_engine();
+
+        ORDER.lock().unwrap().clear();
+
+        subscribe_with_priority(|_: &mut InteractEvent| ORDER.lock().unwrap().push("gameplay"), 0);
+        subscribe_with_priority(|_: &mut InteractEvent| ORDER.lock().unwrap().push("ui"), 100);
+        subscribe_with_priority(|_: &mut InteractEvent| ORDER.lock().unwrap().push("camera"), 50);
+
+        dispatch_interact();
+        assert_eq!(*ORDER.lock().unwrap(), vec!["ui", "camera", "gameplay"]);
+
+        // the ui takes the click, nothing below it sees the event
+        clear_handlers();
+        ORDER.lock().unwrap().clear();
+
+        subscribe_with_priority(|_: &mut InteractEvent| ORDER.lock().unwrap().push("gameplay"), 0);
+        subscribe_with_priority(|event: &mut InteractEvent| {
+            ORDER.lock().unwrap().push("ui");
+            event.cancel(Some(String::from("ui")));
+        }, 100);
+
+        dispatch_interact();
+        assert_eq!(*ORDER.lock().unwrap(), vec!["ui"]);
+    }
+
+    static SHUTDOWN_REQUESTS: Mutex<u32> = Mutex::new(0);
+
+    // asks to save first, the second attempt closes
+    fn unsaved_changes_handler(event: &mut ShutdownEvent) {
+
+        let mut requests = SHUTDOWN_REQUESTS.lock().unwrap();
+        *requests += 1;
+
+        if *requests == 1 {
+            event.cancel(Some(String::from("unsaved changes")));
+        }
+    }
+
+    #[test]
+    fn shutdown_veto_test() {
+
+        let (_guard, _state) = null_engine();
+
+        *SHUTDOWN_REQUESTS.lock().unwrap() = 0;
+
+        // nobody objects
+        assert!(confirm_shutdown());
+
+        subscribe(unsaved_changes_handler);
+
+        assert!(!confirm_shutdown());
+        assert!(confirm_shutdown());
+        assert_eq!(*SHUTDOWN_REQUESTS.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn renderer_actions_test() {
+
+        let (_guard, state) = null_engine();
+
+        let mut event = ActionEvent::new(Action::SetDebug(true));
+        dispatch_event!("engine", &mut event);
+
+        let mut event = ActionEvent::new(Action::SetVsync(true));
+        dispatch_event!("engine", &mut event);
+
+        let mut event = ActionEvent::new(Action::SetClearColor(0x336699ff));
+        dispatch_event!("engine", &mut event);
+
+        assert_eq!(state.borrow().debug, vec![true]);
+        assert_eq!(state.borrow().vsync, vec![true]);
+        assert_eq!(current_scene().unwrap().borrow().clear_color, 0x336699ff);
+
+        // the direct functions take the same path
+        set_vsync(false);
+        set_clear_color(0x000000ff);
+
+        assert_eq!(state.borrow().vsync, vec![true, false]);
+        assert_eq!(current_scene().unwrap().borrow().clear_color, 0x000000ff);
+    }
+
+    #[test]
+    fn window_requests_test() {
+
+        let (_guard, _state) = null_engine();
+
+        set_window_title("XGEngine - 60 fps");
+
+        let mut event = ActionEvent::new(Action::SetWindowSize(800, 600));
+        dispatch_event!("engine", &mut event);
+
+        set_cursor_mode(glfw::CursorMode::Normal);
+
+        // kept for the window loop in the order they were requested
+        assert_eq!(take_window_requests(), vec![
+            WindowRequest::Title(String::from("XGEngine - 60 fps")),
+            WindowRequest::Size(800, 600),
+            WindowRequest::CursorMode(glfw::CursorMode::Normal)
+        ]);
+        assert!(take_window_requests().is_empty());
+    }
+
+    #[test]
+    fn pre_init_actions_test() {
+
+        let _guard = match ENGINE_LOCK.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        let renderer = NullRenderer::new(RenderPerspective::new(1280, 720, 60.0, 0.1, 100.0));
+        let state = renderer.state();
+
+        create_engine(Box::new(renderer), DEFAULT_SCENE);
+
+        // applied although init has not run yet
+        set_debug(true);
+        set_vsync(true);
+        set_clear_color(0x336699ff);
+
+        assert_eq!(state.borrow().debug, vec![true]);
+        assert_eq!(state.borrow().vsync, vec![true]);
+        assert_eq!(current_scene().unwrap().borrow().clear_color, 0x336699ff);
+
+        init().unwrap();
+        set_debug(false);
+
+        // init does not subscribe the handlers a second time
+        assert_eq!(state.borrow().debug, vec![true, false]);
+    }
+
+    #[test]
+    fn debug_toggle_test() {
+
+        let (_guard, state) = null_engine();
+
+        set_debug(true);
+        set_debug(false);
+
+        assert_eq!(state.borrow().debug, vec![true, false]);
+    }
+
+    #[test]
+    fn lookup_errors_test() {
+
+        let (_guard, _state) = null_engine();
+
+        assert_eq!(get_scene(String::from("typo")).err(), Some(XgError::SceneNotFound(String::from("typo"))));
+        assert_eq!(get_shader(42).err(), Some(XgError::ShaderNotFound(42)));
+        assert_eq!(get_shader_named("missing").err(), Some(XgError::NamedShaderNotFound(String::from("missing"))));
+        assert_eq!(set_debug_shader(42), Err(XgError::ShaderNotFound(42)));
+    }
+
+    static UPDATE_DELTAS: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+
+    // records the deltas update_objects hands to objects
+    struct DeltaRecorder {}
+
+    impl SceneObject for DeltaRecorder {
+        fn get_type(&self) -> ObjectTypes {
+            ObjectTypes::Colored
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn bounding_box(&self) -> Aabb {
+            Aabb::new(Vec3::ZERO, Vec3::ONE)
+        }
+
+        fn update(&mut self, delta: f32) {
+            UPDATE_DELTAS.lock().unwrap().push(delta);
+        }
+    }
+
+    #[test]
+    fn frame_delta_test() {
+
+        let (_guard, _state) = null_engine();
+
+        UPDATE_DELTAS.lock().unwrap().clear();
+
+        let mut chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(Box::new(DeltaRecorder {}));
+
+        current_scene().unwrap().borrow_mut().add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        // the window loop hands over the clamped FrameEvent delta
+        set_frame_delta(0.1);
+        do_frame().unwrap();
+
+        // without it the measured time is clamped the same way
+        std::thread::sleep(Duration::from_millis(5));
+        do_frame().unwrap();
+
+        let deltas = UPDATE_DELTAS.lock().unwrap().clone();
+
+        assert_eq!(deltas[0], 0.1);
+        assert!(deltas[1] > 0.0 && deltas[1] <= DEFAULT_MAX_FRAME_DELTA);
+    }
 
 }
@@ -0,0 +1,112 @@
+use std::any::Any;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use crate::events::{Action, ActionEvent};
+
+// events another thread can hand to the engine. they have to be Send, so instead of any event
+// only actions are supported, custom ones carry whatever payload the application needs
+pub enum DeferredEvent {
+    Action(Action),
+    // tag and payload of an Action::Custom
+    Custom(String, Box<dyn Any + Send>)
+}
+
+impl DeferredEvent {
+
+    pub fn into_action_event(self) -> ActionEvent {
+        match self {
+            DeferredEvent::Action(action) => ActionEvent::new(action),
+            DeferredEvent::Custom(tag, payload) => ActionEvent::new(Action::Custom(tag, payload))
+        }
+    }
+
+}
+
+// cloneable handle for queueing events from any thread, see crate::event_sender
+#[derive(Clone)]
+pub struct EventSender {
+    sender: Sender<DeferredEvent>
+}
+
+impl EventSender {
+
+    // false when the engine the sender belongs to is gone
+    pub fn send(&self, event: DeferredEvent) -> bool {
+        self.sender.send(event).is_ok()
+    }
+
+    pub fn action(&self, action: Action) -> bool {
+        self.send(DeferredEvent::Action(action))
+    }
+
+    pub fn custom<T: Any + Send>(&self, tag: &str, payload: T) -> bool {
+        self.send(DeferredEvent::Custom(String::from(tag), Box::new(payload)))
+    }
+
+}
+
+// events queued by other threads, drained on the main thread once per frame
+pub struct EngineEventQueue {
+    sender: Sender<DeferredEvent>,
+    receiver: Receiver<DeferredEvent>
+}
+
+impl EngineEventQueue {
+
+    pub fn new() -> Self {
+
+        let (sender, receiver) = channel();
+
+        Self { sender, receiver }
+    }
+
+    pub fn sender(&self) -> EventSender {
+        EventSender {
+            sender: self.sender.clone()
+        }
+    }
+
+    // everything queued so far in the order it was sent, events sent meanwhile wait for the next drain
+    pub fn drain(&self) -> Vec<DeferredEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+}
+
+impl Default for EngineEventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_test() {
+
+        let queue = EngineEventQueue::new();
+        let sender = queue.sender();
+
+        let loader = std::thread::spawn(move || {
+            sender.action(Action::UpdateResolution(640, 480));
+            sender.custom("loaded", String::from("stone.png"));
+        });
+
+        loader.join().unwrap();
+
+        let events: Vec<ActionEvent> = queue.drain().into_iter().map(DeferredEvent::into_action_event).collect();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0].action, Action::UpdateResolution(640, 480)));
+        assert_eq!(events[1].payload::<String>().map(String::as_str), Some("stone.png"));
+
+        assert!(queue.drain().is_empty());
+
+        // the queue is gone, senders notice
+        let sender = queue.sender();
+        drop(queue);
+        assert!(!sender.custom("loaded", ()));
+    }
+
+}
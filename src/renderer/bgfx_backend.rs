@@ -0,0 +1,1241 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use bgfx_rs::bgfx;
+use bgfx_rs::bgfx::{AddArgs, Attrib, AttribType, BufferFlags, ClearFlags, FrameBuffer, IndexBuffer, Init, Memory, PlatformData, Program, ResetArgs, ResetFlags, SamplerFlags, SetViewClearArgs, StateBlendFlags, StateCullFlags, StateDepthTestFlags, StatePtFlags, StateWriteFlags, SubmitArgs, TextureFormat, Uniform, UniformType, VertexBuffer, VertexLayoutBuilder};
+use bgfx_rs::bgfx::RendererType;
+use bgfx_rs::bgfx::RendererType::Count;
+use glam::{Mat4, Vec3};
+use log::{error, info};
+use raw_window_handle::RawWindowHandle;
+#[cfg(feature = "debug-ui")]
+use crate::debug_ui::{clip_to_pixels, DebugUiFrame, image_rgba};
+use crate::events::{EngineErrorEvent, ErrorSource};
+use crate::renderer::{MAX_SCENE_LAYERS, PendingErrors, Renderer};
+use crate::renderer::error::{handle_kind, RendererError};
+use crate::renderer::debug::{aabb_vertices, axes_vertices, camera_gizmo_vertices, grid_vertices, TextDebugData, transformed_axes_vertices};
+use crate::renderer::transition::SceneFade;
+use crate::renderer::types::{LayerProjection, RenderPerspective, RenderResolution, RenderStats, RenderView, SceneLayer};
+use crate::scene::manager::Transition;
+use crate::scene::object::{BlendMode, ColoredSceneObject, ColoredVertex, CullMode, ImageTexturedVertex, LAYER_ALL, ObjectTypes, SceneObject};
+use crate::scene::particle::ParticleEmitter;
+use crate::scene::scene::Scene;
+use crate::shader::{bgfx_container, BgfxShaderVertexType, get_vertex_layout, PermutedShaderContainer, ShaderContainer, ShaderFeatures, ShaderLoadContext, UniformValue};
+
+// views are processed in id order: the bottom scene layer, the outgoing scene of a fade rendered
+// into a texture, the fade overlay drawn over the bottom layer, the scene layers above it and the
+// debug ui over everything
+const SCENE_VIEW: u16 = 0;
+const FADE_SOURCE_VIEW: u16 = 1;
+const FADE_OVERLAY_VIEW: u16 = 2;
+// view of the second layer, the ones above follow
+const LAYER_VIEW: u16 = 3;
+#[cfg(feature = "debug-ui")]
+const DEBUG_UI_VIEW: u16 = LAYER_VIEW + MAX_SCENE_LAYERS as u16 - 1;
+
+// renderer bgfx selected, set between a successful init and shutdown. bgfx crashes when
+// queried before init, so code that may run without a renderer asks here
+static ACTIVE_RENDERER: Mutex<Option<RendererType>> = Mutex::new(None);
+
+pub fn active_renderer() -> Option<RendererType> {
+    *ACTIVE_RENDERER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn set_active_renderer(renderer_type: Option<RendererType>) {
+    *ACTIVE_RENDERER.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = renderer_type;
+}
+
+// converts a gpu timer interval into milliseconds
+fn timer_to_ms(begin: i64, end: i64, frequency: i64) -> f32 {
+
+    if frequency <= 0 || end < begin {
+        return 0.0;
+    }
+
+    ((end - begin) as f64 * 1000.0 / frequency as f64) as f32
+}
+
+// vertex layout matching ColoredVertex, begun for the renderer bgfx actually selected
+fn colored_vertex_layout() -> VertexLayoutBuilder {
+    get_vertex_layout(BgfxShaderVertexType::COLORED, bgfx::get_renderer_type())
+}
+
+// layout of ParticleInstance, the i_data0 and i_data1 instance attributes of the particle shader
+fn particle_instance_layout() -> VertexLayoutBuilder {
+
+    let layout = VertexLayoutBuilder::new();
+
+    layout
+        .begin(bgfx::get_renderer_type())
+        .add(Attrib::TexCoord7, 4, AttribType::Float, AddArgs::default())
+        .add(Attrib::TexCoord6, 4, AttribType::Float, AddArgs::default())
+        .end();
+
+    layout
+}
+
+// unit quad in the xy plane every particle instance is drawn with
+fn create_particle_quad() -> (VertexBuffer, IndexBuffer) {
+
+    let vertices = [
+        ColoredVertex { coordinates: Vec3::new(-0.5, -0.5, 0.0), color_rgba: 0xffffffff },
+        ColoredVertex { coordinates: Vec3::new(0.5, -0.5, 0.0), color_rgba: 0xffffffff },
+        ColoredVertex { coordinates: Vec3::new(0.5, 0.5, 0.0), color_rgba: 0xffffffff },
+        ColoredVertex { coordinates: Vec3::new(-0.5, 0.5, 0.0), color_rgba: 0xffffffff }
+    ];
+
+    let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+    let vertex_buffer = bgfx::create_vertex_buffer(&Memory::copy(&vertices[..]), &colored_vertex_layout(), BufferFlags::empty().bits());
+    let index_buffer = bgfx::create_index_buffer(&Memory::copy(&indices[..]), BufferFlags::empty().bits());
+
+    (vertex_buffer, index_buffer)
+}
+
+// bgfx cull state for an object cull mode
+fn cull_flags(mode: CullMode) -> u64 {
+    match mode {
+        CullMode::None => 0,
+        CullMode::Clockwise => StateCullFlags::CW.bits(),
+        CullMode::CounterClockwise => StateCullFlags::CCW.bits()
+    }
+}
+
+// vertex layout of egui vertices, position in points, uv and premultiplied srgba color
+#[cfg(feature = "debug-ui")]
+fn debug_ui_vertex_layout() -> VertexLayoutBuilder {
+
+    let layout = VertexLayoutBuilder::new();
+
+    layout
+        .begin(bgfx::get_renderer_type())
+        .add(bgfx::Attrib::Position, 2, bgfx::AttribType::Float, bgfx::AddArgs::default())
+        .add(bgfx::Attrib::TexCoord0, 2, bgfx::AttribType::Float, bgfx::AddArgs::default())
+        .add(bgfx::Attrib::Color0, 4, bgfx::AttribType::Uint8, bgfx::AddArgs { normalized: true, as_int: false })
+        .end();
+
+    layout
+}
+
+// combines source and destination blend factors the way BGFX_STATE_BLEND_FUNC does
+fn blend_function(source: StateBlendFlags, destination: StateBlendFlags) -> u64 {
+    let function = source.bits() | (destination.bits() << 4);
+    function | (function << 8)
+}
+
+// color writes, depth write and blending for an object blend mode
+fn blend_state(mode: BlendMode) -> u64 {
+
+    let color = (StateWriteFlags::R
+        | StateWriteFlags::G
+        | StateWriteFlags::B
+        | StateWriteFlags::A)
+        .bits();
+
+    match mode {
+        BlendMode::Opaque => color | StateWriteFlags::Z.bits(),
+        // no depth write so objects behind a transparent one drawn later still show
+        BlendMode::Alpha => color | blend_function(StateBlendFlags::SRC_ALPHA, StateBlendFlags::INV_SRC_ALPHA),
+        BlendMode::Additive => color | blend_function(StateBlendFlags::ONE, StateBlendFlags::ONE)
+    }
+}
+
+// vertex layout declared by the object's shader, containers that are not bgfx shaders
+// fall back to the vertex type the object's vertices are built as
+fn shader_layout(shaders: &Rc<RefCell<Box<dyn ShaderContainer>>>, fallback: BgfxShaderVertexType, context: &ShaderLoadContext) -> VertexLayoutBuilder {
+
+    let shaders = shaders.deref().borrow();
+
+    let vertex_type = match bgfx_container(&**shaders) {
+        Some(shaders) => shaders.vertex_type,
+        None => fallback
+    };
+
+    get_vertex_layout(vertex_type, context.backend)
+}
+
+// loads the shader container on first use and returns its program, none when it cannot be
+// loaded so the caller skips the draw. permuted shaders return the permutation for the features.
+// a failure is reported once, the container is skipped until it is unloaded or reloaded
+fn load_program(shaders: &Rc<RefCell<Box<dyn ShaderContainer>>>, context: &ShaderLoadContext, features: ShaderFeatures, errors: &PendingErrors) -> Option<Rc<Program>> {
+
+    let mut shaders = shaders.deref().borrow_mut();
+
+    if shaders.load_failed() {
+        return None;
+    }
+
+    match program_of(&mut **shaders, context, features) {
+        Ok(program) => Some(program),
+        Err((message, recoverable)) => {
+            shaders.set_load_failed(true);
+            errors.push(ErrorSource::Shader, message, recoverable);
+            None
+        }
+    }
+}
+
+// program of the container for the features, the error tells whether reloading can fix it
+fn program_of(shaders: &mut dyn ShaderContainer, context: &ShaderLoadContext, features: ShaderFeatures) -> Result<Rc<Program>, (String, bool)> {
+
+    if let Some(permuted) = shaders.as_any_mut().downcast_mut::<PermutedShaderContainer>() {
+        return permuted.program_for(context, features).map_err(|e| (e.to_string(), true));
+    }
+
+    if !shaders.loaded() {
+        shaders.load(context).map_err(|e| (e.to_string(), true))?;
+    }
+
+    match bgfx_container(shaders).and_then(|shaders| shaders.program.clone()) {
+        Some(program) => Ok(program),
+        None => Err((String::from("Shader is not a bgfx shader"), false))
+    }
+}
+
+// sets the shader uniform defaults with the object overrides applied for the next submit
+fn apply_uniforms(shaders: &Rc<RefCell<Box<dyn ShaderContainer>>>, overrides: &HashMap<String, UniformValue>, errors: &PendingErrors) {
+
+    let shaders = shaders.deref().borrow();
+
+    if let Some(shaders) = bgfx_container(&**shaders) {
+        if let Err(e) = shaders.apply_uniforms(overrides) {
+            errors.push(ErrorSource::Shader, format!("Failed to set uniforms: {}", e), true);
+        }
+    }
+}
+
+// bgfx platform data for a window handle
+fn platform_data(handle: &RawWindowHandle) -> Result<PlatformData, RendererError> {
+
+    let mut platform_data = PlatformData::new();
+
+    match handle {
+        RawWindowHandle::Win32(handle) => {
+            platform_data.nwh = handle.hwnd
+        },
+        RawWindowHandle::AppKit(handle) => {
+            platform_data.nwh = handle.ns_window
+        },
+        RawWindowHandle::Xlib(handle) => {
+            platform_data.nwh = handle.window as *mut std::ffi::c_void;
+        },
+        RawWindowHandle::Wayland(handle) => {
+            platform_data.ndt = handle.surface
+        },
+        _ => return Err(RendererError::UnsupportedPlatform(handle_kind(handle)))
+    }
+
+    Ok(platform_data)
+}
+
+pub struct BgfxRenderer {
+    resolution: RenderResolution,
+    old_resolution: RenderResolution,
+    surface: Rc<RefCell<RawWindowHandle>>,
+    debug: Arc<Mutex<bool>>,
+    // bottom first, see Renderer::set_scene_layers
+    layers: Vec<SceneLayer>,
+    debug_data: Option<TextDebugData>,
+    perspective: Arc<Mutex<RenderPerspective>>,
+    shaders: HashMap<ObjectTypes, Program>,
+    grid: Option<(f32, f32)>,
+    axes: bool,
+    camera_gizmo: bool,
+    debug_shader: Option<Rc<RefCell<Box<dyn ShaderContainer>>>>,
+    // debug lines were asked for without a debug shader and that was reported
+    debug_shader_missing: bool,
+    // line list added through debug_line and friends, flushed every frame
+    debug_lines: Vec<ColoredVertex>,
+    // quad the particle instances share, created once bgfx is up
+    particle_quad: Option<(VertexBuffer, IndexBuffer)>,
+    stats: RenderStats,
+    frames: u64,
+    fade: Option<(Rc<RefCell<Scene>>, SceneFade)>,
+    fade_shader: Option<Rc<RefCell<Box<dyn ShaderContainer>>>>,
+    fade_target: Option<FrameBuffer>,
+    // s_texColor sampler and u_fade uniform of the fade shader
+    fade_uniforms: Option<(Uniform, Uniform)>,
+    last_cycle: Option<Instant>,
+    initialized: bool,
+    layer_mask: u32,
+    wireframe: bool,
+    vsync: bool,
+    // errors of the render cycles since the engine last took them
+    errors: PendingErrors,
+    // ui of the next frame, its shader, s_texColor sampler and the uploaded ui textures
+    #[cfg(feature = "debug-ui")]
+    debug_ui: Option<DebugUiFrame>,
+    #[cfg(feature = "debug-ui")]
+    debug_ui_shader: Option<Rc<RefCell<Box<dyn ShaderContainer>>>>,
+    #[cfg(feature = "debug-ui")]
+    debug_ui_sampler: Option<Uniform>,
+    #[cfg(feature = "debug-ui")]
+    debug_ui_textures: HashMap<egui::TextureId, bgfx::Texture>
+}
+
+impl BgfxRenderer {
+
+    // constructor
+    pub fn new(width: u32, height: u32, surface: Rc<RefCell<RawWindowHandle>>, debug: bool, perspective: RenderPerspective) -> Self {
+        Self {
+            resolution: RenderResolution::new(width, height),
+            old_resolution: RenderResolution::new(0, 0),
+            surface,
+            debug: Arc::new(Mutex::new(debug)),
+            layers: Vec::new(),
+            debug_data: None,
+            perspective: Arc::new(Mutex::new(perspective)),
+            shaders: HashMap::new(),
+            grid: None,
+            axes: false,
+            camera_gizmo: false,
+            debug_shader: None,
+            debug_shader_missing: false,
+            debug_lines: Vec::new(),
+            particle_quad: None,
+            stats: RenderStats::default(),
+            frames: 0,
+            fade: None,
+            fade_shader: None,
+            fade_target: None,
+            fade_uniforms: None,
+            last_cycle: None,
+            initialized: false,
+            layer_mask: LAYER_ALL,
+            wireframe: false,
+            vsync: false,
+            errors: PendingErrors::new(),
+            #[cfg(feature = "debug-ui")]
+            debug_ui: None,
+            #[cfg(feature = "debug-ui")]
+            debug_ui_shader: None,
+            #[cfg(feature = "debug-ui")]
+            debug_ui_sampler: None,
+            #[cfg(feature = "debug-ui")]
+            debug_ui_textures: HashMap::new()
+        }
+    }
+
+    // copies bgfx counters of the last submitted frame, none before the first frame
+    fn read_gpu_stats(&self) -> Option<RenderStats> {
+
+        if self.frames == 0 {
+            return None;
+        }
+
+        let stats = bgfx::get_stats();
+
+        if stats.is_null() {
+            return None;
+        }
+
+        let stats = unsafe { &*stats };
+
+        Some(RenderStats {
+            gpu_ms: timer_to_ms(stats.gpu_time_begin, stats.gpu_time_end, stats.gpu_timer_freq),
+            vram_used: stats.gpu_memory_used.max(0) as u64,
+            num_draw_calls: stats.num_draw,
+            num_views: stats.num_views,
+            ..self.stats
+        })
+    }
+
+    // eye, look direction and up vector of the view as debug lines for the next frame
+    pub fn draw_camera_gizmo(&mut self, view: &RenderView) {
+        self.debug_lines.extend(camera_gizmo_vertices(view, 1.0));
+    }
+
+    // bgfx debug flags for the debug text and the wireframe toggle
+    fn apply_debug_flags(&self, debug: bool) {
+
+        let mut flags = bgfx::DebugFlags::NONE;
+
+        if debug {
+            flags |= bgfx::DebugFlags::TEXT;
+        }
+
+        if self.wireframe {
+            flags |= bgfx::DebugFlags::WIREFRAME;
+        }
+
+        bgfx::set_debug(flags.bits());
+    }
+
+    // renderer state shaders loaded lazily during the draw loop are created for
+    fn load_context(&self) -> ShaderLoadContext {
+        ShaderLoadContext::new(bgfx::get_renderer_type())
+    }
+
+    // submits a single scene object to the view, positioned relative to the scene camera origin.
+    // false when nothing was submitted
+    fn submit_object(&self, view: u16, object: &mut dyn SceneObject, scene: &Scene) -> bool {
+
+        let context = self.load_context();
+
+        match object.get_type() {
+
+            ObjectTypes::Colored => {
+
+                let colored = object.as_any_mut().downcast_mut::<ColoredSceneObject>().unwrap();
+
+                // loaded first, multi backend shaders only know their layout once loaded
+                let program = match load_program(&colored.shaders, &context, colored.shader_features | scene.shader_features, &self.errors) {
+                    Some(program) => program,
+                    None => return false
+                };
+
+                let layout = shader_layout(&colored.shaders, BgfxShaderVertexType::COLORED, &context);
+
+                let vertex_buffer = unsafe {
+                    let memory = Memory::reference(&colored.vertices);
+                    bgfx::create_vertex_buffer(&memory, &layout, BufferFlags::empty().bits())
+                };
+
+                // objects without indices are drawn as a plain triangle list
+                let index_buffer = if colored.indices.is_empty() {
+                    None
+                } else {
+                    let memory = unsafe { Memory::reference(&colored.indices) };
+                    Some(bgfx::create_index_buffer(&memory, BufferFlags::empty().bits()))
+                };
+
+                let state = blend_state(colored.blend_mode)
+                    | StateDepthTestFlags::LESS.bits()
+                    | cull_flags(colored.cull_mode);
+
+                let position = scene.render_position(&*colored, colored.coordinates);
+
+                let transform = Mat4::from_scale_rotation_translation(colored.scale, colored.rotation, position);
+
+                bgfx::set_transform(&transform.to_cols_array(), 1);
+                bgfx::set_vertex_buffer(0, &vertex_buffer, 0, std::u32::MAX);
+
+                if let Some(index_buffer) = &index_buffer {
+                    bgfx::set_index_buffer(index_buffer, 0, std::u32::MAX);
+                }
+
+                bgfx::set_state(state, 0);
+
+                let uniforms = colored.staged_uniforms(colored.shaders.deref().borrow().uniforms());
+                apply_uniforms(&colored.shaders, &uniforms, &self.errors);
+
+                bgfx::submit(view, program.as_ref(), SubmitArgs::default());
+
+                true
+            }
+
+            ObjectTypes::Particles => {
+
+                let emitter = object.as_any_mut().downcast_mut::<ParticleEmitter>().unwrap();
+
+                if emitter.particles().is_empty() {
+                    return false;
+                }
+
+                let (quad_vertices, quad_indices) = match &self.particle_quad {
+                    Some((vertices, indices)) => (vertices, indices),
+                    None => return false
+                };
+
+                // only the per particle data changes every frame, it is copied into bgfx owned memory
+                let instances = emitter.build_instances();
+
+                let instance_buffer = bgfx::create_vertex_buffer(&Memory::copy(&instances[..]), &particle_instance_layout(), BufferFlags::empty().bits());
+
+                // additive blending without depth write so overlapping particles accumulate
+                let state = blend_state(BlendMode::Additive)
+                    | StateDepthTestFlags::LESS.bits();
+
+                let position = scene.render_position(&*emitter, emitter.coordinates);
+
+                let transform = Mat4::from_translation(position);
+
+                let program = match load_program(&emitter.shaders, &context, scene.shader_features, &self.errors) {
+                    Some(program) => program,
+                    None => return false
+                };
+
+                bgfx::set_transform(&transform.to_cols_array(), 1);
+                bgfx::set_vertex_buffer(0, quad_vertices, 0, std::u32::MAX);
+                bgfx::set_index_buffer(quad_indices, 0, std::u32::MAX);
+                bgfx::set_instance_data_from_vertex_buffer(&instance_buffer, 0, instances.len() as u32);
+
+                bgfx::set_state(state, 0);
+
+                apply_uniforms(&emitter.shaders, &HashMap::new(), &self.errors);
+
+                bgfx::submit(view, program.as_ref(), SubmitArgs::default());
+
+                true
+            }
+
+            _ => false
+
+        }
+
+    }
+
+    // submits grid, axes and the lines added since the last frame as line primitives,
+    // independent of the scene contents
+    fn submit_debug_lines(&mut self) {
+
+        let mut vertices: Vec<ColoredVertex> = std::mem::take(&mut self.debug_lines);
+
+        let shader = match &self.debug_shader {
+            Some(shader) => shader,
+            None => {
+
+                // no line shader ships with the engine, nothing is drawn until one is set
+                if !self.debug_shader_missing && (!vertices.is_empty() || self.grid.is_some() || self.axes) {
+                    self.debug_shader_missing = true;
+                    self.errors.push(ErrorSource::Renderer, String::from("Debug lines are not drawn without a debug shader, see set_debug_shader"), true);
+                }
+
+                return;
+            }
+        };
+
+        if let Some((spacing, extent)) = self.grid {
+            vertices.extend(grid_vertices(spacing, extent));
+        }
+
+        if self.axes {
+            vertices.extend(axes_vertices(1.0));
+        }
+
+        if vertices.is_empty() {
+            return;
+        }
+
+        let vertex_buffer = bgfx::create_vertex_buffer(&Memory::copy(&vertices[..]), &colored_vertex_layout(), BufferFlags::empty().bits());
+
+        let state = (StateWriteFlags::R
+            | StateWriteFlags::G
+            | StateWriteFlags::B
+            | StateWriteFlags::A
+            | StateWriteFlags::Z)
+            .bits()
+            | StateDepthTestFlags::LESS.bits()
+            | StatePtFlags::LINES.bits();
+
+        let program = match load_program(shader, &self.load_context(), ShaderFeatures::NONE, &self.errors) {
+            Some(program) => program,
+            None => return
+        };
+
+        bgfx::set_transform(&Mat4::IDENTITY.to_cols_array(), 1);
+        bgfx::set_vertex_buffer(0, &vertex_buffer, 0, std::u32::MAX);
+        bgfx::set_state(state, 0);
+
+        bgfx::submit(SCENE_VIEW, program.as_ref(), SubmitArgs::default());
+    }
+
+    // submits the objects of the current chunk, a missing chunk means there is nothing to draw.
+    // returns the counted geometry, the gpu fields stay zero
+    fn submit_scene(&self, view: u16, scene: &Scene) -> RenderStats {
+
+        let mut counted = RenderStats::default();
+
+        match scene.get_current_chunk() {
+            Ok(chunk) => {
+
+                counted.chunks_visited += 1;
+
+                for object in &mut chunk.iter_objects_mut() {
+
+                    if !object.in_layers(self.layer_mask) {
+                        continue;
+                    }
+
+                    if self.submit_object(view, object.as_mut(), scene) {
+                        counted.record_draw(object.geometry_counts());
+                    }
+                }
+            }
+            Err(e) => self.errors.push(ErrorSource::Scene, format!("Failed to get current chunk: {}", e), true)
+        }
+
+        counted
+    }
+
+    // uploads the ui textures of the frame and draws its meshes in screen space over the scene
+    #[cfg(feature = "debug-ui")]
+    fn submit_debug_ui(&mut self) {
+
+        let frame = match self.debug_ui.take() {
+            Some(frame) => frame,
+            None => return
+        };
+
+        // the meshes may use textures set in the same frame
+        for (id, delta) in frame.textures.set.iter() {
+
+            let [width, height] = delta.image.size();
+            let pixels = image_rgba(&delta.image);
+
+            match delta.pos {
+                Some([x, y]) => {
+                    if let Some(texture) = self.debug_ui_textures.get(id) {
+                        bgfx::update_texture_2d(texture, 0, 0, x as u16, y as u16, width as u16, height as u16, &Memory::copy(&pixels[..]), u16::MAX);
+                    }
+                }
+                None => {
+                    let texture = bgfx::create_texture_2d(width as u16, height as u16, false, 1, TextureFormat::RGBA8, (SamplerFlags::U_CLAMP | SamplerFlags::V_CLAMP).bits() as u64, &Memory::copy(&pixels[..]));
+                    self.debug_ui_textures.insert(*id, texture);
+                }
+            }
+        }
+
+        let program = match &self.debug_ui_shader {
+            Some(shader) => load_program(shader, &self.load_context(), ShaderFeatures::NONE, &self.errors),
+            None => None
+        };
+
+        if let Some(program) = program {
+
+            if self.debug_ui_sampler.is_none() {
+                self.debug_ui_sampler = Some(bgfx::create_uniform("s_texColor", UniformType::Sampler, 1));
+            }
+
+            let sampler = self.debug_ui_sampler.as_ref().unwrap();
+            let resolution = (self.resolution.width, self.resolution.height);
+
+            let points = (resolution.0 as f32 / frame.pixels_per_point, resolution.1 as f32 / frame.pixels_per_point);
+
+            bgfx::set_view_rect(DEBUG_UI_VIEW, 0, 0, resolution.0 as u16, resolution.1 as u16);
+            bgfx::set_view_transform(DEBUG_UI_VIEW, &Mat4::IDENTITY.to_cols_array(), &Mat4::orthographic_rh_gl(0.0, points.0, points.1, 0.0, -1.0, 1.0).to_cols_array());
+
+            // egui colors are premultiplied
+            let state = (StateWriteFlags::R | StateWriteFlags::G | StateWriteFlags::B | StateWriteFlags::A).bits()
+                | blend_function(StateBlendFlags::ONE, StateBlendFlags::INV_SRC_ALPHA);
+
+            let layout = debug_ui_vertex_layout();
+
+            for (clip, mesh) in frame.meshes.iter() {
+
+                let texture = match self.debug_ui_textures.get(&mesh.texture_id) {
+                    Some(texture) => texture,
+                    None => continue
+                };
+
+                let (x, y, width, height) = clip_to_pixels(*clip, frame.pixels_per_point, resolution);
+
+                if width == 0 || height == 0 || mesh.indices.is_empty() {
+                    continue;
+                }
+
+                let vertex_buffer = bgfx::create_vertex_buffer(&Memory::copy(&mesh.vertices[..]), &layout, BufferFlags::empty().bits());
+                let index_buffer = bgfx::create_index_buffer(&Memory::copy(&mesh.indices[..]), BufferFlags::empty().bits());
+
+                bgfx::set_scissor(x, y, width, height);
+                bgfx::set_texture(0, sampler, texture, std::u32::MAX);
+                bgfx::set_transform(&Mat4::IDENTITY.to_cols_array(), 1);
+                bgfx::set_vertex_buffer(0, &vertex_buffer, 0, std::u32::MAX);
+                bgfx::set_index_buffer(&index_buffer, 0, std::u32::MAX);
+                bgfx::set_state(state, 0);
+
+                bgfx::submit(DEBUG_UI_VIEW, program.as_ref(), SubmitArgs::default());
+            }
+        }
+
+        for id in frame.textures.free.iter() {
+            self.debug_ui_textures.remove(id);
+        }
+    }
+
+    fn reset_flags(&self) -> u32 {
+        match self.vsync {
+            true => ResetFlags::VSYNC.bits(),
+            false => ResetFlags::NONE.bits()
+        }
+    }
+
+    // draws the layers above the bottom one, each with only its depth cleared so it ends up on
+    // top of everything below. returns the counted geometry like submit_scene
+    fn submit_layers(&self, perspective: &RenderPerspective) -> RenderStats {
+
+        let mut counted = RenderStats::default();
+        let resolution = (self.resolution.width, self.resolution.height);
+
+        for (view, layer) in (LAYER_VIEW..).zip(self.layers.iter().skip(1).take(MAX_SCENE_LAYERS - 1)) {
+
+            let scene = layer.scene.borrow();
+
+            bgfx::set_view_rect(view, 0, 0, resolution.0 as u16, resolution.1 as u16);
+            bgfx::set_view_clear(view, ClearFlags::DEPTH.bits(), SetViewClearArgs::default());
+            bgfx::set_view_transform(view, &scene.camera.view_matrix().to_cols_array(), &layer.projection.matrix(perspective, resolution).to_cols_array());
+
+            let layer_counted = self.submit_scene(view, &scene);
+
+            counted.triangles += layer_counted.triangles;
+            counted.vertices += layer_counted.vertices;
+            counted.chunks_visited += layer_counted.chunks_visited;
+
+            bgfx::touch(view);
+        }
+
+        counted
+    }
+
+    // renders the outgoing scene into a texture and blends it over the current scene
+    fn submit_fade(&mut self, perspective: &RenderPerspective, delta: f32) {
+
+        let (outgoing, fade) = match self.fade.as_mut() {
+            Some((outgoing, fade)) => {
+                fade.advance(delta);
+                (Rc::clone(outgoing), *fade)
+            }
+            None => return
+        };
+
+        let shader = match &self.fade_shader {
+            Some(shader) if !fade.finished() => Rc::clone(shader),
+            _ => {
+                self.fade = None;
+                return;
+            }
+        };
+
+        let (width, height) = (self.resolution.width as u16, self.resolution.height as u16);
+
+        if self.fade_target.is_none() {
+            self.fade_target = Some(bgfx::create_frame_buffer(width, height, TextureFormat::BGRA8, (SamplerFlags::U_CLAMP | SamplerFlags::V_CLAMP).bits() as u64));
+        }
+
+        if self.fade_uniforms.is_none() {
+            self.fade_uniforms = Some((bgfx::create_uniform("s_texColor", UniformType::Sampler, 1), bgfx::create_uniform("u_fade", UniformType::Vec4, 1)));
+        }
+
+        let target = self.fade_target.as_ref().unwrap();
+        let (sampler, fade_uniform) = self.fade_uniforms.as_ref().unwrap();
+
+        let outgoing = outgoing.borrow();
+
+        bgfx::set_view_frame_buffer(FADE_SOURCE_VIEW, target);
+        bgfx::set_view_rect(FADE_SOURCE_VIEW, 0, 0, width, height);
+        bgfx::set_view_clear(
+            FADE_SOURCE_VIEW,
+            ClearFlags::COLOR.bits() | ClearFlags::DEPTH.bits(),
+            SetViewClearArgs {
+                rgba: outgoing.clear_color,
+                ..Default::default()
+            },
+        );
+        bgfx::set_view_transform(FADE_SOURCE_VIEW, &outgoing.camera.view_matrix().to_cols_array(), &perspective.projection_matrix().to_cols_array());
+
+        self.submit_scene(FADE_SOURCE_VIEW, &outgoing);
+        bgfx::touch(FADE_SOURCE_VIEW);
+
+        // full screen quad in normalized device coordinates, u and v are normalized i16
+        let vertices = [
+            ImageTexturedVertex { coordinates: Vec3::new(-1.0, -1.0, 0.0), texture_u: 0, texture_v: i16::MAX },
+            ImageTexturedVertex { coordinates: Vec3::new(1.0, -1.0, 0.0), texture_u: i16::MAX, texture_v: i16::MAX },
+            ImageTexturedVertex { coordinates: Vec3::new(1.0, 1.0, 0.0), texture_u: i16::MAX, texture_v: 0 },
+            ImageTexturedVertex { coordinates: Vec3::new(-1.0, 1.0, 0.0), texture_u: 0, texture_v: 0 },
+        ];
+        let indices: [u16; 6] = [0, 2, 1, 0, 3, 2];
+
+        let layout = get_vertex_layout(BgfxShaderVertexType::TEXTURED, bgfx::get_renderer_type());
+
+        let vertex_buffer = bgfx::create_vertex_buffer(&Memory::copy(&vertices[..]), &layout, BufferFlags::empty().bits());
+        let index_buffer = bgfx::create_index_buffer(&Memory::copy(&indices[..]), BufferFlags::empty().bits());
+
+        bgfx::set_view_rect(FADE_OVERLAY_VIEW, 0, 0, width, height);
+        bgfx::set_view_transform(FADE_OVERLAY_VIEW, &Mat4::IDENTITY.to_cols_array(), &Mat4::IDENTITY.to_cols_array());
+
+        let state = (StateWriteFlags::R | StateWriteFlags::G | StateWriteFlags::B).bits()
+            | blend_function(StateBlendFlags::SRC_ALPHA, StateBlendFlags::INV_SRC_ALPHA);
+
+        let program = match load_program(&shader, &self.load_context(), ShaderFeatures::NONE, &self.errors) {
+            Some(program) => program,
+            None => return
+        };
+
+        bgfx::set_texture(0, sampler, &bgfx::get_texture(target, 0), std::u32::MAX);
+        bgfx::set_uniform(fade_uniform, &[fade.alpha(), 0.0, 0.0, 0.0], 1);
+        bgfx::set_transform(&Mat4::IDENTITY.to_cols_array(), 1);
+        bgfx::set_vertex_buffer(0, &vertex_buffer, 0, std::u32::MAX);
+        bgfx::set_index_buffer(&index_buffer, 0, std::u32::MAX);
+        bgfx::set_state(state, 0);
+
+        bgfx::submit(FADE_OVERLAY_VIEW, program.as_ref(), SubmitArgs::default());
+    }
+
+}
+
+impl Renderer for BgfxRenderer {
+
+    fn init(&mut self) -> Result<(), RendererError> {
+
+        info!("Initializing BgfxRenderer");
+
+        let mut init = Init::new();
+        init.type_r = Count;
+        init.resolution.width = self.resolution.width;
+        init.resolution.height = self.resolution.height;
+        init.resolution.reset = self.reset_flags();
+
+        // get platform data from raw windows handle
+        init.platform_data = match platform_data(self.surface.borrow().deref()) {
+            Ok(platform_data) => platform_data,
+            Err(e) => {
+                error!("{}", e);
+                return Err(e);
+            }
+        };
+
+        if !bgfx::init(&init) {
+            error!("Failed to init bgfx");
+            return Err(RendererError::InitFailed);
+        }
+
+        set_active_renderer(Some(bgfx::get_renderer_type()));
+        self.initialized = true;
+
+        self.particle_quad = Some(create_particle_quad());
+
+        self.apply_debug_flags(false);
+        self.clean_up();
+
+        Ok(())
+    }
+
+    fn do_render_cycle(&mut self) -> Result<(), RendererError> {
+
+        let cycle_start = Instant::now();
+
+        let (scene, projection) = match self.layers.first() {
+            Some(layer) => (Rc::clone(&layer.scene), layer.projection),
+            None => {
+                self.errors.push(ErrorSource::Renderer, String::from("Scene is not initialized"), false);
+                return Err(RendererError::NoScene);
+            }
+        };
+
+        let debug = *self.debug.lock().expect("Failed to lock debug mutex");
+        let perspective = *self.perspective.lock().expect("Failed to lock perspective mutex");
+
+        if !self.resolution.eq(&self.old_resolution) {
+            self.old_resolution.from(&self.resolution);
+            bgfx::reset(self.resolution.width, self.resolution.height, ResetArgs { flags: self.reset_flags(), ..Default::default() });
+
+            // recreated with the new size on the next fade
+            self.fade_target = None;
+        }
+
+        if let Some(stats) = self.read_gpu_stats() {
+            self.stats = stats;
+        }
+
+        bgfx::dbg_text_clear(bgfx::DbgTextClearArgs::default());
+        bgfx::set_view_rect(0, 0, 0, self.resolution.width.clone() as u16, self.resolution.height.clone() as u16);
+
+        let scene_reference = scene.borrow();
+
+        bgfx::set_view_clear(
+            0,
+            ClearFlags::COLOR.bits() | ClearFlags::DEPTH.bits(),
+            SetViewClearArgs {
+                rgba: scene_reference.clear_color,
+                ..Default::default()
+            },
+        );
+
+        let view_matrix = scene_reference.camera.view_matrix();
+        let proj_matrix = projection.matrix(&perspective, (self.resolution.width, self.resolution.height));
+
+        bgfx::set_view_transform(0, &view_matrix.to_cols_array(), &proj_matrix.to_cols_array());
+
+        if self.camera_gizmo {
+            self.draw_camera_gizmo(&scene_reference.camera);
+        }
+
+        self.submit_debug_lines();
+
+        // a missing chunk means there is nothing to draw, the frame is still submitted
+        let mut counted = self.submit_scene(SCENE_VIEW, &scene_reference);
+
+        drop(scene_reference);
+
+        let now = Instant::now();
+
+        let delta = match self.last_cycle {
+            Some(last) => now.duration_since(last).as_secs_f32(),
+            None => 0.0
+        };
+
+        self.last_cycle = Some(now);
+
+        self.submit_fade(&perspective, delta);
+
+        let layers = self.submit_layers(&perspective);
+
+        counted.triangles += layers.triangles;
+        counted.vertices += layers.vertices;
+        counted.chunks_visited += layers.chunks_visited;
+
+        // draw calls come from bgfx, which also sees the debug lines and the fade overlay
+        self.stats.triangles = counted.triangles;
+        self.stats.vertices = counted.vertices;
+        self.stats.chunks_visited = counted.chunks_visited;
+
+        #[cfg(feature = "debug-ui")]
+        self.submit_debug_ui();
+
+        if debug {
+
+            let mut row: u16 = 0;
+
+            if let Some(debug_data) = self.debug_data.as_ref() {
+
+                for line in debug_data.lines().iter() {
+                    bgfx::dbg_text(0, row, 0x0f, format!("{}: {}", line.key(), line.value()).as_str());
+                    row += 1;
+                }
+
+            }
+
+            for line in self.stats.debug_lines() {
+                bgfx::dbg_text(0, row, 0x0f, format!("{}: {}", line.key(), line.value()).as_str());
+                row += 1;
+            }
+
+            bgfx::dbg_text(0, row, 0x0f, format!("camera_gizmo: {}", if self.camera_gizmo { "on" } else { "off" }).as_str());
+
+        }
+
+        bgfx::touch(0);
+        bgfx::frame(false);
+
+        self.frames += 1;
+        self.stats.frame_ms = cycle_start.elapsed().as_secs_f32() * 1000.0;
+
+        Ok(())
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    fn shutdown(&mut self) {
+        info!("Shutting down BgfxRenderer");
+        self.initialized = false;
+        set_active_renderer(None);
+
+        // destroyed while bgfx is still up
+        self.particle_quad = None;
+
+        bgfx::shutdown();
+    }
+
+    fn set_scene(&mut self, scene: Rc<RefCell<Scene>>) {
+        match self.layers.first_mut() {
+            Some(layer) => layer.scene = scene,
+            None => self.layers.push(SceneLayer::new(scene, LayerProjection::Perspective))
+        }
+    }
+
+    fn set_scene_layers(&mut self, layers: Vec<SceneLayer>) {
+        self.layers = layers;
+    }
+
+    fn set_scene_with_transition(&mut self, scene: Rc<RefCell<Scene>>, transition: Transition) {
+
+        let outgoing = self.layers.first().map(|layer| Rc::clone(&layer.scene));
+
+        self.fade = match (transition, outgoing) {
+            (Transition::Fade(duration), Some(outgoing)) if duration > 0.0 && self.fade_shader.is_some() => Some((outgoing, SceneFade::new(duration))),
+            _ => None
+        };
+
+        self.set_scene(scene);
+    }
+
+    fn set_debug_data(&mut self, data: TextDebugData) {
+
+        self.debug_data = Some(data);
+    }
+
+    fn do_debug(&mut self, debug: bool) {
+
+        let mut debug_guard = self.debug.lock().expect("Failed to lock debug mutex");
+        *debug_guard = debug;
+
+        if debug {
+            info!("Debugging enabled");
+        } else {
+            info!("Debugging disabled");
+        }
+
+        self.apply_debug_flags(debug);
+
+    }
+
+    fn clean_up(&mut self) {
+        info!("Cleaning up BgfxRenderer");
+        bgfx::set_view_clear(
+            0,
+            ClearFlags::COLOR.bits() | ClearFlags::DEPTH.bits(),
+            SetViewClearArgs {
+                rgba: 0x103030ff,
+                ..Default::default()
+            },
+        );
+    }
+
+    // applied with a reset of the surface
+    fn set_vsync(&mut self, enabled: bool) {
+        self.vsync = enabled;
+        self.refresh();
+    }
+
+    // the clear color is read from the scene every frame, resetting brings back the backbuffer
+    fn refresh(&mut self) {
+        info!("Refreshing BgfxRenderer");
+        self.old_resolution = RenderResolution::new(0, 0);
+        self.fade_target = None;
+    }
+
+    // programs of shader containers are dropped by their owner, see crate::clear_gpu_caches.
+    // the debug ui textures are kept, egui only sends them once
+    fn clear_caches(&mut self) {
+        info!("Clearing BgfxRenderer caches");
+        self.refresh();
+        self.shaders.clear();
+        self.fade_uniforms = None;
+    }
+
+    fn take_errors(&mut self) -> Vec<EngineErrorEvent> {
+        self.errors.take()
+    }
+
+    fn update_surface_resolution(&mut self, width: u32, height: u32) {
+        self.old_resolution.from(&self.resolution);
+        self.resolution.update(width, height);
+
+        self.perspective.lock().expect("Failed to lock perspective mutex").set_aspect(width, height);
+    }
+
+    fn update_perspective(&mut self, perspective: RenderPerspective) {
+
+        let mut perspective_guard = self.perspective.lock().expect("Failed to lock perspective mutex");
+        *perspective_guard = perspective;
+
+    }
+
+    fn get_perspective(&self) -> RenderPerspective {
+        *self.perspective.lock().expect("Failed to lock perspective mutex")
+    }
+
+    fn get_stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    fn draw_grid(&mut self, enabled: bool, spacing: f32, extent: f32) {
+        self.grid = if enabled { Some((spacing, extent)) } else { None };
+    }
+
+    fn draw_axes(&mut self, enabled: bool) {
+        self.axes = enabled;
+    }
+
+    fn set_wireframe(&mut self, enabled: bool) {
+
+        self.wireframe = enabled;
+
+        // applied by init otherwise
+        if self.initialized {
+            self.apply_debug_flags(*self.debug.lock().expect("Failed to lock debug mutex"));
+        }
+    }
+
+    fn show_camera_gizmo(&mut self, enabled: bool) {
+        self.camera_gizmo = enabled;
+    }
+
+    fn set_layer_mask(&mut self, mask: u32) {
+        self.layer_mask = mask;
+    }
+
+    fn debug_line(&mut self, start: Vec3, end: Vec3, color: u32) {
+        self.debug_lines.push(ColoredVertex { coordinates: start, color_rgba: color });
+        self.debug_lines.push(ColoredVertex { coordinates: end, color_rgba: color });
+    }
+
+    fn debug_aabb(&mut self, min: Vec3, max: Vec3, color: u32) {
+        self.debug_lines.extend(aabb_vertices(min, max, color));
+    }
+
+    fn debug_axes(&mut self, transform: Mat4) {
+        self.debug_lines.extend(transformed_axes_vertices(transform));
+    }
+
+    fn set_transition_shader(&mut self, shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {
+        self.fade_shader = Some(shader);
+    }
+
+    fn set_debug_shader(&mut self, shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {
+        self.debug_shader = Some(shader);
+    }
+
+    #[cfg(feature = "debug-ui")]
+    fn draw_debug_ui(&mut self, frame: DebugUiFrame) {
+        self.debug_ui = Some(frame);
+    }
+
+    #[cfg(feature = "debug-ui")]
+    fn set_debug_ui_shader(&mut self, shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {
+        self.debug_ui_shader = Some(shader);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use raw_window_handle::WebWindowHandle;
+    use crate::scene::object::test_shaders;
+    use crate::shader::BgfxShaderContainer;
+    use super::*;
+
+    #[test]
+    fn timer_to_ms_test() {
+        assert_eq!(timer_to_ms(1000, 3000, 1_000_000), 2.0);
+        assert_eq!(timer_to_ms(0, 100, 0), 0.0);
+        assert_eq!(timer_to_ms(100, 0, 1000), 0.0);
+    }
+
+    #[test]
+    fn unsupported_handle_test() {
+
+        let handle = RawWindowHandle::Web(WebWindowHandle::empty());
+
+        assert_eq!(platform_data(&handle).err(), Some(RendererError::UnsupportedPlatform("Web")));
+
+        let mut renderer = BgfxRenderer::new(100, 100, Rc::new(RefCell::new(handle)), false, RenderPerspective::new(100, 100, 60.0, 0.1, 100.0));
+
+        assert_eq!(renderer.init(), Err(RendererError::UnsupportedPlatform("Web")));
+        assert!(!renderer.is_initialized());
+    }
+
+    #[test]
+    fn no_scene_test() {
+
+        let handle = RawWindowHandle::Web(WebWindowHandle::empty());
+
+        let mut renderer = BgfxRenderer::new(100, 100, Rc::new(RefCell::new(handle)), false, RenderPerspective::new(100, 100, 60.0, 0.1, 100.0));
+
+        assert_eq!(renderer.do_render_cycle(), Err(RendererError::NoScene));
+
+        let errors = renderer.take_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, ErrorSource::Renderer);
+        assert!(!errors[0].recoverable);
+        assert!(renderer.take_errors().is_empty());
+    }
+
+    #[test]
+    fn missing_debug_shader_test() {
+
+        let handle = RawWindowHandle::Web(WebWindowHandle::empty());
+
+        let mut renderer = BgfxRenderer::new(100, 100, Rc::new(RefCell::new(handle)), false, RenderPerspective::new(100, 100, 60.0, 0.1, 100.0));
+
+        // nothing asked for, nothing to report
+        renderer.submit_debug_lines();
+        assert!(renderer.take_errors().is_empty());
+
+        renderer.draw_grid(true, 1.0, 10.0);
+
+        renderer.submit_debug_lines();
+        renderer.submit_debug_lines();
+
+        let errors = renderer.take_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, ErrorSource::Renderer);
+        assert!(renderer.take_errors().is_empty());
+    }
+
+    #[test]
+    fn failed_load_reported_once_test() {
+
+        // empty binaries are rejected before they reach bgfx
+        let shaders: Rc<RefCell<Box<dyn ShaderContainer>>> = Rc::new(RefCell::new(Box::new(BgfxShaderContainer::new(Vec::new(), Vec::new()))));
+        let context = ShaderLoadContext::new(RendererType::Noop);
+        let errors = PendingErrors::new();
+
+        assert!(load_program(&shaders, &context, ShaderFeatures::NONE, &errors).is_none());
+        assert!(load_program(&shaders, &context, ShaderFeatures::NONE, &errors).is_none());
+
+        let reported = errors.take();
+
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].source, ErrorSource::Shader);
+        assert!(shaders.borrow().load_failed());
+
+        // the next failure after an unload is reported again
+        shaders.borrow_mut().unload();
+
+        assert!(load_program(&shaders, &context, ShaderFeatures::NONE, &errors).is_none());
+        assert_eq!(errors.take().len(), 1);
+    }
+
+    // renderer types the custom layout was begun for
+    static CUSTOM_LAYOUT_TARGETS: Mutex<Vec<RendererType>> = Mutex::new(Vec::new());
+
+    fn custom_layout(renderer_type: RendererType) -> VertexLayoutBuilder {
+
+        CUSTOM_LAYOUT_TARGETS.lock().unwrap().push(renderer_type);
+
+        get_vertex_layout(BgfxShaderVertexType::COLORED, renderer_type)
+    }
+
+    #[test]
+    fn custom_layout_test() {
+
+        let container = BgfxShaderContainer::with_vertex_type(Vec::new(), Vec::new(), BgfxShaderVertexType::CUSTOM(custom_layout));
+        let shaders: Rc<RefCell<Box<dyn ShaderContainer>>> = Rc::new(RefCell::new(Box::new(container)));
+
+        // the layout is begun for the renderer in the load context
+        shader_layout(&shaders, BgfxShaderVertexType::COLORED, &ShaderLoadContext::new(RendererType::Vulkan));
+        shader_layout(&shaders, BgfxShaderVertexType::COLORED, &ShaderLoadContext::new(RendererType::OpenGL));
+
+        assert_eq!(*CUSTOM_LAYOUT_TARGETS.lock().unwrap(), vec![RendererType::Vulkan, RendererType::OpenGL]);
+
+        // containers without a bgfx layout use the fallback
+        let shaders = test_shaders();
+
+        shader_layout(&shaders, BgfxShaderVertexType::COLORED, &ShaderLoadContext::new(RendererType::Noop));
+
+        assert_eq!(CUSTOM_LAYOUT_TARGETS.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn cull_flags_test() {
+
+        let cull_mask = StateCullFlags::CW.bits() | StateCullFlags::CCW.bits();
+
+        assert_eq!(cull_flags(CullMode::None) & cull_mask, 0);
+        assert_eq!(cull_flags(CullMode::Clockwise), StateCullFlags::CW.bits());
+        assert_eq!(cull_flags(CullMode::CounterClockwise), StateCullFlags::CCW.bits());
+        assert_eq!(CullMode::default(), CullMode::Clockwise);
+    }
+
+    #[test]
+    fn blend_state_test() {
+
+        let color = (StateWriteFlags::R | StateWriteFlags::G | StateWriteFlags::B | StateWriteFlags::A).bits();
+
+        assert_eq!(blend_state(BlendMode::Opaque), color | StateWriteFlags::Z.bits());
+        assert_eq!(blend_state(BlendMode::Alpha), color | blend_function(StateBlendFlags::SRC_ALPHA, StateBlendFlags::INV_SRC_ALPHA));
+        assert_eq!(blend_state(BlendMode::Additive), color | blend_function(StateBlendFlags::ONE, StateBlendFlags::ONE));
+
+        // transparent modes leave the depth buffer alone
+        assert_eq!(blend_state(BlendMode::Alpha) & StateWriteFlags::Z.bits(), 0);
+        assert_eq!(BlendMode::default(), BlendMode::Opaque);
+    }
+
+}
@@ -0,0 +1,285 @@
+use glam::{Mat4, Vec3};
+use crate::renderer::types::RenderView;
+use crate::scene::object::ColoredVertex;
+
+pub struct DebugLine {
+    key: String,
+    value: String
+}
+
+impl DebugLine {
+
+    // constructor
+    pub fn new(key: String, value: String) -> Self {
+        Self {
+            key, value
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+}
+
+pub struct TextDebugData {
+    lines: Vec<DebugLine>
+}
+
+impl TextDebugData {
+
+    // constructor
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new()
+        }
+    }
+
+    pub fn add_line(&mut self, line: DebugLine) {
+        self.lines.push(line);
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+}
+
+pub const GRID_COLOR: u32 = 0xff808080;
+pub const AXIS_X_COLOR: u32 = 0xff0000ff;
+pub const AXIS_Y_COLOR: u32 = 0xff00ff00;
+pub const AXIS_Z_COLOR: u32 = 0xffff0000;
+
+// lines on each side of the grid center, keeps the grid inside what a u16 indexed mesh holds
+pub const MAX_GRID_LINES: i32 = ((u16::MAX as i32 + 1) / 4 - 1) / 2;
+
+// line list for a grid on the XZ plane at y = 0, two vertices per line.
+// empty for non-positive or non-finite arguments, the extent shrinks to MAX_GRID_LINES lines
+pub fn grid_vertices(spacing: f32, extent: f32) -> Vec<ColoredVertex> {
+
+    let mut vertices = Vec::new();
+
+    if !spacing.is_finite() || !extent.is_finite() || spacing <= 0.0 || extent <= 0.0 {
+        return vertices;
+    }
+
+    // the cast saturates for very small spacings
+    let lines = (extent / spacing).floor() as i32;
+
+    let (lines, extent) = if lines > MAX_GRID_LINES {
+        (MAX_GRID_LINES, MAX_GRID_LINES as f32 * spacing)
+    } else {
+        (lines, extent)
+    };
+
+    for i in -lines..=lines {
+
+        let offset = i as f32 * spacing;
+
+        // line parallel to the z axis
+        vertices.push(ColoredVertex { coordinates: Vec3::new(offset, 0.0, -extent), color_rgba: GRID_COLOR });
+        vertices.push(ColoredVertex { coordinates: Vec3::new(offset, 0.0, extent), color_rgba: GRID_COLOR });
+
+        // line parallel to the x axis
+        vertices.push(ColoredVertex { coordinates: Vec3::new(-extent, 0.0, offset), color_rgba: GRID_COLOR });
+        vertices.push(ColoredVertex { coordinates: Vec3::new(extent, 0.0, offset), color_rgba: GRID_COLOR });
+    }
+
+    vertices
+}
+
+// line list for the world axes at the origin, x red, y green, z blue
+pub fn axes_vertices(length: f32) -> Vec<ColoredVertex> {
+    vec![
+        ColoredVertex { coordinates: Vec3::ZERO, color_rgba: AXIS_X_COLOR },
+        ColoredVertex { coordinates: Vec3::new(length, 0.0, 0.0), color_rgba: AXIS_X_COLOR },
+        ColoredVertex { coordinates: Vec3::ZERO, color_rgba: AXIS_Y_COLOR },
+        ColoredVertex { coordinates: Vec3::new(0.0, length, 0.0), color_rgba: AXIS_Y_COLOR },
+        ColoredVertex { coordinates: Vec3::ZERO, color_rgba: AXIS_Z_COLOR },
+        ColoredVertex { coordinates: Vec3::new(0.0, 0.0, length), color_rgba: AXIS_Z_COLOR },
+    ]
+}
+
+// line list for the twelve edges of an axis aligned box
+pub fn aabb_vertices(min: Vec3, max: Vec3, color: u32) -> Vec<ColoredVertex> {
+
+    let corner = |x: bool, y: bool, z: bool| Vec3::new(
+        if x { max.x } else { min.x },
+        if y { max.y } else { min.y },
+        if z { max.z } else { min.z }
+    );
+
+    let mut vertices = Vec::with_capacity(24);
+
+    for a in [false, true] {
+        for b in [false, true] {
+
+            // one edge along each axis for every combination of the other two
+            for (start, end) in [
+                (corner(false, a, b), corner(true, a, b)),
+                (corner(a, false, b), corner(a, true, b)),
+                (corner(a, b, false), corner(a, b, true))
+            ] {
+                vertices.push(ColoredVertex { coordinates: start, color_rgba: color });
+                vertices.push(ColoredVertex { coordinates: end, color_rgba: color });
+            }
+        }
+    }
+
+    vertices
+}
+
+// unit axes of the transform, scaled and rotated with it, colored like the world axes
+pub fn transformed_axes_vertices(transform: Mat4) -> Vec<ColoredVertex> {
+
+    let mut vertices = axes_vertices(1.0);
+
+    for vertex in vertices.iter_mut() {
+        vertex.coordinates = transform.transform_point3(vertex.coordinates);
+    }
+
+    vertices
+}
+
+// orthonormal basis of the camera drawn from the eye, right red, up green and the look
+// direction blue. the up vector is made perpendicular to the look direction first
+pub fn camera_gizmo_vertices(view: &RenderView, length: f32) -> Vec<ColoredVertex> {
+
+    let forward = view.get_normal();
+
+    let mut right = forward.cross(view.up);
+
+    // up parallel to the look direction, any perpendicular works
+    if right.length_squared() == 0.0 {
+        right = forward.any_orthonormal_vector();
+    }
+
+    let right = right.normalize();
+    let up = right.cross(forward);
+
+    let mut vertices = Vec::with_capacity(6);
+
+    for (direction, color) in [(right, AXIS_X_COLOR), (up, AXIS_Y_COLOR), (forward, AXIS_Z_COLOR)] {
+        vertices.push(ColoredVertex { coordinates: view.eye, color_rgba: color });
+        vertices.push(ColoredVertex { coordinates: view.eye + direction * length, color_rgba: color });
+    }
+
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_test() {
+
+        let vertices = grid_vertices(1.0, 2.0);
+
+        // five lines in each direction
+        assert_eq!(vertices.len(), 20);
+
+        for vertex in vertices.iter() {
+            assert_eq!(vertex.coordinates.y, 0.0);
+            assert!(vertex.coordinates.x.abs() <= 2.0);
+            assert!(vertex.coordinates.z.abs() <= 2.0);
+        }
+
+        assert_eq!(vertices[0].coordinates, Vec3::new(-2.0, 0.0, -2.0));
+        assert_eq!(vertices[1].coordinates, Vec3::new(-2.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn grid_invalid_spacing_test() {
+        assert!(grid_vertices(0.0, 2.0).is_empty());
+        assert!(grid_vertices(1.0, -1.0).is_empty());
+        assert!(grid_vertices(f32::NAN, 2.0).is_empty());
+        assert!(grid_vertices(1.0, f32::INFINITY).is_empty());
+        assert!(grid_vertices(-1.0, 2.0).is_empty());
+    }
+
+    #[test]
+    fn grid_cap_test() {
+
+        let vertices = grid_vertices(0.001, 1000.0);
+
+        assert_eq!(vertices.len(), (MAX_GRID_LINES as usize * 2 + 1) * 4);
+        assert!(vertices.len() <= u16::MAX as usize + 1);
+
+        // the lines end where the last capped line is
+        let extent = MAX_GRID_LINES as f32 * 0.001;
+        assert!(vertices.iter().all(|vertex| vertex.coordinates.x.abs() <= extent + 1e-3 && vertex.coordinates.z.abs() <= extent + 1e-3));
+    }
+
+    #[test]
+    fn axes_test() {
+
+        let vertices = axes_vertices(3.0);
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[1].coordinates, Vec3::new(3.0, 0.0, 0.0));
+        assert_eq!(vertices[3].coordinates, Vec3::new(0.0, 3.0, 0.0));
+        assert_eq!(vertices[5].coordinates, Vec3::new(0.0, 0.0, 3.0));
+    }
+
+    #[test]
+    fn aabb_test() {
+
+        let (min, max) = (Vec3::new(-1.0, 0.0, 2.0), Vec3::new(1.0, 3.0, 4.0));
+
+        let vertices = aabb_vertices(min, max, 0xffffffff);
+
+        assert_eq!(vertices.len(), 24);
+
+        // every edge runs along exactly one axis, so the other two components stay the same
+        for edge in vertices.chunks(2) {
+
+            let length = (edge[1].coordinates - edge[0].coordinates).abs();
+
+            assert_eq!([length.x, length.y, length.z].iter().filter(|l| **l == 0.0).count(), 2);
+            assert!(length.cmple(max - min).all());
+        }
+    }
+
+    #[test]
+    fn camera_gizmo_test() {
+
+        // looking down -z with a tilted up vector
+        let view = RenderView::new(Vec3::new(0.0, 1.0, 5.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.5));
+
+        let vertices = camera_gizmo_vertices(&view, 2.0);
+
+        assert_eq!(vertices.len(), 6);
+        assert!(vertices.chunks(2).all(|line| line[0].coordinates == view.eye));
+
+        let direction = |index: usize| (vertices[index].coordinates - view.eye) / 2.0;
+
+        assert!(direction(1).abs_diff_eq(Vec3::X, 1e-6));
+        assert!(direction(3).abs_diff_eq(Vec3::Y, 1e-6));
+        assert!(direction(5).abs_diff_eq(Vec3::NEG_Z, 1e-6));
+        assert_eq!(vertices[5].color_rgba, AXIS_Z_COLOR);
+
+        // up along the look direction still gives a basis
+        let view = RenderView::new(Vec3::ZERO, Vec3::Y, Vec3::Y);
+        let vertices = camera_gizmo_vertices(&view, 1.0);
+
+        assert!(vertices.iter().all(|vertex| vertex.coordinates.is_finite()));
+        assert!((vertices[1].coordinates.dot(vertices[3].coordinates)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transformed_axes_test() {
+
+        let vertices = transformed_axes_vertices(Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)));
+
+        assert_eq!(vertices[0].coordinates, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(vertices[1].coordinates, Vec3::new(2.0, 2.0, 3.0));
+        assert_eq!(vertices[5].coordinates, Vec3::new(1.0, 2.0, 4.0));
+        assert_eq!(vertices[5].color_rgba, AXIS_Z_COLOR);
+    }
+
+}
@@ -0,0 +1,46 @@
+use std::fmt::{Display, Formatter};
+use raw_window_handle::RawWindowHandle;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RendererError {
+    // the native rendering framework refused to start
+    InitFailed,
+    // the window handle kind is not supported by the backend
+    UnsupportedPlatform(&'static str),
+    // a frame was requested before any scene was set
+    NoScene
+}
+
+impl Display for RendererError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RendererError::InitFailed => write!(f, "Failed to initialize renderer"),
+            RendererError::UnsupportedPlatform(kind) => write!(f, "Unsupported platform: {}", kind),
+            RendererError::NoScene => write!(f, "Scene is not initialized")
+        }
+    }
+
+}
+
+impl std::error::Error for RendererError {}
+
+// name of the window handle variant, used in error messages
+pub fn handle_kind(handle: &RawWindowHandle) -> &'static str {
+    match handle {
+        RawWindowHandle::UiKit(_) => "UiKit",
+        RawWindowHandle::AppKit(_) => "AppKit",
+        RawWindowHandle::Orbital(_) => "Orbital",
+        RawWindowHandle::Xlib(_) => "Xlib",
+        RawWindowHandle::Xcb(_) => "Xcb",
+        RawWindowHandle::Wayland(_) => "Wayland",
+        RawWindowHandle::Drm(_) => "Drm",
+        RawWindowHandle::Gbm(_) => "Gbm",
+        RawWindowHandle::Win32(_) => "Win32",
+        RawWindowHandle::WinRt(_) => "WinRt",
+        RawWindowHandle::Web(_) => "Web",
+        RawWindowHandle::AndroidNdk(_) => "AndroidNdk",
+        RawWindowHandle::Haiku(_) => "Haiku",
+        _ => "Unknown"
+    }
+}
@@ -1,37 +1,79 @@
 use event_bus::Event;
 
+// asks the renderer to reset its surface and recreate size dependent targets on the next frame,
+// see crate::refresh. dispatched as a notification, it cannot be cancelled
 pub struct RefreshEvent {
     cancelled: bool,
     reason: Option<String>
 }
 
+impl RefreshEvent {
+
+    pub fn new() -> Self {
+        Self {
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
 impl Event for RefreshEvent {
 
     fn cancellable(&self) -> bool {
-        true
+        false
     }
 
-    fn cancel(&mut self, reason: Option<String>) {
-        self.cancelled = true;
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
         self.reason = reason;
     }
 
 }
 
+// asks the renderer to drop every cached gpu resource so the next frame rebuilds them,
+// see crate::clear_gpu_caches. implies a refresh
 pub struct ClearEvent {
     cancelled: bool,
     reason: Option<String>
 }
 
+impl ClearEvent {
+
+    pub fn new() -> Self {
+        Self {
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
 impl Event for ClearEvent {
 
     fn cancellable(&self) -> bool {
-        true
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
     }
 
-    fn cancel(&mut self, reason: Option<String>) {
-        self.cancelled = true;
-        self.reason = reason
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
     }
 
-}
\ No newline at end of file
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason;
+    }
+
+}
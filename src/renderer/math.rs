@@ -0,0 +1,188 @@
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use crate::renderer::{RenderPerspective, RenderResolution, RenderView};
+use crate::scene::object::Winding;
+
+impl RenderView {
+
+    // left handed look-at matrix, the same one the renderer submits
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at_lh(self.eye, self.at, self.up)
+    }
+
+}
+
+impl RenderPerspective {
+
+    // left handed projection with depth in 0..1, the same one the renderer submits
+    pub fn projection_matrix(&self) -> Mat4 {
+        Mat4::perspective_lh(self.fov, self.aspect(), self.near, self.far)
+    }
+
+}
+
+// projects a world position to pixel coordinates with the origin at the top left,
+// none when the point is behind the camera
+pub fn world_to_screen(view: &RenderView, perspective: &RenderPerspective, point: Vec3, resolution: &RenderResolution) -> Option<Vec2> {
+
+    let clip = perspective.projection_matrix() * view.view_matrix() * Vec4::new(point.x, point.y, point.z, 1.0);
+
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = clip.truncate() / clip.w;
+
+    Some(Vec2::new(
+        (ndc.x + 1.0) * 0.5 * resolution.width as f32,
+        (1.0 - ndc.y) * 0.5 * resolution.height as f32
+    ))
+}
+
+// ray through a pixel, origin on the near plane and normalized direction
+pub fn screen_to_ray(view: &RenderView, perspective: &RenderPerspective, screen: Vec2, resolution: &RenderResolution) -> (Vec3, Vec3) {
+
+    let ndc_x = screen.x / resolution.width as f32 * 2.0 - 1.0;
+    let ndc_y = 1.0 - screen.y / resolution.height as f32 * 2.0;
+
+    let inverse = (perspective.projection_matrix() * view.view_matrix()).inverse();
+
+    let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
+    let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+
+    (near, (far - near).normalize())
+}
+
+// winding of a world space triangle as seen by the camera, none when it is degenerate on screen
+// or a vertex is behind the camera
+pub fn screen_winding(view: &RenderView, perspective: &RenderPerspective, triangle: [Vec3; 3]) -> Option<Winding> {
+
+    let view_projection = perspective.projection_matrix() * view.view_matrix();
+
+    let mut ndc = [Vec2::ZERO; 3];
+
+    for (index, point) in triangle.iter().enumerate() {
+
+        let clip = view_projection * Vec4::new(point.x, point.y, point.z, 1.0);
+
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        ndc[index] = Vec2::new(clip.x / clip.w, clip.y / clip.w);
+    }
+
+    let area = (ndc[1] - ndc[0]).perp_dot(ndc[2] - ndc[0]);
+
+    if area > 0.0 {
+        Some(Winding::CounterClockwise)
+    } else if area < 0.0 {
+        Some(Winding::Clockwise)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::object::CullMode;
+
+    fn camera() -> RenderView {
+        RenderView::new(Vec3::new(-5.0, 2.0, -5.0), Vec3::new(1.0, 0.0, 3.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn world_to_screen_center_test() {
+
+        let view = camera();
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+        let resolution = RenderResolution::new(1920, 1080);
+
+        let screen = world_to_screen(&view, &perspective, view.at, &resolution).unwrap();
+
+        assert!((screen - Vec2::new(960.0, 540.0)).length() < 1e-2);
+    }
+
+    #[test]
+    fn resize_updates_aspect_test() {
+
+        let mut perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+
+        perspective.set_aspect(1000, 1000);
+
+        let projection = perspective.projection_matrix();
+
+        // square aspect scales x and y equally
+        assert!((projection.x_axis.x - projection.y_axis.y).abs() < 1e-6);
+
+        // a minimized window keeps the last aspect
+        perspective.set_aspect(0, 0);
+        assert_eq!(perspective.aspect(), 1.0);
+    }
+
+    #[test]
+    fn world_to_screen_behind_test() {
+
+        let view = camera();
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+        let resolution = RenderResolution::new(1920, 1080);
+
+        let behind = view.eye - view.get_normal() * 10.0;
+
+        assert!(world_to_screen(&view, &perspective, behind, &resolution).is_none());
+    }
+
+    #[test]
+    fn screen_to_ray_center_test() {
+
+        let view = camera();
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+        let resolution = RenderResolution::new(1920, 1080);
+
+        let (origin, direction) = screen_to_ray(&view, &perspective, Vec2::new(960.0, 540.0), &resolution);
+
+        assert!(direction.abs_diff_eq(view.get_normal(), 1e-4));
+
+        // origin lies on the near plane in front of the eye
+        assert!((origin - (view.eye + view.get_normal() * 0.2)).length() < 1e-3);
+    }
+
+    // a triangle in the z = 0 plane, counter-clockwise when looked at from negative z
+    const TRIANGLE: [Vec3; 3] = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)];
+
+    #[test]
+    fn front_face_test() {
+
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+
+        // camera on negative z looking towards positive z sees the front face, which is not culled
+        let front = RenderView::new(Vec3::new(0.0, 0.0, -5.0), Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0));
+
+        let winding = screen_winding(&front, &perspective, TRIANGLE).unwrap();
+
+        assert_eq!(winding, Winding::CounterClockwise);
+        assert!(!CullMode::default().culls(winding));
+
+        // reversed vertex order is a back face and gets culled
+        let reversed = screen_winding(&front, &perspective, [TRIANGLE[0], TRIANGLE[2], TRIANGLE[1]]).unwrap();
+
+        assert_eq!(reversed, Winding::Clockwise);
+        assert!(CullMode::default().culls(reversed));
+    }
+
+    #[test]
+    fn back_face_test() {
+
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+
+        // from positive z the same triangle shows its back and is culled, unless culling is off
+        let back = RenderView::new(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0));
+
+        let winding = screen_winding(&back, &perspective, TRIANGLE).unwrap();
+
+        assert_eq!(winding, Winding::Clockwise);
+        assert!(CullMode::default().culls(winding));
+        assert!(!CullMode::None.culls(winding));
+    }
+
+}
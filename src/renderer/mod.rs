@@ -0,0 +1,159 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use glam::{Mat4, Vec3};
+use log::error;
+#[cfg(feature = "debug-ui")]
+use crate::debug_ui::DebugUiFrame;
+use crate::events::{EngineErrorEvent, ErrorSource};
+use crate::scene::manager::Transition;
+use crate::scene::scene::Scene;
+use crate::shader::ShaderContainer;
+
+pub mod bgfx_backend;
+pub mod debug;
+pub mod error;
+pub mod events;
+pub mod math;
+pub mod null;
+pub mod transition;
+pub mod types;
+
+// old XGEngine::renderer::renderer paths, kept for one release
+#[deprecated(note = "import from XGEngine::renderer instead")]
+pub mod renderer;
+
+pub use bgfx_backend::BgfxRenderer;
+pub use debug::{DebugLine, TextDebugData};
+pub use error::RendererError;
+pub use math::{screen_to_ray, world_to_screen};
+pub use null::{NullRenderer, NullRendererState};
+pub use types::{LayerProjection, MoveDirection, RenderPerspective, RenderResolution, RenderStats, RenderView, SceneLayer};
+
+// layers Renderer::set_scene_layers draws, further ones are ignored
+pub const MAX_SCENE_LAYERS: usize = 8;
+
+// smallest width and height Renderer::resize hands to the backend
+pub const MIN_SURFACE_SIZE: u32 = 1;
+
+// errors found while rendering. the renderer, the scene and the shaders are borrowed then, so
+// they are kept until the engine dispatches them, see Renderer::take_errors
+#[derive(Default)]
+pub struct PendingErrors {
+    errors: RefCell<Vec<EngineErrorEvent>>
+}
+
+impl PendingErrors {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // logs the error right away, one that is already pending is not kept twice so an object
+    // drawn many times reports once per frame
+    pub fn push(&self, source: ErrorSource, message: String, recoverable: bool) {
+
+        error!("{}", message);
+
+        let mut errors = self.errors.borrow_mut();
+
+        if !errors.iter().any(|pending| pending.source == source && pending.message == message) {
+            errors.push(EngineErrorEvent::new(source, message, recoverable));
+        }
+    }
+
+    pub fn take(&self) -> Vec<EngineErrorEvent> {
+        self.errors.take()
+    }
+
+}
+
+pub trait Renderer {
+
+    // initializes all resources required for rendering
+    fn init(&mut self) -> Result<(), RendererError>;
+
+    // true between a successful init and shutdown
+    fn is_initialized(&self) -> bool;
+
+    // do one cycle which does all action in native render framework required for object render
+    fn do_render_cycle(&mut self) -> Result<(), RendererError>;
+
+    fn shutdown(&mut self);
+
+    // replaces the scene of the bottom layer, the layers above stay
+    fn set_scene(&mut self, scene: Rc<RefCell<Scene>>);
+
+    // scenes drawn every frame, bottom first, each into its own view with its own projection.
+    // z-ordering: a layer is drawn after the one below with the depth buffer cleared, so it is
+    // always on top regardless of depth. only the bottom layer clears the color, and scene
+    // transitions only fade the bottom layer. at most MAX_SCENE_LAYERS are drawn
+    fn set_scene_layers(&mut self, layers: Vec<SceneLayer>);
+
+    // swaps the scene using the transition, backends without transition support swap instantly
+    fn set_scene_with_transition(&mut self, scene: Rc<RefCell<Scene>>, _transition: Transition) {
+        self.set_scene(scene);
+    }
+
+    fn set_debug_data(&mut self, data: TextDebugData);
+    fn do_debug(&mut self, debug: bool);
+    fn clean_up(&mut self);
+    fn update_surface_resolution(&mut self, width: u32, height: u32);
+
+    // applies the surface size clamped to MIN_SURFACE_SIZE. a minimized window reports zero,
+    // which would give the projection a NaN aspect and reset bgfx to an empty backbuffer
+    fn resize(&mut self, width: u32, height: u32) {
+        self.update_surface_resolution(width.max(MIN_SURFACE_SIZE), height.max(MIN_SURFACE_SIZE));
+    }
+    fn update_perspective(&mut self, perspective: RenderPerspective);
+    fn get_perspective(&self) -> RenderPerspective;
+    fn get_stats(&self) -> RenderStats;
+    fn draw_grid(&mut self, enabled: bool, spacing: f32, extent: f32);
+    fn draw_axes(&mut self, enabled: bool);
+
+    // draws triangles as outlines
+    fn set_wireframe(&mut self, enabled: bool);
+
+    // waits for the display refresh before presenting, from the next frame on
+    fn set_vsync(&mut self, enabled: bool);
+
+    // resets the surface and recreates size dependent targets on the next frame, see RefreshEvent
+    fn refresh(&mut self);
+
+    // drops every cached gpu resource so the next frame rebuilds them, see ClearEvent
+    fn clear_caches(&mut self);
+
+    // draws the basis of the scene camera every frame, see debug::camera_gizmo_vertices
+    fn show_camera_gizmo(&mut self, enabled: bool);
+
+    // only objects in one of the layers of the mask are drawn, see scene::object::LAYER_WORLD
+    fn set_layer_mask(&mut self, mask: u32);
+
+    // world space lines drawn with the debug shader for the next frame only, so they
+    // have to be added again every frame they should stay visible
+    fn debug_line(&mut self, start: Vec3, end: Vec3, color: u32);
+    fn debug_aabb(&mut self, min: Vec3, max: Vec3, color: u32);
+    fn debug_axes(&mut self, transform: Mat4);
+
+    // shader used for debug lines, it has to consume Position and Color0 like ColoredVertex
+    fn set_debug_shader(&mut self, shader: Rc<RefCell<Box<dyn ShaderContainer>>>);
+
+    // shader blending the outgoing scene during a fade, it consumes Position and TexCoord0 like
+    // the TEXTURED layout, samples s_texColor and takes the opacity from u_fade.x
+    fn set_transition_shader(&mut self, shader: Rc<RefCell<Box<dyn ShaderContainer>>>);
+
+    // debug ui drawn over the scene by the next render cycle, backends without ui support drop it
+    #[cfg(feature = "debug-ui")]
+    fn draw_debug_ui(&mut self, _frame: DebugUiFrame) {}
+
+    // shader for the debug ui, it consumes Position as 2 floats, TexCoord0 and Color0 like egui
+    // vertices, samples s_texColor and multiplies it with the premultiplied vertex color
+    #[cfg(feature = "debug-ui")]
+    fn set_debug_ui_shader(&mut self, _shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {}
+
+    // errors of the render cycles since the last call, the engine dispatches them as
+    // EngineErrorEvent once the renderer is released
+    fn take_errors(&mut self) -> Vec<EngineErrorEvent> {
+        Vec::new()
+    }
+
+}
@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+use glam::{Mat4, Vec3};
+use crate::events::{EngineErrorEvent, ErrorSource};
+use crate::renderer::{MAX_SCENE_LAYERS, PendingErrors, Renderer};
+use crate::renderer::debug::TextDebugData;
+use crate::renderer::error::RendererError;
+use crate::renderer::types::{LayerProjection, RenderPerspective, RenderStats, SceneLayer};
+use crate::scene::manager::Transition;
+use crate::scene::object::LAYER_ALL;
+use crate::scene::scene::Scene;
+use crate::shader::ShaderContainer;
+
+// everything the NullRenderer was asked to do
+#[derive(Default)]
+pub struct NullRendererState {
+    pub init_calls: u32,
+    pub frames: u64,
+    pub scenes: Vec<String>,
+    pub transitions: Vec<Transition>,
+    // scene names of every set_scene_layers call, bottom first
+    pub layers: Vec<Vec<String>>,
+    pub resolutions: Vec<(u32, u32)>,
+    pub perspectives: Vec<RenderPerspective>,
+    pub debug: Vec<bool>,
+    pub wireframe: Vec<bool>,
+    pub vsync: Vec<bool>,
+    pub clean_up_calls: u32,
+    pub refresh_calls: u32,
+    pub clear_cache_calls: u32,
+    pub shutdown_calls: u32,
+    // objects of the current chunks of all scene layers inside the layer mask in the last frame
+    pub drawn_objects: usize
+}
+
+// renderer that touches no GPU api and records its calls, for headless runs and tests.
+// keep the handle from state() before boxing the renderer into the engine to inspect it later
+pub struct NullRenderer {
+    state: Rc<RefCell<NullRendererState>>,
+    layers: Vec<SceneLayer>,
+    perspective: RenderPerspective,
+    stats: RenderStats,
+    layer_mask: u32,
+    errors: PendingErrors
+}
+
+impl NullRenderer {
+
+    // constructor
+    pub fn new(perspective: RenderPerspective) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(NullRendererState::default())),
+            layers: Vec::new(),
+            perspective,
+            stats: RenderStats::default(),
+            layer_mask: LAYER_ALL,
+            errors: PendingErrors::new()
+        }
+    }
+
+    pub fn state(&self) -> Rc<RefCell<NullRendererState>> {
+        Rc::clone(&self.state)
+    }
+
+}
+
+impl Renderer for NullRenderer {
+
+    fn init(&mut self) -> Result<(), RendererError> {
+        self.state.borrow_mut().init_calls += 1;
+        Ok(())
+    }
+
+    fn is_initialized(&self) -> bool {
+        let state = self.state.borrow();
+        state.init_calls > state.shutdown_calls
+    }
+
+    fn do_render_cycle(&mut self) -> Result<(), RendererError> {
+
+        let cycle_start = Instant::now();
+
+        if self.layers.is_empty() {
+            self.errors.push(ErrorSource::Renderer, String::from("Scene is not initialized"), false);
+            return Err(RendererError::NoScene);
+        }
+
+        // counts what a real renderer would submit
+        let mut drawn_objects = 0;
+        let mut stats = RenderStats::default();
+
+        for layer in self.layers.iter().take(MAX_SCENE_LAYERS) {
+
+            match layer.scene.borrow().get_current_chunk() {
+                Ok(chunk) => {
+
+                    stats.chunks_visited += 1;
+
+                    for object in &chunk.iter_objects() {
+                        if object.in_layers(self.layer_mask) {
+                            drawn_objects += 1;
+                            stats.record_draw(object.geometry_counts());
+                        }
+                    }
+                }
+                Err(e) => self.errors.push(ErrorSource::Scene, format!("Failed to get current chunk: {}", e), true)
+            }
+        }
+
+        stats.frame_ms = cycle_start.elapsed().as_secs_f32() * 1000.0;
+        self.stats = stats;
+
+        let mut state = self.state.borrow_mut();
+        state.frames += 1;
+        state.drawn_objects = drawn_objects;
+
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        self.state.borrow_mut().shutdown_calls += 1;
+    }
+
+    fn take_errors(&mut self) -> Vec<EngineErrorEvent> {
+        self.errors.take()
+    }
+
+    fn set_scene(&mut self, scene: Rc<RefCell<Scene>>) {
+        self.state.borrow_mut().scenes.push(scene.borrow().name.clone());
+
+        match self.layers.first_mut() {
+            Some(layer) => layer.scene = scene,
+            None => self.layers.push(SceneLayer::new(scene, LayerProjection::Perspective))
+        }
+    }
+
+    fn set_scene_layers(&mut self, layers: Vec<SceneLayer>) {
+        self.state.borrow_mut().layers.push(layers.iter().map(|layer| layer.scene.borrow().name.clone()).collect());
+        self.layers = layers;
+    }
+
+    fn set_scene_with_transition(&mut self, scene: Rc<RefCell<Scene>>, transition: Transition) {
+        self.state.borrow_mut().transitions.push(transition);
+        self.set_scene(scene);
+    }
+
+    fn set_debug_data(&mut self, _data: TextDebugData) {}
+
+    fn do_debug(&mut self, debug: bool) {
+        self.state.borrow_mut().debug.push(debug);
+    }
+
+    fn clean_up(&mut self) {
+        self.state.borrow_mut().clean_up_calls += 1;
+    }
+
+    fn set_vsync(&mut self, enabled: bool) {
+        self.state.borrow_mut().vsync.push(enabled);
+    }
+
+    fn refresh(&mut self) {
+        self.state.borrow_mut().refresh_calls += 1;
+    }
+
+    fn clear_caches(&mut self) {
+        self.state.borrow_mut().clear_cache_calls += 1;
+    }
+
+    fn update_surface_resolution(&mut self, width: u32, height: u32) {
+        self.perspective.set_aspect(width, height);
+        self.state.borrow_mut().resolutions.push((width, height));
+    }
+
+    fn update_perspective(&mut self, perspective: RenderPerspective) {
+        self.perspective = perspective;
+        self.state.borrow_mut().perspectives.push(perspective);
+    }
+
+    fn get_perspective(&self) -> RenderPerspective {
+        self.perspective
+    }
+
+    fn get_stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    fn draw_grid(&mut self, _enabled: bool, _spacing: f32, _extent: f32) {}
+
+    fn draw_axes(&mut self, _enabled: bool) {}
+
+    fn set_wireframe(&mut self, enabled: bool) {
+        self.state.borrow_mut().wireframe.push(enabled);
+    }
+
+    fn show_camera_gizmo(&mut self, _enabled: bool) {}
+
+    fn set_layer_mask(&mut self, mask: u32) {
+        self.layer_mask = mask;
+    }
+
+    fn debug_line(&mut self, _start: Vec3, _end: Vec3, _color: u32) {}
+
+    fn debug_aabb(&mut self, _min: Vec3, _max: Vec3, _color: u32) {}
+
+    fn debug_axes(&mut self, _transform: Mat4) {}
+
+    fn set_debug_shader(&mut self, _shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {}
+
+    fn set_transition_shader(&mut self, _shader: Rc<RefCell<Box<dyn ShaderContainer>>>) {}
+}
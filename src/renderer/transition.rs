@@ -0,0 +1,67 @@
+// progress of a crossfade between the outgoing and the incoming scene
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneFade {
+    duration: f32,
+    elapsed: f32
+}
+
+impl SceneFade {
+
+    // constructor
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration: duration.max(0.0),
+            elapsed: 0.0
+        }
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta.max(0.0)).min(self.duration);
+    }
+
+    // opacity of the outgoing scene drawn over the incoming one, 1 at the start and 0 at the end
+    pub fn alpha(&self) -> f32 {
+
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+
+        1.0 - self.elapsed / self.duration
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_test() {
+
+        let mut fade = SceneFade::new(2.0);
+
+        assert_eq!(fade.alpha(), 1.0);
+        assert!(!fade.finished());
+
+        fade.advance(0.5);
+        assert_eq!(fade.alpha(), 0.75);
+
+        fade.advance(5.0);
+        assert_eq!(fade.alpha(), 0.0);
+        assert!(fade.finished());
+    }
+
+    #[test]
+    fn zero_duration_test() {
+
+        let fade = SceneFade::new(0.0);
+
+        assert_eq!(fade.alpha(), 0.0);
+        assert!(fade.finished());
+    }
+
+}
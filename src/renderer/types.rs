@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use glam::{Mat4, Vec3};
+use crate::renderer::debug::DebugLine;
+use crate::scene::scene::Scene;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderPerspective {
+    pub width: u32,
+    pub height: u32,
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32
+}
+
+impl RenderPerspective {
+
+    // constructor
+    pub fn new(width: u32, height: u32, fov: f32, near: f32, far: f32) -> Self {
+        Self {
+            width,
+            height,
+            fov: fov * (std::f32::consts::PI / 180.0),
+            near,
+            far
+        }
+    }
+
+    // follows the framebuffer size, a minimized window (zero size) keeps the previous aspect
+    pub fn set_aspect(&mut self, width: u32, height: u32) {
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+}
+
+pub struct RenderView {
+    pub eye: Vec3,
+    pub at: Vec3,
+    pub up: Vec3
+}
+
+pub enum MoveDirection {
+    FORWARD, BACKWARDS, LEFT, RIGHT
+}
+
+impl RenderView {
+
+    // constructor
+    pub fn new(eye: Vec3, at: Vec3, up: Vec3) -> Self {
+        Self {
+            eye, at, up
+        }
+    }
+
+    pub fn set_eye(&mut self, eye: Vec3) {
+        self.eye = eye;
+    }
+
+    pub fn set_at(&mut self, at: Vec3) {
+        self.at = at;
+    }
+
+    pub fn set_up(&mut self, up: Vec3) {
+        self.up = up;
+    }
+
+    // calculates normal direction from at and eye
+    pub fn get_normal(&self) -> Vec3 {
+        (self.at - self.eye).normalize()
+    }
+
+    // moves eye in normal direction
+    pub fn move_eye(&mut self, distance: f32, direction: MoveDirection) {
+
+        match direction {
+            MoveDirection::FORWARD => self.eye += self.get_normal() * distance,
+            MoveDirection::BACKWARDS => self.eye -= self.get_normal() * distance,
+
+            // move eye left or right
+            MoveDirection::LEFT => {
+                let normal = self.get_normal();
+                let left = Vec3::new(normal.z, normal.y, -normal.x);
+                self.eye += left * distance;
+            },
+
+            MoveDirection::RIGHT => {
+                let normal = self.get_normal();
+                let right = Vec3::new(-normal.z, normal.y, normal.x);
+                self.eye += right * distance;
+            },
+        }
+
+    }
+
+}
+
+// projection a scene layer is drawn with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LayerProjection {
+    // the renderer perspective, for world scenes
+    Perspective,
+    // orthographic in surface pixels with the origin top left and y growing downwards, for ui.
+    // z from -1000 to 1000 is visible
+    Screen,
+    Custom(Mat4)
+}
+
+impl LayerProjection {
+
+    pub fn matrix(&self, perspective: &RenderPerspective, (width, height): (u32, u32)) -> Mat4 {
+        match self {
+            LayerProjection::Perspective => perspective.projection_matrix(),
+            LayerProjection::Screen => Mat4::orthographic_lh(0.0, width.max(1) as f32, height.max(1) as f32, 0.0, -1000.0, 1000.0),
+            LayerProjection::Custom(matrix) => *matrix
+        }
+    }
+
+}
+
+// scene drawn in one layer of the frame, see Renderer::set_scene_layers
+#[derive(Clone)]
+pub struct SceneLayer {
+    pub scene: Rc<RefCell<Scene>>,
+    pub projection: LayerProjection
+}
+
+impl SceneLayer {
+
+    pub fn new(scene: Rc<RefCell<Scene>>, projection: LayerProjection) -> Self {
+        Self {
+            scene, projection
+        }
+    }
+
+}
+
+pub struct RenderResolution {
+    pub width: u32,
+    pub height: u32
+}
+
+impl RenderResolution {
+
+    // constructor
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width, height
+        }
+    }
+
+    pub(crate) fn update(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub(crate) fn from(&mut self, other: &Self) {
+        self.width = other.width.clone();
+        self.height = other.height.clone();
+    }
+
+}
+
+impl PartialEq<Self> for RenderResolution {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height
+    }
+}
+
+impl Eq for RenderResolution {}
+
+// backend agnostic copy of the renderer statistics, fields a backend cannot provide stay zero
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    pub gpu_ms: f32,
+    pub vram_used: u64,
+    pub num_draw_calls: u32,
+    pub num_views: u16,
+    // scene geometry submitted in the last frame, counted by the renderer itself
+    pub triangles: u32,
+    pub vertices: u32,
+    pub chunks_visited: u32,
+    // cpu time the last render cycle took
+    pub frame_ms: f32
+}
+
+impl RenderStats {
+
+    // counts a draw call of an object with the given geometry, without indices every three
+    // vertices are a triangle
+    pub fn record_draw(&mut self, (vertices, indices): (usize, usize)) {
+
+        let drawn = if indices == 0 { vertices } else { indices };
+
+        self.num_draw_calls += 1;
+        self.vertices += vertices as u32;
+        self.triangles += (drawn / 3) as u32;
+    }
+
+    // lines appended to the debug overlay
+    pub fn debug_lines(&self) -> Vec<DebugLine> {
+        vec![
+            DebugLine::new(String::from("gpu_ms"), format!("{:.3}", self.gpu_ms)),
+            DebugLine::new(String::from("vram_used"), format!("{} KiB", self.vram_used / 1024)),
+            DebugLine::new(String::from("num_draw_calls"), self.num_draw_calls.to_string()),
+            DebugLine::new(String::from("triangles"), self.triangles.to_string()),
+            DebugLine::new(String::from("frame_ms"), format!("{:.3}", self.frame_ms)),
+        ]
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_projection_test() {
+
+        let perspective = RenderPerspective::new(800, 600, 60.0, 0.1, 100.0);
+        let projection = LayerProjection::Screen.matrix(&perspective, (800, 600));
+
+        // pixel corners map to the clip space corners, top left is up
+        assert!(projection.project_point3(Vec3::new(0.0, 0.0, 0.0)).truncate().abs_diff_eq(glam::Vec2::new(-1.0, 1.0), 1e-6));
+        assert!(projection.project_point3(Vec3::new(800.0, 600.0, 0.0)).truncate().abs_diff_eq(glam::Vec2::new(1.0, -1.0), 1e-6));
+
+        assert_eq!(LayerProjection::Perspective.matrix(&perspective, (800, 600)), perspective.projection_matrix());
+        assert!(LayerProjection::Screen.matrix(&perspective, (0, 0)).is_finite());
+    }
+
+    #[test]
+    fn stats_debug_lines_test() {
+
+        let stats = RenderStats { gpu_ms: 1.5, vram_used: 4096, num_draw_calls: 3, num_views: 1, triangles: 12, frame_ms: 0.25, ..Default::default() };
+
+        let lines = stats.debug_lines();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0].value(), "1.500");
+        assert_eq!(lines[1].value(), "4 KiB");
+        assert_eq!(lines[2].value(), "3");
+        assert_eq!(lines[3].value(), "12");
+        assert_eq!(lines[4].value(), "0.250");
+    }
+
+    #[test]
+    fn record_draw_test() {
+
+        let mut stats = RenderStats::default();
+
+        stats.record_draw((8, 36));
+        stats.record_draw((4, 6));
+
+        assert_eq!((stats.num_draw_calls, stats.vertices, stats.triangles), (2, 12, 14));
+
+        // non indexed
+        stats.record_draw((6, 0));
+        assert_eq!((stats.num_draw_calls, stats.vertices, stats.triangles), (3, 18, 16));
+    }
+
+}
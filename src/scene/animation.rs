@@ -0,0 +1,251 @@
+use glam::{Quat, Vec3};
+use uuid::Uuid;
+use crate::scene::scene::Scene;
+
+pub struct Keyframe {
+    pub time: f32,
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3
+}
+
+impl Keyframe {
+
+    // constructor
+    pub fn new(time: f32, position: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            time, position, rotation, scale
+        }
+    }
+
+}
+
+pub struct Animation {
+    keyframes: Vec<Keyframe>
+}
+
+impl Animation {
+
+    // constructor, keyframes are sorted by time
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Self {
+
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self {
+            keyframes
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        match self.keyframes.last() {
+            Some(keyframe) => keyframe.time,
+            None => 0.0
+        }
+    }
+
+    // interpolated transform at time t, clamped to the first and last keyframe
+    pub fn sample(&self, t: f32) -> (Vec3, Quat, Vec3) {
+
+        let first = match self.keyframes.first() {
+            Some(first) => first,
+            None => return (Vec3::ZERO, Quat::IDENTITY, Vec3::ONE)
+        };
+
+        if t <= first.time {
+            return (first.position, first.rotation, first.scale);
+        }
+
+        for pair in self.keyframes.windows(2) {
+
+            let (from, to) = (&pair[0], &pair[1]);
+
+            if t < to.time {
+
+                let factor = (t - from.time) / (to.time - from.time);
+
+                return (
+                    from.position.lerp(to.position, factor),
+                    from.rotation.slerp(to.rotation, factor),
+                    from.scale.lerp(to.scale, factor)
+                );
+            }
+
+        }
+
+        let last = self.keyframes.last().unwrap();
+
+        (last.position, last.rotation, last.scale)
+    }
+
+}
+
+pub struct AnimationPlayer {
+    pub animation: Animation,
+    pub target: Uuid,
+    pub looping: bool,
+    pub playing: bool,
+    time: f32
+}
+
+impl AnimationPlayer {
+
+    // constructor
+    pub fn new(animation: Animation, target: Uuid) -> Self {
+        Self {
+            animation,
+            target,
+            looping: false,
+            playing: true,
+            time: 0.0
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    // moves the playhead by delta seconds, wrapping or stopping at the end
+    pub fn advance(&mut self, delta: f32) {
+
+        if !self.playing {
+            return;
+        }
+
+        self.time += delta;
+
+        let duration = self.animation.duration();
+
+        if self.time >= duration {
+
+            if self.looping && duration > 0.0 {
+                self.time %= duration;
+            } else {
+                self.time = duration;
+                self.playing = false;
+            }
+
+        }
+    }
+
+    // writes the sampled transform into the target object, false when the target is not in the scene
+    pub fn apply(&self, scene: &Scene) -> bool {
+
+        let (position, rotation, scale) = self.animation.sample(self.time);
+
+        scene.with_object_mut(self.target, |object| object.set_transform(position, rotation, scale)).is_some()
+    }
+
+    // advances by the tick delta and applies the result
+    pub fn update(&mut self, delta: f32, scene: &Scene) -> bool {
+        self.advance(delta);
+        self.apply(scene)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec2, Vec2};
+    use crate::renderer::RenderView;
+    use crate::scene::chunk::Chunk;
+    use crate::scene::object::{ColoredSceneObject, test_shaders};
+    use super::*;
+
+    fn animation() -> Animation {
+        Animation::new(vec![
+            Keyframe::new(2.0, Vec3::new(10.0, 0.0, 0.0), Quat::from_rotation_y(std::f32::consts::PI), Vec3::splat(3.0)),
+            Keyframe::new(0.0, Vec3::ZERO, Quat::IDENTITY, Vec3::ONE),
+        ])
+    }
+
+    #[test]
+    fn sample_boundaries_test() {
+
+        let animation = animation();
+
+        assert_eq!(animation.duration(), 2.0);
+
+        let (position, rotation, scale) = animation.sample(0.0);
+        assert_eq!(position, Vec3::ZERO);
+        assert_eq!(rotation, Quat::IDENTITY);
+        assert_eq!(scale, Vec3::ONE);
+
+        let (position, _, scale) = animation.sample(2.0);
+        assert_eq!(position, Vec3::new(10.0, 0.0, 0.0));
+        assert_eq!(scale, Vec3::splat(3.0));
+
+        // outside the range the ends are held
+        assert_eq!(animation.sample(-1.0).0, Vec3::ZERO);
+        assert_eq!(animation.sample(5.0).0, Vec3::new(10.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn sample_midpoint_test() {
+
+        let animation = animation();
+
+        let (position, rotation, scale) = animation.sample(1.0);
+
+        assert_eq!(position, Vec3::new(5.0, 0.0, 0.0));
+        assert_eq!(scale, Vec3::splat(2.0));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2), 1e-5));
+    }
+
+    #[test]
+    fn empty_animation_test() {
+        let animation = Animation::new(Vec::new());
+        assert_eq!(animation.sample(1.0), (Vec3::ZERO, Quat::IDENTITY, Vec3::ONE));
+    }
+
+    #[test]
+    fn player_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        let object = ColoredSceneObject::new(
+            Box::new([]),
+            Box::new([]),
+            test_shaders(),
+            Vec3::ZERO
+        );
+
+        let id = object.id;
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(Box::new(object));
+        scene.add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        let mut player = AnimationPlayer::new(animation(), id);
+
+        assert!(player.update(0.5, &scene));
+        assert!(player.update(0.5, &scene));
+
+        let coordinates = scene.with_object_mut(id, |object| {
+            object.as_any().downcast_ref::<ColoredSceneObject>().unwrap().coordinates
+        });
+
+        assert_eq!(coordinates, Some(Vec3::new(5.0, 0.0, 0.0)));
+
+        // playing past the end stops on the last keyframe
+        player.update(5.0, &scene);
+        assert!(!player.playing);
+        assert_eq!(player.time(), 2.0);
+
+        // unknown targets are reported
+        let player = AnimationPlayer::new(animation(), Uuid::new_v4());
+        assert!(!player.apply(&scene));
+    }
+
+    #[test]
+    fn looping_test() {
+
+        let mut player = AnimationPlayer::new(animation(), Uuid::new_v4());
+        player.looping = true;
+
+        player.advance(2.5);
+
+        assert!(player.playing);
+        assert_eq!(player.time(), 0.5);
+    }
+
+}
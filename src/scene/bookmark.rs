@@ -0,0 +1,107 @@
+use glam::Vec3;
+use crate::renderer::RenderView;
+
+// saved camera placement, see Scene::save_camera_bookmark
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraBookmark {
+    pub eye: Vec3,
+    pub at: Vec3,
+    pub up: Vec3
+}
+
+impl CameraBookmark {
+
+    pub fn of(view: &RenderView) -> Self {
+        Self {
+            eye: view.eye,
+            at: view.at,
+            up: view.up
+        }
+    }
+
+    pub fn apply(&self, view: &mut RenderView) {
+        view.eye = self.eye;
+        view.at = self.at;
+        view.up = self.up;
+    }
+
+    // eased blend towards other, factor is clamped to 0..=1 and ends exactly on other
+    pub fn blend(&self, other: &CameraBookmark, factor: f32) -> CameraBookmark {
+
+        if factor >= 1.0 {
+            return *other;
+        }
+
+        let factor = factor.max(0.0);
+        let eased = factor * factor * (3.0 - 2.0 * factor);
+
+        CameraBookmark {
+            eye: self.eye.lerp(other.eye, eased),
+            at: self.at.lerp(other.at, eased),
+            up: self.up.lerp(other.up, eased)
+        }
+    }
+
+}
+
+// camera moving between two placements over a number of seconds, advanced by Scene::update
+#[derive(Clone, Copy, Debug)]
+pub struct CameraFlight {
+    from: CameraBookmark,
+    to: CameraBookmark,
+    duration: f32,
+    elapsed: f32
+}
+
+impl CameraFlight {
+
+    pub fn new(from: CameraBookmark, to: CameraBookmark, duration: f32) -> Self {
+        Self {
+            from, to,
+            duration: duration.max(0.0),
+            elapsed: 0.0
+        }
+    }
+
+    // placement after another delta seconds
+    pub fn advance(&mut self, delta: f32) -> CameraBookmark {
+
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+
+        if self.duration == 0.0 {
+            return self.to;
+        }
+
+        self.from.blend(&self.to, self.elapsed / self.duration)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flight_test() {
+
+        let from = CameraBookmark { eye: Vec3::ZERO, at: Vec3::Z, up: Vec3::Y };
+        let to = CameraBookmark { eye: Vec3::new(10.0, 0.0, 0.0), at: Vec3::new(10.0, 0.0, 1.0), up: Vec3::Y };
+
+        let mut flight = CameraFlight::new(from, to, 2.0);
+
+        // halfway of the eased curve is halfway of the way
+        assert_eq!(flight.advance(1.0).eye, Vec3::new(5.0, 0.0, 0.0));
+        assert!(!flight.finished());
+
+        // overshooting the duration lands exactly on the target
+        assert_eq!(flight.advance(5.0), to);
+        assert!(flight.finished());
+
+        assert_eq!(CameraFlight::new(from, to, 0.0).advance(0.0), to);
+    }
+
+}
@@ -0,0 +1,111 @@
+use glam::{Quat, Vec3};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl Aabb {
+
+    // constructor
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min, max
+        }
+    }
+
+    // smallest box containing all points, a zero sized box at the origin when there are none
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+
+        let mut iter = points.into_iter();
+
+        let first = match iter.next() {
+            Some(first) => first,
+            None => return Self::new(Vec3::ZERO, Vec3::ZERO)
+        };
+
+        let mut bounds = Self::new(first, first);
+
+        for point in iter {
+            bounds.min = bounds.min.min(point);
+            bounds.max = bounds.max.max(point);
+        }
+
+        bounds
+    }
+
+    // bounds of local points after applying scale, rotation and translation
+    pub fn from_transformed<I: IntoIterator<Item = Vec3>>(points: I, coordinates: Vec3, rotation: Quat, scale: Vec3) -> Self {
+
+        let mut points = points.into_iter().peekable();
+
+        if points.peek().is_none() {
+            return Self::new(coordinates, coordinates);
+        }
+
+        Self::from_points(points.map(|point| rotation * (point * scale) + coordinates))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    // touching boxes count as intersecting
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x &&
+            self.min.y <= other.max.y && self.max.y >= other.min.y &&
+            self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+            point.y >= self.min.y && point.y <= self.max.y &&
+            point.z >= self.min.z && point.z <= self.max.z
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points_test() {
+
+        let bounds = Aabb::from_points(vec![Vec3::new(1.0, -2.0, 3.0), Vec3::new(-1.0, 2.0, 0.0)]);
+
+        assert_eq!(bounds.min, Vec3::new(-1.0, -2.0, 0.0));
+        assert_eq!(bounds.max, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(bounds.center(), Vec3::new(0.0, 0.0, 1.5));
+    }
+
+    #[test]
+    fn from_transformed_test() {
+
+        let bounds = Aabb::from_transformed(vec![Vec3::ZERO, Vec3::ONE], Vec3::new(10.0, 0.0, 0.0), Quat::IDENTITY, Vec3::splat(2.0));
+
+        assert_eq!(bounds, Aabb::new(Vec3::new(10.0, 0.0, 0.0), Vec3::new(12.0, 2.0, 2.0)));
+
+        // no vertices collapse to the object position
+        let empty = Aabb::from_transformed(Vec::new(), Vec3::ONE, Quat::IDENTITY, Vec3::ONE);
+        assert_eq!(empty, Aabb::new(Vec3::ONE, Vec3::ONE));
+    }
+
+    #[test]
+    fn intersects_test() {
+
+        let a = Aabb::new(Vec3::ZERO, Vec3::ONE);
+
+        assert!(a.intersects(&Aabb::new(Vec3::splat(0.5), Vec3::splat(2.0))));
+        assert!(a.intersects(&Aabb::new(Vec3::ONE, Vec3::splat(2.0))));
+        assert!(!a.intersects(&Aabb::new(Vec3::splat(1.5), Vec3::splat(2.0))));
+        assert!(a.contains(Vec3::splat(0.5)));
+        assert!(!a.contains(Vec3::splat(1.5)));
+    }
+
+}
@@ -1,41 +1,174 @@
-use std::cell::{Ref, RefCell};
+use std::cell::{Ref, RefCell, RefMut};
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::sync::{Mutex, MutexGuard};
-use glam::{IVec2};
+use glam::{IVec2, Vec3};
 use uuid::Uuid;
+use crate::scene::bounds::Aabb;
 use crate::scene::object::{SceneObject};
+use crate::scene::spatial::SpatialGrid;
+
+// borrowed view of the chunk objects, released when dropped
+pub struct ChunkObjects<'a> {
+    objects: Ref<'a, [Box<dyn SceneObject>]>
+}
+
+// mutably borrowed view of the chunk objects, released when dropped
+pub struct ChunkObjectsMut<'a> {
+    objects: RefMut<'a, [Box<dyn SceneObject>]>
+}
+
+impl<'a> Deref for ChunkObjects<'a> {
+    type Target = [Box<dyn SceneObject>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.objects
+    }
+}
+
+impl<'a> Deref for ChunkObjectsMut<'a> {
+    type Target = [Box<dyn SceneObject>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.objects
+    }
+}
+
+impl<'a> DerefMut for ChunkObjectsMut<'a> {
+
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.objects
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b ChunkObjects<'a> {
+    type Item = &'b Box<dyn SceneObject>;
+    type IntoIter = std::slice::Iter<'b, Box<dyn SceneObject>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects.iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b mut ChunkObjectsMut<'a> {
+    type Item = &'b mut Box<dyn SceneObject>;
+    type IntoIter = std::slice::IterMut<'b, Box<dyn SceneObject>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects.iter_mut()
+    }
+}
 
 pub struct Chunk {
     pub coordinates: IVec2,
-    pub objects: RefCell<Vec<Box<dyn SceneObject>>>
+    pub objects: RefCell<Vec<Box<dyn SceneObject>>>,
+    spatial: RefCell<Option<SpatialGrid>>
 }
 
 impl Chunk {
 
     pub fn new(coordinates: IVec2) -> Self {
         Self {
-            coordinates, objects: RefCell::new(Vec::new())
+            coordinates, objects: RefCell::new(Vec::new()), spatial: RefCell::new(None)
         }
     }
 
-    pub fn add_object(&mut self, object: Box<dyn SceneObject>) -> usize {
+    // chunk with room for capacity objects before the Vec reallocates
+    pub fn with_capacity(coordinates: IVec2, capacity: usize) -> Self {
+        Self {
+            coordinates, objects: RefCell::new(Vec::with_capacity(capacity)), spatial: RefCell::new(None)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.borrow().is_empty()
+    }
+
+    // objects the chunk holds without reallocating
+    pub fn capacity(&self) -> usize {
+        self.objects.borrow().capacity()
+    }
+
+    // objects live in a RefCell, so chunks shared through the scene can be added to
+    pub fn add_object(&self, object: Box<dyn SceneObject>) -> usize {
 
         let index: usize = self.objects.borrow().len();
 
         self.objects.borrow_mut().push(object);
+        self.invalidate_spatial();
 
         index
     }
 
+    // removes the object at index, later indices shift down by one
+    pub fn remove_object(&mut self, index: usize) -> Option<Box<dyn SceneObject>> {
+
+        let mut objects = self.objects.borrow_mut();
+
+        if index >= objects.len() {
+            return None;
+        }
+
+        let object = objects.remove(index);
+
+        drop(objects);
+        self.invalidate_spatial();
+
+        Some(object)
+    }
+
+    // indices of objects whose bounding box intersects min..max, the grid is rebuilt lazily after changes
+    pub fn objects_in_aabb(&self, min: Vec3, max: Vec3) -> Vec<usize> {
+
+        let mut spatial = self.spatial.borrow_mut();
+
+        if spatial.is_none() {
+
+            let bounds: Vec<Aabb> = self.iter_objects().iter().map(|object| object.bounding_box()).collect();
+
+            *spatial = Some(SpatialGrid::build(bounds));
+        }
+
+        spatial.as_ref().unwrap().query(&Aabb::new(min, max))
+    }
+
+    // drops the spatial grid, needed after moving objects through the public objects field
+    pub fn invalidate_spatial(&self) {
+        *self.spatial.borrow_mut() = None;
+    }
+
+    // borrows the objects for reading, iterate with `for object in &chunk.iter_objects()`
+    pub fn iter_objects(&self) -> ChunkObjects<'_> {
+        ChunkObjects {
+            objects: Ref::map(self.objects.borrow(), |objects| objects.as_slice())
+        }
+    }
+
+    // borrows the objects for writing, iterate with `for object in &mut chunk.iter_objects_mut()`
+    // objects may move, so the spatial grid is invalidated
+    pub fn iter_objects_mut(&self) -> ChunkObjectsMut<'_> {
+
+        self.invalidate_spatial();
+
+        ChunkObjectsMut {
+            objects: RefMut::map(self.objects.borrow_mut(), |objects| objects.as_mut_slice())
+        }
+    }
+
 }
 
 #[cfg(test)]
 mod tests {
     use std::cell::{Cell, RefCell};
     use std::rc::Rc;
-    use glam::IVec2;
+    use glam::{IVec2, Vec3};
     use crate::scene::chunk::Chunk;
+    use crate::scene::bounds::Aabb;
+    use crate::scene::object::{ColoredSceneObject, test_cube, test_shaders};
 
     #[test]
     fn test() {
@@ -56,4 +189,126 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn iter_objects_test() {
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+
+        chunk.add_object(Box::new(ColoredSceneObject::new(
+            Box::new([]),
+            Box::new([]),
+            test_shaders(),
+            Vec3::new(1.0, 0.0, 0.0)
+        )));
+
+        chunk.add_object(Box::new(ColoredSceneObject::new(
+            Box::new([]),
+            Box::new([]),
+            test_shaders(),
+            Vec3::new(2.0, 0.0, 0.0)
+        )));
+
+        for object in &mut chunk.iter_objects_mut() {
+            let colored = object.as_any_mut().downcast_mut::<ColoredSceneObject>().unwrap();
+            colored.coordinates.y = 5.0;
+        }
+
+        let mut count = 0;
+
+        for object in &chunk.iter_objects() {
+            let colored = object.as_any().downcast_ref::<ColoredSceneObject>().unwrap();
+            assert_eq!(colored.coordinates.y, 5.0);
+            count += 1;
+        }
+
+        assert_eq!(count, 2);
+        assert_eq!(chunk.iter_objects().len(), 2);
+
+        // the borrow is released once the view is dropped
+        assert!(chunk.objects.try_borrow_mut().is_ok());
+    }
+
+    #[test]
+    fn len_test() {
+
+        let mut chunk = Chunk::with_capacity(IVec2::new(0, 0), 16);
+
+        assert!(chunk.is_empty());
+        assert_eq!(chunk.len(), 0);
+        assert!(chunk.capacity() >= 16);
+
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::ZERO)));
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::ONE)));
+
+        assert!(!chunk.is_empty());
+        assert_eq!(chunk.len(), 2);
+
+        chunk.remove_object(0);
+        assert_eq!(chunk.len(), 1);
+
+        chunk.remove_object(0);
+        assert!(chunk.is_empty());
+
+        // reserved space is kept after removing
+        assert!(chunk.capacity() >= 16);
+    }
+
+    #[test]
+    fn objects_in_aabb_test() {
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+
+        // deterministic scatter of cubes with varying sizes
+        for i in 0..200 {
+            let x = ((i * 37) % 101) as f32 - 50.0;
+            let y = ((i * 17) % 13) as f32 - 6.0;
+            let z = ((i * 53) % 97) as f32 - 48.0;
+            chunk.add_object(Box::new(test_cube(1.0 + (i % 4) as f32, Vec3::new(x, y, z))));
+        }
+
+        let queries = [
+            (Vec3::new(-5.0, -5.0, -5.0), Vec3::new(5.0, 5.0, 5.0)),
+            (Vec3::new(10.0, 0.0, 10.0), Vec3::new(12.0, 1.0, 30.0)),
+            (Vec3::new(-100.0, -100.0, -100.0), Vec3::new(100.0, 100.0, 100.0)),
+            (Vec3::new(200.0, 200.0, 200.0), Vec3::new(201.0, 201.0, 201.0)),
+        ];
+
+        for (min, max) in queries.iter() {
+
+            let query = Aabb::new(*min, *max);
+
+            let brute_force: Vec<usize> = chunk.iter_objects().iter().enumerate()
+                .filter(|(_, object)| object.bounding_box().intersects(&query))
+                .map(|(index, _)| index)
+                .collect();
+
+            assert_eq!(chunk.objects_in_aabb(*min, *max), brute_force);
+        }
+    }
+
+    #[test]
+    fn objects_in_aabb_invalidation_test() {
+
+        let mut chunk = Chunk::new(IVec2::new(0, 0));
+
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::new(0.0, 0.0, 0.0))));
+
+        assert_eq!(chunk.objects_in_aabb(Vec3::splat(9.0), Vec3::splat(11.0)), Vec::<usize>::new());
+
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::new(10.0, 10.0, 10.0))));
+
+        assert_eq!(chunk.objects_in_aabb(Vec3::splat(9.0), Vec3::splat(11.0)), vec![1]);
+
+        chunk.remove_object(0);
+
+        assert_eq!(chunk.objects_in_aabb(Vec3::splat(9.0), Vec3::splat(11.0)), vec![0]);
+
+        // moving objects through the mutable iterator invalidates the grid too
+        for object in &mut chunk.iter_objects_mut() {
+            object.as_any_mut().downcast_mut::<ColoredSceneObject>().unwrap().coordinates = Vec3::ZERO;
+        }
+
+        assert_eq!(chunk.objects_in_aabb(Vec3::splat(9.0), Vec3::splat(11.0)), Vec::<usize>::new());
+    }
+
+}
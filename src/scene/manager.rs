@@ -4,29 +4,62 @@ use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use event_bus::{dispatch_event, Event, EventResult, subscribe_event};
 use glam::Vec3;
+use crate::error::XgError;
 use crate::events::ActionEvent;
-use crate::renderer::renderer::RenderView;
+use crate::renderer::RenderView;
 use crate::scene::scene::Scene;
 
+// how the renderer swaps scenes, the default is an instant swap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+    Instant,
+    // crossfade from the outgoing scene over the given seconds
+    Fade(f32)
+}
+
+impl Default for Transition {
+
+    fn default() -> Self {
+        Transition::Instant
+    }
+
+}
+
 pub struct SceneManager {
-    pub scene_map: Arc<Mutex<Box<HashMap<String, Rc<RefCell<Scene>>>>>>
+    pub scene_map: Arc<Mutex<Box<HashMap<String, Rc<RefCell<Scene>>>>>>,
+    transition: Transition
 }
 
 impl SceneManager {
 
     pub fn new() -> Self {
+        Self::with_initial_scene(crate::DEFAULT_SCENE)
+    }
+
+    // manager holding one empty scene with the given name
+    pub fn with_initial_scene(name: &str) -> Self {
 
-        let default_scene = Scene::new(String::from("default"), RenderView::new(Vec3::new(0.0,0.0,0.0), Vec3::new(0.0,0.0,0.0), Vec3::new(0.0,0.0,0.0)));
+        let initial_scene = Scene::new(String::from(name), RenderView::new(Vec3::new(0.0,0.0,0.0), Vec3::new(0.0,0.0,0.0), Vec3::new(0.0,0.0,0.0)));
 
         let mut scene_map: Box<HashMap<String, Rc<RefCell<Scene>>>> = Box::new(HashMap::new());
 
-        scene_map.insert(String::from(&default_scene.name.clone()), Rc::new(RefCell::new(default_scene)));
+        scene_map.insert(String::from(&initial_scene.name.clone()), Rc::new(RefCell::new(initial_scene)));
 
         Self {
-            scene_map: Arc::new(Mutex::new(scene_map))
+            scene_map: Arc::new(Mutex::new(scene_map)),
+            transition: Transition::default()
         }
     }
 
+    // transition used by following scene changes
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    pub fn transition(&self) -> Transition {
+        self.transition
+    }
+
     pub fn add_scene(&mut self, scene: Scene) {
 
         let mut scene_map = match self.scene_map.lock() {
@@ -38,7 +71,7 @@ impl SceneManager {
 
     }
 
-    pub fn get_scene(&self, name: String) -> std::io::Result<Rc<RefCell<Scene>>> {
+    pub fn get_scene(&self, name: String) -> Result<Rc<RefCell<Scene>>, XgError> {
 
         let scene_map = match self.scene_map.lock() {
             Ok(guard) => guard,
@@ -49,14 +82,12 @@ impl SceneManager {
 
         match scene {
             Some(scene) => Ok(Rc::clone(&scene)),
-            None => {
-                Err(std::io::Error::new(std::io::ErrorKind::Other, "Scene instance does not exist"))
-            }
+            None => Err(XgError::SceneNotFound(name))
         }
 
     }
 
-    pub fn render_scene(&self, name: String) -> std::io::Result<(EventResult)> {
+    pub fn render_scene(&self, name: String) -> Result<EventResult, XgError> {
 
         let scene_map = match self.scene_map.lock() {
             Ok(guard) => guard,
@@ -66,11 +97,12 @@ impl SceneManager {
         let scene: Option<&Rc<RefCell<Scene>>> = scene_map.get(name.as_str());
 
         if scene.is_none() {
-            panic!("Scene instance does not exist")
+            return Err(XgError::SceneNotFound(name));
         }
 
         let mut event = ChangeSceneEvent {
             scene: scene.unwrap().clone(),
+            transition: self.transition,
             cancelled: false,
             reason: None
         };
@@ -79,6 +111,20 @@ impl SceneManager {
 
     }
 
+    // names of all registered scenes in alphabetical order
+    pub fn scene_names(&self) -> Vec<String> {
+
+        let scene_map = match self.scene_map.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        let mut names: Vec<String> = scene_map.keys().cloned().collect();
+        names.sort();
+
+        names
+    }
+
     fn has_scene(&self, name: String) -> bool {
         let scene_map = match self.scene_map.lock() {
             Ok(guard) => guard,
@@ -90,8 +136,89 @@ impl SceneManager {
 
 }
 
+// the current scene changed, exit is dispatched for the previous scene before enter for the
+// new one. handlers bound with crate::subscribe_in_scene are gone by then
+pub struct SceneEnterEvent {
+    pub name: String,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+pub struct SceneExitEvent {
+    pub name: String,
+    cancelled: bool,
+    reason: Option<String>
+}
+
+impl SceneEnterEvent {
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl SceneExitEvent {
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            cancelled: false,
+            reason: None
+        }
+    }
+
+}
+
+impl Event for SceneEnterEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason
+    }
+
+}
+
+impl Event for SceneExitEvent {
+
+    fn cancellable(&self) -> bool {
+        false
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn get_cancelled_reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    fn set_cancelled(&mut self, _cancel: bool, reason: Option<String>) {
+        self.cancelled = _cancel;
+        self.reason = reason
+    }
+
+}
+
 pub struct ChangeSceneEvent {
     pub scene: Rc<RefCell<Scene>>,
+    pub transition: Transition,
     cancelled: bool,
     reason: Option<String>
 }
@@ -119,46 +246,23 @@ impl Event for ChangeSceneEvent {
 
 #[cfg(test)]
 mod tests {
-    use std::cell::{Cell, RefCell};
+    use std::cell::Cell;
     use std::ops::Deref;
     use std::rc::Rc;
     use event_bus::{Event, EventBus, subscribe_event};
     use event_bus::EventResult::EvCancelled;
     use glam::{IVec2, Vec2, Vec3};
-    use crate::renderer::renderer::RenderView;
+    use crate::environment::EngineEnvironment;
+    use crate::error::XgError;
+    use crate::renderer::{RenderPerspective, RenderView, Renderer};
+    use crate::renderer::null::NullRenderer;
     use crate::scene::chunk::Chunk;
     use crate::scene::manager::{ChangeSceneEvent, SceneManager};
     use crate::scene::scene::Scene;
 
-    static mut RENDERER: Cell<Option<RendererSim>> = Cell::new(None);
-
-    struct RendererSim {
-        current_scene: Rc<RefCell<Scene>>
-    }
-
-    impl RendererSim {
-
-        fn new(default: Rc<RefCell<Scene>>) -> Self {
-            Self {
-                current_scene: Rc::clone(&default)
-            }
-        }
-
-        fn change_scene(&mut self, scene: Rc<RefCell<Scene>>) {
-            self.current_scene = Rc::clone(&scene);
-        }
-
-        fn render(&self) {
-
-            let chunk = self.current_scene.borrow().get_chunk(Vec2::new(0.0, 0.0));
-
-            println!("{}", chunk.is_ok());
+    static mut RENDERER: Cell<Option<NullRenderer>> = Cell::new(None);
 
-        }
-
-    }
-
-    unsafe fn set_renderer(renderer: RendererSim) {
+    unsafe fn set_renderer(renderer: NullRenderer) {
         RENDERER.set(Some(renderer));
     }
 
@@ -169,7 +273,7 @@ mod tests {
 
             let scene = Rc::clone(&event.scene);
 
-            RENDERER.get_mut().as_mut().unwrap().change_scene(scene);
+            RENDERER.get_mut().as_mut().unwrap().set_scene(scene);
 
         }
 
@@ -191,17 +295,22 @@ mod tests {
 
         unsafe {
 
-            set_renderer(RendererSim::new(mamager.get_scene(String::from("test")).unwrap()))
+            let mut renderer = NullRenderer::new(RenderPerspective::new(1280, 720, 60.0, 0.1, 100.0));
+            renderer.set_scene(mamager.get_scene(String::from("test")).unwrap());
+
+            set_renderer(renderer)
 
         }
 
+        let state = unsafe { RENDERER.get_mut().as_ref().unwrap().state() };
+
         let mut result = match mamager.render_scene(String::from("test")) {
             Ok(res) => res,
             Err(err) => panic!("{}", err)
         };
 
         unsafe {
-            RENDERER.get_mut().as_ref().unwrap().render();
+            RENDERER.get_mut().as_mut().unwrap().do_render_cycle().unwrap();
         };
 
         let chunk = Chunk::new(IVec2::new(0,0));
@@ -210,12 +319,23 @@ mod tests {
 
         unsafe {
 
-            RENDERER.get_mut().as_mut().unwrap().render();
+            RENDERER.get_mut().as_mut().unwrap().do_render_cycle().unwrap();
 
         }
 
         assert_eq!(result, EvCancelled(String::from("test reason")));
 
+        // the handler switched the renderer before cancelling
+        assert_eq!(state.borrow().scenes, vec![String::from("test"), String::from("test")]);
+        assert_eq!(state.borrow().frames, 2);
+
+        // the environment stays in the scene it was in
+        let mut environment = EngineEnvironment::new();
+        environment.create_scene(String::from("test"));
+
+        assert_eq!(environment.render_scene(String::from("test")), Err(XgError::SceneChangeCancelled(String::from("test reason"))));
+        assert_eq!(environment.current_scene.borrow().name, "default");
+
     }
 
 }
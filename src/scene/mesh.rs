@@ -0,0 +1,143 @@
+use glam::Vec3;
+use crate::scene::object::{FRONT_FACE, Winding};
+
+// smooth per-vertex normals from a triangle list, face normals are weighted by triangle area.
+// normals point towards the camera that sees a triangle as a front face with the FRONT_FACE
+// winding in the left-handed engine space, so they face outwards on meshes the default CullMode
+// draws. degenerate and out of range triangles are skipped and unused vertices get a zero normal
+pub fn compute_normals(vertices: &[Vec3], indices: &[u16]) -> Vec<Vec3> {
+
+    let mut normals = vec![Vec3::ZERO; vertices.len()];
+
+    for triangle in indices.chunks_exact(3) {
+
+        let (a, b, c) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+
+        if a >= vertices.len() || b >= vertices.len() || c >= vertices.len() {
+            continue;
+        }
+
+        // cross product length is twice the triangle area, the left-handed space flips its direction
+        let face = match FRONT_FACE {
+            Winding::CounterClockwise => (vertices[c] - vertices[a]).cross(vertices[b] - vertices[a]),
+            Winding::Clockwise => (vertices[b] - vertices[a]).cross(vertices[c] - vertices[a])
+        };
+
+        if face.length_squared() <= f32::EPSILON * f32::EPSILON {
+            continue;
+        }
+
+        normals[a] += face;
+        normals[b] += face;
+        normals[c] += face;
+    }
+
+    for normal in normals.iter_mut() {
+        *normal = normal.normalize_or_zero();
+    }
+
+    normals
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::renderer::{RenderPerspective, RenderView};
+    use crate::renderer::math::screen_winding;
+    use crate::scene::object::CullMode;
+    use super::*;
+
+    fn cube() -> (Vec<Vec3>, Vec<u16>) {
+
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ];
+
+        // counter-clockwise on screen seen from outside, the FRONT_FACE winding
+        let indices = vec![
+            0, 2, 3, 0, 1, 2, // -z
+            4, 6, 5, 4, 7, 6, // +z
+            0, 7, 4, 0, 3, 7, // -x
+            1, 6, 2, 1, 5, 6, // +x
+            0, 5, 1, 0, 4, 5, // -y
+            3, 6, 7, 3, 2, 6, // +y
+        ];
+
+        (vertices, indices)
+    }
+
+    #[test]
+    fn cube_normals_test() {
+
+        let (vertices, indices) = cube();
+
+        let normals = compute_normals(&vertices, &indices);
+
+        let center = Vec3::splat(0.5);
+
+        for (vertex, normal) in vertices.iter().zip(normals.iter()) {
+            assert!((normal.length() - 1.0).abs() < 1e-5);
+            assert!(normal.dot(*vertex - center) > 0.0);
+        }
+    }
+
+    #[test]
+    fn flat_face_normal_test() {
+
+        let vertices = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)];
+
+        let normals = compute_normals(&vertices, &[0, 1, 2]);
+
+        for normal in normals.iter() {
+            assert!(normal.abs_diff_eq(Vec3::new(0.0, 0.0, 1.0), 1e-6));
+        }
+    }
+
+    #[test]
+    fn front_face_normal_test() {
+
+        let perspective = RenderPerspective::new(1920, 1080, 60.0, 0.2, 150.0);
+        let view = RenderView::new(Vec3::new(0.0, 0.0, -5.0), Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0));
+
+        let triangle = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)];
+
+        // drawn with the default cull mode, so its normal has to face the camera
+        let winding = screen_winding(&view, &perspective, triangle).unwrap();
+        assert!(!CullMode::default().culls(winding));
+
+        for normal in compute_normals(&triangle, &[0, 1, 2]) {
+            assert!(normal.dot(view.eye - triangle[0]) > 0.0);
+        }
+    }
+
+    #[test]
+    fn degenerate_triangle_test() {
+
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+
+        // first triangle is collinear, second repeats a vertex, third references a missing vertex
+        let normals = compute_normals(&vertices, &[0, 1, 2, 0, 0, 3, 0, 1, 9]);
+
+        for normal in normals.iter() {
+            assert_eq!(*normal, Vec3::ZERO);
+        }
+
+        // a degenerate triangle does not disturb a valid neighbour
+        let normals = compute_normals(&vertices, &[0, 1, 2, 0, 3, 1]);
+
+        assert!(normals[0].abs_diff_eq(Vec3::new(0.0, 0.0, 1.0), 1e-6));
+        assert_eq!(normals[2], Vec3::ZERO);
+    }
+
+}
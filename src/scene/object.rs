@@ -1,24 +1,63 @@
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 use bgfx_rs::bgfx::Texture;
-use glam::Vec3;
+use glam::{DVec3, Mat4, Quat, Vec3, Vec4};
 use image::DynamicImage;
 use uuid::Uuid;
-use crate::shader::ShaderContainer;
+use crate::error::XgError;
+use crate::scene::bounds::Aabb;
+use crate::shader::{ShaderContainer, ShaderFeatures, ShaderLoadContext, ShaderLoadError, ShaderUniforms, UniformType, UniformValue};
 
+// vec4 uniform the colored object tint is uploaded as, multiplied with the vertex color
+pub const TINT_UNIFORM: &str = "u_tint";
+
+// vertex structs are handed to the GPU as raw memory, their field order is the
+// attribute order of the matching layout in shader::get_vertex_layout
+#[repr(C)]
 pub struct ColoredVertex {
     pub coordinates: Vec3,
     pub color_rgba: u32
 }
 
+impl ColoredVertex {
+
+    // color as red, green, blue and alpha in 0..1, see rgba_to_vec4
+    pub fn color_as_vec4(&self) -> Vec4 {
+        rgba_to_vec4(self.color_rgba)
+    }
+
+    // packs the color back, channels are clamped to 0..1 and rounded to the nearest byte
+    pub fn set_color_vec4(&mut self, color: Vec4) {
+
+        let [r, g, b, a] = (color.clamp(Vec4::ZERO, Vec4::ONE) * 255.0).round().to_array().map(|channel| channel as u32);
+
+        self.color_rgba = r | (g << 8) | (b << 16) | (a << 24);
+    }
+
+}
+
+// a packed vertex color as red, green, blue and alpha in 0..1. the Color0 attribute reads the u32
+// as four bytes in memory order, so red is the lowest byte and alpha the highest
+pub fn rgba_to_vec4(color_rgba: u32) -> Vec4 {
+    Vec4::new(
+        (color_rgba & 0xff) as f32,
+        ((color_rgba >> 8) & 0xff) as f32,
+        ((color_rgba >> 16) & 0xff) as f32,
+        (color_rgba >> 24) as f32
+    ) / 255.0
+}
+
+#[repr(C)]
 pub struct ImageTexturedVertex {
     pub coordinates: Vec3,
     pub texture_u: i16,
     pub texture_v: i16
 }
 
+#[repr(C)]
 pub struct TgaTexturedVertex {
     pub coordinates: Vec3,
     pub normal_rgba: u32,
@@ -27,12 +66,81 @@ pub struct TgaTexturedVertex {
     pub texture_v: i16
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ObjectTypes {
     Colored,
     ImageTextured,
-    TgaTextured
+    TgaTextured,
+    Particles
+}
+
+// winding of a triangle as seen by the camera, in normalized device coordinates with y up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise
 }
 
+// canonical convention of the engine: triangles wound counter-clockwise as seen by the camera are
+// front faces. backends never hardcode a cull direction, they take it from CullMode which defaults to
+// culling the opposite of this winding
+pub const FRONT_FACE: Winding = Winding::CounterClockwise;
+
+// faces removed by culling, named after the screen space winding that gets removed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Clockwise,
+    CounterClockwise
+}
+
+impl CullMode {
+
+    // cull mode removing the back faces of the canonical winding
+    pub fn back_faces() -> Self {
+        match FRONT_FACE {
+            Winding::CounterClockwise => CullMode::Clockwise,
+            Winding::Clockwise => CullMode::CounterClockwise
+        }
+    }
+
+    // true when triangles with this screen space winding are removed
+    pub fn culls(&self, winding: Winding) -> bool {
+        match self {
+            CullMode::None => false,
+            CullMode::Clockwise => winding == Winding::Clockwise,
+            CullMode::CounterClockwise => winding == Winding::CounterClockwise
+        }
+    }
+
+}
+
+impl Default for CullMode {
+
+    fn default() -> Self {
+        CullMode::back_faces()
+    }
+
+}
+
+// how an object is combined with what is already drawn, transparent modes test depth
+// but do not write it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Opaque,
+    // source alpha over the destination
+    Alpha,
+    // source color added to the destination
+    Additive
+}
+
+// layers objects are drawn in, a renderer only draws objects sharing a bit with its layer mask
+pub const LAYER_WORLD: u32 = 1;
+pub const LAYER_UI: u32 = 1 << 1;
+pub const LAYER_DEBUG: u32 = 1 << 2;
+pub const LAYER_ALL: u32 = u32::MAX;
+
 pub struct Shaders {
     vertex: Vec<u8>,
     pixel: Vec<u8>
@@ -43,45 +151,274 @@ pub trait SceneObject {
     fn get_type(&self) -> ObjectTypes;
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    // the nil id unless overridden, such objects can't be told apart by Scene::with_object_mut
+    fn get_id(&self) -> Uuid {
+        Uuid::nil()
+    }
+
+    // ignored unless overridden, AnimationPlayer then leaves the object in place
+    fn set_transform(&mut self, _coordinates: Vec3, _rotation: Quat, _scale: Vec3) {}
+
+    // world space bounds of the transformed vertices
+    fn bounding_box(&self) -> Aabb;
+
+    // position of the object, used to pick its chunk
+    fn coordinates(&self) -> Vec3 {
+        self.bounding_box().center()
+    }
+
+    // called once per frame with the seconds elapsed since the previous frame
+    fn update(&mut self, _delta: f32) {}
+
+    // large world position, none for objects placed with f32 coordinates only
+    fn world_position(&self) -> Option<DVec3> {
+        None
+    }
+
+    // layer bitmask of the object, see LAYER_WORLD
+    fn layer(&self) -> u32 {
+        LAYER_WORLD
+    }
+
+    // vertices and indices drawn for the object, counted into the render statistics
+    fn geometry_counts(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    // true when the object is in one of the layers of the mask
+    fn in_layers(&self, mask: u32) -> bool {
+        self.layer() & mask != 0
+    }
 }
 
 pub struct ColoredSceneObject {
+    pub id: Uuid,
     pub vertices: Box<[ColoredVertex]>,
+    // empty draws the vertices as a plain triangle list
     pub indices: Box<[u16]>,
     pub shaders: Rc<RefCell<Box<dyn ShaderContainer>>>,
-    pub coordinates: Vec3
+    pub coordinates: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub cull_mode: CullMode,
+    pub blend_mode: BlendMode,
+    // double precision position used instead of coordinates when set, see Scene::set_camera_origin
+    pub world_position: Option<DVec3>,
+    // overrides of the shader uniform defaults for this object
+    pub uniforms: HashMap<String, UniformValue>,
+    // permutation of a PermutedShaderContainer drawn for the object, combined with the scene features
+    pub shader_features: ShaderFeatures,
+    // layer bitmask, LAYER_WORLD unless set
+    pub layer: u32,
+    // color multiplied with the vertex colors, white leaves them unchanged
+    pub tint: Vec4,
+    // opts into Scene::bake_static, the object must not move or change afterwards
+    pub is_static: bool
 }
 
 pub struct ImageTexturedSceneObject {
+    pub id: Uuid,
     pub vertices: Box<[ImageTexturedVertex]>,
     pub indices: Box<[u16]>,
     pub texture: DynamicImage,
     pub shaders: Rc<RefCell<Box<dyn ShaderContainer>>>,
-    pub coordinates: Vec3
+    pub coordinates: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub cull_mode: CullMode,
+    pub blend_mode: BlendMode,
+    pub world_position: Option<DVec3>,
+    pub uniforms: HashMap<String, UniformValue>,
+    pub shader_features: ShaderFeatures,
+    pub layer: u32
 }
 
 pub struct TgaTexturedSceneObject {
+    pub id: Uuid,
     pub vertices: Box<[TgaTexturedVertex]>,
     pub indices: Box<[u16]>,
     pub texture_color: DynamicImage,
     pub texture_normal: DynamicImage,
     pub shaders: Rc<RefCell<Box<dyn ShaderContainer>>>,
-    pub coordinates: Vec3
+    pub coordinates: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub cull_mode: CullMode,
+    pub blend_mode: BlendMode,
+    pub world_position: Option<DVec3>,
+    pub uniforms: HashMap<String, UniformValue>,
+    pub shader_features: ShaderFeatures,
+    pub layer: u32
 }
 
 // Implementations of new() with parameters for all SceneObject implementations
 impl ColoredSceneObject {
     pub fn new(vertices: Box<[ColoredVertex]>, indices: Box<[u16]>, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> Self {
         Self {
-            vertices, indices, shaders, coordinates
+            id: Uuid::new_v4(),
+            vertices, indices, shaders, coordinates,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD,
+            tint: Vec4::ONE,
+            is_static: false
         }
     }
+
+    // new with the geometry checked, every index has to point at a vertex and the indices, or the
+    // vertices when there are none, have to form whole triangles. new skips the checks
+    pub fn try_new(vertices: Box<[ColoredVertex]>, indices: Box<[u16]>, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> Result<Self, XgError> {
+
+        validate_triangles(vertices.len(), &indices)?;
+
+        Ok(Self::new(vertices, indices, shaders, coordinates))
+    }
+}
+
+// checks that the indices form a triangle list over vertex_count vertices
+pub fn validate_triangles(vertex_count: usize, indices: &[u16]) -> Result<(), XgError> {
+
+    if let Some(index) = indices.iter().find(|index| **index as usize >= vertex_count) {
+        return Err(XgError::IndexOutOfRange { index: *index, vertices: vertex_count });
+    }
+
+    let count = if indices.is_empty() { vertex_count } else { indices.len() };
+
+    if count % 3 != 0 {
+        return Err(XgError::IncompleteTriangle(count));
+    }
+
+    Ok(())
+}
+
+// vertices a merged batch may hold, every index has to fit in a u16
+pub const BATCH_VERTEX_LIMIT: usize = u16::MAX as usize + 1;
+
+impl ColoredSceneObject {
+
+    // whether the objects can be drawn with one draw call, everything but the geometry has to match
+    pub fn batches_with(&self, other: &ColoredSceneObject) -> bool {
+        Rc::ptr_eq(&self.shaders, &other.shaders) &&
+            self.cull_mode == other.cull_mode &&
+            self.blend_mode == other.blend_mode &&
+            self.uniforms == other.uniforms &&
+            self.shader_features == other.shader_features &&
+            self.layer == other.layer &&
+            self.tint == other.tint
+    }
+
+    // vertices moved by scale, rotation and coordinates, with the indices of a plain triangle
+    // list filled in
+    fn world_geometry(&self) -> (Vec<ColoredVertex>, Vec<u16>) {
+
+        let transform = Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.coordinates);
+
+        let vertices = self.vertices.iter()
+            .map(|vertex| ColoredVertex { coordinates: transform.transform_point3(vertex.coordinates), color_rgba: vertex.color_rgba })
+            .collect();
+
+        let indices = match self.indices.is_empty() {
+            true => (0..self.vertices.len() as u16).collect(),
+            false => self.indices.to_vec()
+        };
+
+        (vertices, indices)
+    }
+
+    // merges the objects into as few objects as BATCH_VERTEX_LIMIT allows, in world space at the
+    // origin. the state of the first object is used for all of them, see batches_with
+    pub fn merge(objects: &[&ColoredSceneObject]) -> Vec<ColoredSceneObject> {
+
+        let first = match objects.first() {
+            Some(first) => first,
+            None => return Vec::new()
+        };
+
+        let mut batches = Vec::new();
+        let (mut vertices, mut indices): (Vec<ColoredVertex>, Vec<u16>) = (Vec::new(), Vec::new());
+
+        for object in objects {
+
+            let (object_vertices, object_indices) = object.world_geometry();
+
+            if !vertices.is_empty() && vertices.len() + object_vertices.len() > BATCH_VERTEX_LIMIT {
+                batches.push((std::mem::take(&mut vertices), std::mem::take(&mut indices)));
+            }
+
+            let offset = vertices.len() as u16;
+
+            indices.extend(object_indices.iter().map(|index| index + offset));
+            vertices.extend(object_vertices);
+        }
+
+        batches.push((vertices, indices));
+
+        batches.into_iter().map(|(vertices, indices)| {
+
+            let mut merged = ColoredSceneObject::new(vertices.into_boxed_slice(), indices.into_boxed_slice(), Rc::clone(&first.shaders), Vec3::ZERO);
+
+            merged.cull_mode = first.cull_mode;
+            merged.blend_mode = first.blend_mode;
+            merged.uniforms = first.uniforms.clone();
+            merged.shader_features = first.shader_features;
+            merged.layer = first.layer;
+            merged.tint = first.tint;
+            merged.is_static = true;
+
+            merged
+        }).collect()
+    }
+
+}
+
+impl ColoredSceneObject {
+
+    pub fn set_tint(&mut self, tint: Vec4) {
+        self.tint = tint;
+    }
+
+    // uniform overrides submitted with the object, the tint is staged when the shader declares
+    // TINT_UNIFORM and the object does not override it explicitly
+    pub fn staged_uniforms(&self, declared: Option<&ShaderUniforms>) -> HashMap<String, UniformValue> {
+
+        let mut uniforms = self.uniforms.clone();
+
+        if declared.and_then(|declared| declared.get_type(TINT_UNIFORM)) == Some(UniformType::Vec4) {
+            uniforms.entry(String::from(TINT_UNIFORM)).or_insert(UniformValue::Vec4(self.tint));
+        }
+
+        uniforms
+    }
+
+    // object drawn with the built-in colored shader, the engine has to be initialized
+    #[cfg(feature = "builtin-shaders")]
+    pub fn new_with_default_shader(vertices: Box<[ColoredVertex]>, indices: Box<[u16]>, coordinates: Vec3) -> Result<Self, XgError> {
+
+        let shaders = crate::get_shader(crate::default_colored_shader()?)?;
+
+        Ok(Self::new(vertices, indices, shaders, coordinates))
+    }
 }
 
 impl ImageTexturedSceneObject {
     pub fn new(vertices: Box<[ImageTexturedVertex]>, indices: Box<[u16]>, texture: DynamicImage, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> Self {
         Self {
-            vertices, indices, texture, shaders, coordinates
+            id: Uuid::new_v4(),
+            vertices, indices, texture, shaders, coordinates,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD
         }
     }
 }
@@ -89,7 +426,16 @@ impl ImageTexturedSceneObject {
 impl TgaTexturedSceneObject {
     pub fn new(vertices: Box<[TgaTexturedVertex]>, indices: Box<[u16]>, texture_color: DynamicImage, texture_normal: DynamicImage, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> Self {
         Self {
-            vertices, indices, texture_color, texture_normal, shaders, coordinates
+            id: Uuid::new_v4(),
+            vertices, indices, texture_color, texture_normal, shaders, coordinates,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD
         }
     }
 }
@@ -108,6 +454,36 @@ impl SceneObject for ColoredSceneObject {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn set_transform(&mut self, coordinates: Vec3, rotation: Quat, scale: Vec3) {
+        self.coordinates = coordinates;
+        self.rotation = rotation;
+        self.scale = scale;
+    }
+
+    fn coordinates(&self) -> Vec3 {
+        self.coordinates
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_transformed(self.vertices.iter().map(|vertex| vertex.coordinates), self.coordinates, self.rotation, self.scale)
+    }
+
+    fn world_position(&self) -> Option<DVec3> {
+        self.world_position
+    }
+
+    fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    fn geometry_counts(&self) -> (usize, usize) {
+        (self.vertices.len(), self.indices.len())
+    }
 }
 
 // SceneObject implementation for ImageTexturedSceneObject
@@ -124,6 +500,36 @@ impl SceneObject for ImageTexturedSceneObject {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn set_transform(&mut self, coordinates: Vec3, rotation: Quat, scale: Vec3) {
+        self.coordinates = coordinates;
+        self.rotation = rotation;
+        self.scale = scale;
+    }
+
+    fn coordinates(&self) -> Vec3 {
+        self.coordinates
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_transformed(self.vertices.iter().map(|vertex| vertex.coordinates), self.coordinates, self.rotation, self.scale)
+    }
+
+    fn world_position(&self) -> Option<DVec3> {
+        self.world_position
+    }
+
+    fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    fn geometry_counts(&self) -> (usize, usize) {
+        (self.vertices.len(), self.indices.len())
+    }
 }
 
 // SceneObject implementation for TgaTexturedSceneObject
@@ -140,6 +546,36 @@ impl SceneObject for TgaTexturedSceneObject {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn set_transform(&mut self, coordinates: Vec3, rotation: Quat, scale: Vec3) {
+        self.coordinates = coordinates;
+        self.rotation = rotation;
+        self.scale = scale;
+    }
+
+    fn coordinates(&self) -> Vec3 {
+        self.coordinates
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_transformed(self.vertices.iter().map(|vertex| vertex.coordinates), self.coordinates, self.rotation, self.scale)
+    }
+
+    fn world_position(&self) -> Option<DVec3> {
+        self.world_position
+    }
+
+    fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    fn geometry_counts(&self) -> (usize, usize) {
+        (self.vertices.len(), self.indices.len())
+    }
 }
 
 pub struct TestShaderContainer {}
@@ -149,8 +585,9 @@ impl ShaderContainer for TestShaderContainer {
         false
     }
 
-    fn load(&mut self) {
+    fn load(&mut self, _context: &ShaderLoadContext) -> Result<(), ShaderLoadError> {
         println!("TestShaderContainer::load()");
+        Ok(())
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -162,6 +599,24 @@ impl ShaderContainer for TestShaderContainer {
     }
 }
 
+// shader handle for objects in tests, nothing is ever loaded
+#[cfg(test)]
+pub(crate) fn test_shaders() -> Rc<RefCell<Box<dyn ShaderContainer>>> {
+    Rc::new(RefCell::new(Box::new(TestShaderContainer {})))
+}
+
+// object spanning (0, 0, 0) to (size, size, size) around coordinates, shared by the scene tests
+#[cfg(test)]
+pub(crate) fn test_cube(size: f32, coordinates: Vec3) -> ColoredSceneObject {
+
+    let vertices: Box<[ColoredVertex]> = Box::new([
+        ColoredVertex { coordinates: Vec3::new(0.0, 0.0, 0.0), color_rgba: 0xffffffff },
+        ColoredVertex { coordinates: Vec3::new(size, size, size), color_rgba: 0xffffffff },
+    ]);
+
+    ColoredSceneObject::new(vertices, Box::new([]), test_shaders(), coordinates)
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -169,31 +624,112 @@ mod tests {
     use glfw::Key::V;
     use super::*;
 
+    fn triangle_vertices() -> Box<[ColoredVertex]> {
+        Box::new([
+            ColoredVertex { coordinates: Vec3::ZERO, color_rgba: 0xffffffff },
+            ColoredVertex { coordinates: Vec3::X, color_rgba: 0xffffffff },
+            ColoredVertex { coordinates: Vec3::Y, color_rgba: 0xffffffff }
+        ])
+    }
+
+    #[test]
+    fn try_new_test() {
+
+        let shaders = test_shaders();
+
+        assert!(ColoredSceneObject::try_new(triangle_vertices(), Box::new([0, 1, 2, 2, 1, 0]), Rc::clone(&shaders), Vec3::ZERO).is_ok());
+        assert!(ColoredSceneObject::try_new(triangle_vertices(), Box::new([]), Rc::clone(&shaders), Vec3::ZERO).is_ok());
+
+        // index 3 reads past the three vertices
+        let result = ColoredSceneObject::try_new(triangle_vertices(), Box::new([0, 1, 3]), Rc::clone(&shaders), Vec3::ZERO);
+        assert_eq!(result.err(), Some(XgError::IndexOutOfRange { index: 3, vertices: 3 }));
+
+        let result = ColoredSceneObject::try_new(triangle_vertices(), Box::new([0, 1, 2, 0]), Rc::clone(&shaders), Vec3::ZERO);
+        assert_eq!(result.err(), Some(XgError::IncompleteTriangle(4)));
+
+        // without indices the vertices are the triangle list
+        let vertices: Box<[ColoredVertex]> = Box::new([
+            ColoredVertex { coordinates: Vec3::ZERO, color_rgba: 0xffffffff },
+            ColoredVertex { coordinates: Vec3::X, color_rgba: 0xffffffff }
+        ]);
+
+        let result = ColoredSceneObject::try_new(vertices, Box::new([]), shaders, Vec3::ZERO);
+        assert_eq!(result.err(), Some(XgError::IncompleteTriangle(2)));
+    }
+
+    #[test]
+    fn vertex_color_test() {
+
+        let mut vertex = ColoredVertex { coordinates: Vec3::ZERO, color_rgba: 0xff0000ff };
+
+        // red with full alpha in the render layout
+        assert_eq!(vertex.color_as_vec4(), Vec4::new(1.0, 0.0, 0.0, 1.0));
+
+        for color in [0x00000000, 0xffffffff, 0x80402010, 0x7f00ff33, 0x01fe02fd] {
+            vertex.color_rgba = color;
+            let float = vertex.color_as_vec4();
+            vertex.set_color_vec4(float);
+            assert_eq!(vertex.color_rgba, color);
+        }
+
+        vertex.set_color_vec4(Vec4::new(0.0, 1.0, 2.0, -1.0));
+        assert_eq!(vertex.color_rgba, 0x00ffff00);
+    }
+
     // as_any() test for all SceneObject implementations
     #[test]
     fn as_any() {
         let colored_object = ColoredSceneObject {
+            id: Uuid::new_v4(),
             vertices: Box::new([]),
             indices: Box::new([]),
-            shaders: Rc::new(RefCell::new(Box::new(TestShaderContainer {}))),
-            coordinates: Vec3::new(0.0, 0.0, 0.0)
+            shaders: test_shaders(),
+            coordinates: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD,
+            tint: Vec4::ONE,
+            is_static: false
         };
 
         let image_textured_object = ImageTexturedSceneObject {
+            id: Uuid::new_v4(),
             vertices: Box::new([]),
             indices: Box::new([]),
             texture: DynamicImage::new_rgb8(50, 50),
-            shaders: Rc::new(RefCell::new(Box::new(TestShaderContainer {}))),
-            coordinates: Vec3::new(0.0, 0.0, 0.0)
+            shaders: test_shaders(),
+            coordinates: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD
         };
 
         let tga_textured_object = TgaTexturedSceneObject {
+            id: Uuid::new_v4(),
             vertices: Box::new([]),
             indices: Box::new([]),
             texture_color: DynamicImage::new_rgb8(50, 50),
             texture_normal: DynamicImage::new_rgb8(50, 50),
-            shaders: Rc::new(RefCell::new(Box::new(TestShaderContainer {}))),
-            coordinates: Vec3::new(0.0, 0.0, 0.0)
+            shaders: test_shaders(),
+            coordinates: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            cull_mode: CullMode::default(),
+            blend_mode: BlendMode::default(),
+            world_position: None,
+            uniforms: HashMap::new(),
+            shader_features: ShaderFeatures::NONE,
+            layer: LAYER_WORLD
         };
 
         assert!(colored_object.as_any().is::<ColoredSceneObject>());
@@ -215,4 +751,76 @@ mod tests {
         assert_eq!(tga_textured_object_casted.type_id(), tga_textured_object.type_id());
 
     }
-}
\ No newline at end of file
+
+    // offsets and strides the vertex layouts describe, reordering a field has to break here
+    #[test]
+    fn tint_uniform_test() {
+
+        let mut object = ColoredSceneObject::new(Box::new([]), Box::new([]), test_shaders(), Vec3::ZERO);
+
+        let mut declared = ShaderUniforms::new();
+        declared.declare(TINT_UNIFORM, UniformType::Vec4);
+
+        // white by default
+        assert_eq!(object.staged_uniforms(Some(&declared)).get(TINT_UNIFORM), Some(&UniformValue::Vec4(Vec4::ONE)));
+
+        object.set_tint(Vec4::new(1.0, 0.5, 0.0, 1.0));
+        assert_eq!(object.staged_uniforms(Some(&declared)).get(TINT_UNIFORM), Some(&UniformValue::Vec4(Vec4::new(1.0, 0.5, 0.0, 1.0))));
+
+        // shaders without the uniform would reject it
+        assert!(object.staged_uniforms(Some(&ShaderUniforms::new())).is_empty());
+        assert!(object.staged_uniforms(None).is_empty());
+
+        // explicit overrides win
+        object.uniforms.insert(String::from(TINT_UNIFORM), UniformValue::Vec4(Vec4::ZERO));
+        assert_eq!(object.staged_uniforms(Some(&declared)).get(TINT_UNIFORM), Some(&UniformValue::Vec4(Vec4::ZERO)));
+    }
+
+    #[test]
+    fn vertex_layout_test() {
+
+        assert_eq!(std::mem::offset_of!(ColoredVertex, coordinates), 0);
+        assert_eq!(std::mem::offset_of!(ColoredVertex, color_rgba), 12);
+        assert_eq!(std::mem::size_of::<ColoredVertex>(), 16);
+
+        assert_eq!(std::mem::offset_of!(ImageTexturedVertex, texture_u), 12);
+        assert_eq!(std::mem::offset_of!(ImageTexturedVertex, texture_v), 14);
+        assert_eq!(std::mem::size_of::<ImageTexturedVertex>(), 16);
+
+        assert_eq!(std::mem::offset_of!(TgaTexturedVertex, normal_rgba), 12);
+        assert_eq!(std::mem::offset_of!(TgaTexturedVertex, tangent), 16);
+        assert_eq!(std::mem::offset_of!(TgaTexturedVertex, texture_u), 20);
+        assert_eq!(std::mem::size_of::<TgaTexturedVertex>(), 24);
+    }
+
+    struct FixedObject {}
+
+    impl SceneObject for FixedObject {
+        fn get_type(&self) -> ObjectTypes {
+            ObjectTypes::Colored
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn bounding_box(&self) -> Aabb {
+            Aabb::new(Vec3::ZERO, Vec3::ONE)
+        }
+    }
+
+    #[test]
+    fn trait_defaults_test() {
+
+        let mut object = FixedObject {};
+
+        assert_eq!(object.get_id(), Uuid::nil());
+
+        object.set_transform(Vec3::splat(5.0), Quat::IDENTITY, Vec3::ONE);
+        assert_eq!(object.coordinates(), Vec3::splat(0.5));
+    }
+}
@@ -0,0 +1,324 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use glam::{Quat, Vec3, Vec4};
+use uuid::Uuid;
+use crate::scene::bounds::Aabb;
+use crate::scene::object::{LAYER_WORLD, ObjectTypes, rgba_to_vec4, SceneObject};
+use crate::scene::sky::lerp_rgba;
+use crate::shader::ShaderContainer;
+
+// most particles an emitter keeps alive, bounds the instance data copied to the GPU every frame
+pub const MAX_PARTICLES: usize = 16 * 1024;
+
+// per particle data of the instanced quad, the particle vertex shader reads it as i_data0 and i_data1
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleInstance {
+    // in emitter space
+    pub position: Vec3,
+    // edge length of the quad
+    pub size: f32,
+    // red, green, blue and alpha in 0..1
+    pub color: Vec4
+}
+
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32
+}
+
+pub struct ParticleEmitter {
+    pub id: Uuid,
+    pub spawn_rate: f32,
+    pub lifetime: f32,
+    pub velocity: Vec3,
+    pub spread: f32,
+    pub gravity: Vec3,
+    // cap of live particles, larger values count as MAX_PARTICLES
+    pub max_particles: usize,
+    pub size: f32,
+    pub color_rgba: u32,
+    // color reached at the end of the lifetime, blended per channel from color_rgba.
+    // none keeps color_rgba for the whole life
+    pub end_color_rgba: Option<u32>,
+    // drawn as instanced quads, so the vertex shader has to read the instance data like
+    // resources/shaders/src/vs_particles.sc
+    pub shaders: Rc<RefCell<Box<dyn ShaderContainer>>>,
+    pub coordinates: Vec3,
+    // layer bitmask, LAYER_WORLD unless set
+    pub layer: u32,
+    particles: Vec<Particle>,
+    spawn_accumulator: f32,
+    seed: u32
+}
+
+impl ParticleEmitter {
+
+    // constructor
+    pub fn new(spawn_rate: f32, lifetime: f32, velocity: Vec3, gravity: Vec3, max_particles: usize, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            spawn_rate,
+            lifetime,
+            velocity,
+            spread: 0.0,
+            gravity,
+            max_particles: max_particles.min(MAX_PARTICLES),
+            size: 0.1,
+            color_rgba: 0xffffffff,
+            end_color_rgba: None,
+            shaders,
+            coordinates,
+            layer: LAYER_WORLD,
+            particles: Vec::with_capacity(max_particles.min(MAX_PARTICLES)),
+            spawn_accumulator: 0.0,
+            seed: 0x9e3779b9
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    // advances the simulation, ages and despawns particles, then spawns new ones up to the cap.
+    // linear in the live particles, their instance data is rebuilt and copied to the GPU every frame
+    // on top of that, so keep max_particles in the low thousands per emitter
+    pub fn step(&mut self, delta: f32) {
+
+        let lifetime = self.lifetime;
+        let gravity = self.gravity;
+
+        for particle in self.particles.iter_mut() {
+            particle.age += delta;
+            particle.velocity += gravity * delta;
+            particle.position += particle.velocity * delta;
+        }
+
+        self.particles.retain(|particle| particle.age < lifetime);
+
+        self.spawn_accumulator += self.spawn_rate * delta;
+
+        let max_particles = self.max_particles.min(MAX_PARTICLES);
+
+        while self.spawn_accumulator >= 1.0 && self.particles.len() < max_particles {
+
+            let velocity = self.velocity + self.random_offset() * self.spread;
+
+            self.particles.push(Particle {
+                position: Vec3::ZERO,
+                velocity,
+                age: 0.0
+            });
+
+            self.spawn_accumulator -= 1.0;
+        }
+
+        // do not bank spawns while the emitter is at its cap
+        if self.particles.len() >= max_particles {
+            self.spawn_accumulator = self.spawn_accumulator.min(1.0);
+        }
+    }
+
+    // color of the particle at its age, see end_color_rgba
+    pub fn particle_color(&self, particle: &Particle) -> u32 {
+        match self.end_color_rgba {
+            Some(end) if self.lifetime > 0.0 => lerp_rgba(self.color_rgba, end, particle.age / self.lifetime),
+            _ => self.color_rgba
+        }
+    }
+
+    // one instance of the unit quad per particle, the vertex shader turns the quads to the camera
+    pub fn build_instances(&self) -> Vec<ParticleInstance> {
+        self.particles.iter()
+            .map(|particle| ParticleInstance {
+                position: particle.position,
+                size: self.size,
+                color: rgba_to_vec4(self.particle_color(particle))
+            })
+            .collect()
+    }
+
+    // xorshift, returns a vector with components in -1..1
+    fn random_offset(&mut self) -> Vec3 {
+
+        let mut next = || {
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            (self.seed as f32 / u32::MAX as f32) * 2.0 - 1.0
+        };
+
+        Vec3::new(next(), next(), next())
+    }
+
+}
+
+impl SceneObject for ParticleEmitter {
+
+    fn get_type(&self) -> ObjectTypes {
+        ObjectTypes::Particles
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    // the quads always face the camera, so only the position is taken
+    fn set_transform(&mut self, coordinates: Vec3, _rotation: Quat, _scale: Vec3) {
+        self.coordinates = coordinates;
+    }
+
+    fn coordinates(&self) -> Vec3 {
+        self.coordinates
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_transformed(self.particles.iter().map(|particle| particle.position), self.coordinates, Quat::IDENTITY, Vec3::ONE)
+    }
+
+    fn update(&mut self, delta: f32) {
+        self.step(delta);
+    }
+
+    fn layer(&self) -> u32 {
+        self.layer
+    }
+
+    // one instanced quad per particle, see build_instances
+    fn geometry_counts(&self) -> (usize, usize) {
+        (self.particles.len() * 4, self.particles.len() * 6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::object::test_shaders;
+
+    fn emitter(spawn_rate: f32, lifetime: f32, max_particles: usize) -> ParticleEmitter {
+        ParticleEmitter::new(
+            spawn_rate,
+            lifetime,
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            max_particles,
+            test_shaders(),
+            Vec3::new(0.0, 0.0, 0.0)
+        )
+    }
+
+    #[test]
+    fn spawn_test() {
+
+        let mut emitter = emitter(10.0, 5.0, 100);
+
+        emitter.step(0.5);
+
+        assert_eq!(emitter.particles().len(), 5);
+    }
+
+    #[test]
+    fn aging_test() {
+
+        let mut emitter = emitter(1.0, 5.0, 100);
+
+        emitter.step(1.0);
+        emitter.step(1.0);
+
+        assert_eq!(emitter.particles().len(), 2);
+        assert_eq!(emitter.particles()[0].age, 1.0);
+        assert_eq!(emitter.particles()[1].age, 0.0);
+
+        // first particle moved up and was pulled back by gravity
+        assert_eq!(emitter.particles()[0].velocity, Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(emitter.particles()[0].position, Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn despawn_test() {
+
+        let mut emitter = emitter(1.0, 1.0, 100);
+
+        emitter.step(1.0);
+        emitter.step(1.0);
+
+        // first particle reached its lifetime and was removed
+        assert_eq!(emitter.particles().len(), 1);
+        assert_eq!(emitter.particles()[0].age, 0.0);
+    }
+
+    #[test]
+    fn cap_test() {
+
+        let mut emitter = emitter(100.0, 10.0, 8);
+
+        emitter.step(1.0);
+        assert_eq!(emitter.particles().len(), 8);
+
+        emitter.step(1.0);
+        assert_eq!(emitter.particles().len(), 8);
+    }
+
+    #[test]
+    fn max_particles_test() {
+
+        let mut emitter = emitter(100000.0, 10.0, 100000);
+
+        assert_eq!(emitter.max_particles, MAX_PARTICLES);
+
+        // raised past the limit after construction
+        emitter.max_particles = usize::MAX;
+        emitter.step(1.0);
+
+        assert_eq!(emitter.particles().len(), MAX_PARTICLES);
+        assert_eq!(emitter.build_instances().len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn instance_test() {
+
+        let mut emitter = emitter(3.0, 10.0, 8);
+        emitter.size = 0.5;
+        emitter.color_rgba = 0xff0000ff;
+
+        emitter.step(1.0);
+
+        let instances = emitter.build_instances();
+
+        assert_eq!(instances.len(), 3);
+        assert_eq!(instances[0], ParticleInstance { position: emitter.particles()[0].position, size: 0.5, color: Vec4::new(1.0, 0.0, 0.0, 1.0) });
+
+        // i_data0 and i_data1 are two vec4
+        assert_eq!(std::mem::size_of::<ParticleInstance>(), 32);
+        assert_eq!(std::mem::offset_of!(ParticleInstance, color), 16);
+    }
+
+    #[test]
+    fn color_over_life_test() {
+
+        let mut emitter = emitter(1.0, 2.0, 8);
+        emitter.color_rgba = 0xff00ff00;
+
+        let particle = |age: f32| Particle { position: Vec3::ZERO, velocity: Vec3::ZERO, age };
+
+        // no end color keeps the start color
+        assert_eq!(emitter.particle_color(&particle(1.0)), 0xff00ff00);
+
+        emitter.end_color_rgba = Some(0x00ff0000);
+
+        assert_eq!(emitter.particle_color(&particle(0.0)), 0xff00ff00);
+        assert_eq!(emitter.particle_color(&particle(1.0)), 0x80808000);
+        assert_eq!(emitter.particle_color(&particle(2.0)), 0x00ff0000);
+    }
+
+}
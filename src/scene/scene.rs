@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex, MutexGuard};
-use glam::{IVec2, Vec2, Vec3};
+use glam::{DVec3, IVec2, Vec2, Vec3};
 use glfw::Key::O;
-use crate::renderer::renderer::RenderView;
+use uuid::Uuid;
+use crate::error::XgError;
+use crate::renderer::RenderView;
+use crate::scene::bookmark::{CameraBookmark, CameraFlight};
+use crate::scene::bounds::Aabb;
 use crate::scene::chunk::Chunk;
+use crate::scene::object::{BATCH_VERTEX_LIMIT, ColoredSceneObject, ObjectTypes, SceneObject};
+use crate::scene::sky::{Rgba, SkyGradient};
+use crate::shader::ShaderFeatures;
 
 pub struct ChunkCorners {
     begin: Vec2,
@@ -23,22 +30,148 @@ impl ChunkCorners {
 
 }
 
+// edge length of the chunks Scene::spawn creates for positions outside every chunk
+pub const SPAWN_CHUNK_SIZE: f32 = 100.0;
+
 pub struct Scene {
     pub name: String,
-    chunk_map: HashMap<IVec2, Rc<Chunk>>,
+    // keyed by the chunk coordinates as [x, y], so chunks iterate in a stable order
+    chunk_map: BTreeMap<[i32; 2], Rc<Chunk>>,
     chunk_corners: Vec<ChunkCorners>,
-    pub camera: RenderView
+    // chunks spawn created on the grid, kept apart from chunk_map so they never share a chunk
+    // added with add_chunk that has the same coordinates
+    grid_chunks: Vec<(ChunkCorners, Rc<Chunk>)>,
+    pub camera: RenderView,
+    pub clear_color: Rgba,
+    sky: Option<SkyGradient>,
+    camera_origin: Option<DVec3>,
+    // edge length of the chunk grid, none when only chunks added with add_chunk exist
+    chunk_size: Option<f32>,
+    // shader permutation features enabled for every object in the scene, like fog
+    pub shader_features: ShaderFeatures,
+    camera_bookmarks: HashMap<String, CameraBookmark>,
+    camera_flight: Option<CameraFlight>
 }
 
 impl Scene {
 
     pub fn new(name: String, camera: RenderView) -> Self {
         Self {
-            name, chunk_map: HashMap::new(), chunk_corners: Vec::new(), camera
+            name, chunk_map: BTreeMap::new(), chunk_corners: Vec::new(), grid_chunks: Vec::new(), camera,
+            clear_color: 0x103030ff,
+            sky: None,
+            camera_origin: None,
+            chunk_size: None,
+            shader_features: ShaderFeatures::NONE,
+            camera_bookmarks: HashMap::new(),
+            camera_flight: None
         }
     }
 
-    pub fn get_current_chunk(&self) -> std::io::Result<Rc<Chunk>> {
+    // opt-in camera relative rendering for large worlds. with an origin set the camera is placed
+    // relative to it and objects with a world_position are rendered at world_position - origin,
+    // computed in f64 so there is no jitter far away from zero
+    pub fn set_camera_origin(&mut self, origin: Option<DVec3>) {
+        self.camera_origin = origin;
+    }
+
+    pub fn camera_origin(&self) -> Option<DVec3> {
+        self.camera_origin
+    }
+
+    // moves the origin under the camera eye and shifts the camera back to zero,
+    // call when the camera wandered far from the current origin
+    pub fn rebase_origin(&mut self) {
+
+        let origin = match self.camera_origin {
+            Some(origin) => origin,
+            None => return
+        };
+
+        let eye = self.camera.eye;
+
+        self.camera_origin = Some(origin + eye.as_dvec3());
+        self.camera.eye -= eye;
+        self.camera.at -= eye;
+    }
+
+    // f32 position the renderer uses for the object, coordinates are used when it has no world_position
+    pub fn render_position(&self, object: &dyn SceneObject, coordinates: Vec3) -> Vec3 {
+        match (object.world_position(), self.camera_origin) {
+            (Some(world), Some(origin)) => (world - origin).as_vec3(),
+            (Some(world), None) => world.as_vec3(),
+            (None, _) => coordinates
+        }
+    }
+
+    // animates the clear color between keyed colors, keys are (seconds, color)
+    pub fn set_sky_gradient(&mut self, keys: Vec<(f32, Rgba)>) {
+
+        let sky = SkyGradient::new(keys);
+
+        if let Some(color) = sky.color() {
+            self.clear_color = color;
+        }
+
+        self.sky = Some(sky);
+    }
+
+    pub fn clear_sky_gradient(&mut self) {
+        self.sky = None;
+    }
+
+    // remembers eye, at and up of the camera under the name, replacing an older bookmark
+    pub fn save_camera_bookmark(&mut self, name: &str) {
+        self.camera_bookmarks.insert(String::from(name), CameraBookmark::of(&self.camera));
+    }
+
+    pub fn camera_bookmark(&self, name: &str) -> Option<CameraBookmark> {
+        self.camera_bookmarks.get(name).copied()
+    }
+
+    // moves the camera to the bookmark, instantly for a duration of 0 and otherwise eased over
+    // that many seconds of updates. moving the camera meanwhile is overridden until it arrives
+    pub fn goto_camera_bookmark(&mut self, name: &str, duration: f32) -> Result<(), XgError> {
+
+        let bookmark = match self.camera_bookmark(name) {
+            Some(bookmark) => bookmark,
+            None => return Err(XgError::CameraBookmarkNotFound(String::from(name)))
+        };
+
+        if duration > 0.0 {
+            self.camera_flight = Some(CameraFlight::new(CameraBookmark::of(&self.camera), bookmark, duration));
+        } else {
+            self.camera_flight = None;
+            bookmark.apply(&mut self.camera);
+        }
+
+        Ok(())
+    }
+
+    // called once per tick with the seconds since the previous one
+    pub fn update(&mut self, delta: f32) {
+
+        if let Some(flight) = self.camera_flight.as_mut() {
+
+            flight.advance(delta).apply(&mut self.camera);
+
+            if flight.finished() {
+                self.camera_flight = None;
+            }
+        }
+
+        if let Some(sky) = self.sky.as_mut() {
+
+            sky.advance(delta);
+
+            if let Some(color) = sky.color() {
+                self.clear_color = color;
+            }
+
+        }
+    }
+
+    pub fn get_current_chunk(&self) -> Result<Rc<Chunk>, XgError> {
 
         let coordinates = Vec2::new(self.camera.at.x, self.camera.at.z);
 
@@ -46,18 +179,16 @@ impl Scene {
 
     }
 
-    pub fn get_chunk(&self, coordinates: Vec2) -> std::io::Result<Rc<Chunk>> {
+    pub fn get_chunk(&self, coordinates: Vec2) -> Result<Rc<Chunk>, XgError> {
 
         for corner in self.chunk_corners.iter() {
 
             if corner.check_range(coordinates) {
 
-                let coordinates: &IVec2 = &corner.chunk;
-
-                let chunk: Option<&Rc<Chunk>> = self.chunk_map.get(coordinates);
+                let chunk: Option<&Rc<Chunk>> = self.chunk_map.get(&corner.chunk.to_array());
 
                 if chunk.is_none() {
-                    return Err(std::io::Error::new(std::io::ErrorKind::Other, "Chunk does not exist"));
+                    return Err(XgError::ChunkNotFound);
                 }
 
                 return Ok(Rc::clone(chunk.unwrap()));
@@ -65,7 +196,38 @@ impl Scene {
 
         }
 
-        Err(std::io::Error::new(std::io::ErrorKind::Other, "Chunk does not exist"))
+        for (corner, chunk) in self.grid_chunks.iter() {
+
+            if corner.check_range(coordinates) {
+                return Ok(Rc::clone(chunk));
+            }
+
+        }
+
+        Err(XgError::ChunkNotFound)
+    }
+
+    // places every position on a grid of chunks with the edge length, spawn creates the chunk
+    // of a cell when the first object lands in it. set it before spawning objects, cells created
+    // with another size keep their bounds. sizes that are not finite and above zero are rejected
+    pub fn set_chunk_size(&mut self, size: f32) -> Result<(), XgError> {
+
+        if !size.is_finite() || size <= 0.0 {
+            return Err(XgError::InvalidChunkSize(size.to_string()));
+        }
+
+        self.chunk_size = Some(size);
+
+        Ok(())
+    }
+
+    pub fn chunk_size(&self) -> Option<f32> {
+        self.chunk_size
+    }
+
+    // chunk coordinates of the grid cell containing the position, none without a grid
+    pub fn grid_coordinates(&self, position: Vec2) -> Option<IVec2> {
+        self.chunk_size.map(|size| (position / size).floor().as_ivec2())
     }
 
     pub fn add_chunk(&mut self, chunk: Chunk, begin: Vec2, end: Vec2) {
@@ -74,18 +236,210 @@ impl Scene {
             begin, end, chunk: chunk.coordinates
         };
 
-        self.chunk_map.insert(chunk.coordinates.clone(), Rc::new(chunk));
+        self.chunk_map.insert(chunk.coordinates.to_array(), Rc::new(chunk));
         self.chunk_corners.push(corners);
     }
 
+    // adds the object to the chunk containing its coordinates and returns its id. positions
+    // outside every chunk get a new chunk from the grid set with set_chunk_size, or a
+    // SPAWN_CHUNK_SIZE grid around the origin without one
+    pub fn spawn(&mut self, object: Box<dyn SceneObject>) -> Uuid {
+
+        let id = object.get_id();
+        let coordinates = object.coordinates();
+        let position = Vec2::new(coordinates.x, coordinates.z);
+
+        let chunk = match self.get_chunk(position) {
+            Ok(chunk) => chunk,
+            Err(_) => self.add_grid_chunk(position, self.chunk_size.unwrap_or(SPAWN_CHUNK_SIZE))
+        };
+
+        chunk.add_object(object);
+
+        id
+    }
+
+    // new chunk covering the grid cell containing position, only called when no chunk covers it
+    fn add_grid_chunk(&mut self, position: Vec2, size: f32) -> Rc<Chunk> {
+
+        let cell = (position / size).floor();
+        let coordinates = cell.as_ivec2();
+
+        let chunk = Rc::new(Chunk::new(coordinates));
+
+        let begin = cell * size;
+
+        let corners = ChunkCorners {
+            begin,
+            end: begin + Vec2::splat(size),
+            chunk: coordinates
+        };
+
+        self.grid_chunks.push((corners, Rc::clone(&chunk)));
+
+        chunk
+    }
+
+    // chunks added with add_chunk ordered by their x coordinate, then by y, followed by the grid
+    // chunks in the order spawn created them
+    pub fn chunks(&self) -> impl Iterator<Item = &Rc<Chunk>> {
+        self.chunk_map.values().chain(self.grid_chunks.iter().map(|(_, chunk)| chunk))
+    }
+
+    // merges the static colored objects of each chunk sharing a shader and render state into
+    // one object per batch, so they take one draw call. returns how many objects were merged,
+    // objects with a world_position are left alone
+    pub fn bake_static(&self) -> usize {
+
+        let mut merged = 0;
+
+        for chunk in self.chunks() {
+
+            let mut objects = chunk.objects.borrow_mut();
+
+            // indices of the objects drawn together
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+
+            for (index, object) in objects.iter().enumerate() {
+
+                let colored = match object.as_any().downcast_ref::<ColoredSceneObject>() {
+                    Some(colored) if colored.is_static && colored.world_position.is_none() && colored.vertices.len() <= BATCH_VERTEX_LIMIT => colored,
+                    _ => continue
+                };
+
+                match groups.iter_mut().find(|group| colored.batches_with(colored_at(&objects, group[0]))) {
+                    Some(group) => group.push(index),
+                    None => groups.push(vec![index])
+                }
+            }
+
+            groups.retain(|group| group.len() > 1);
+
+            if groups.is_empty() {
+                continue;
+            }
+
+            let batches: Vec<ColoredSceneObject> = groups.iter()
+                .flat_map(|group| ColoredSceneObject::merge(&group.iter().map(|index| colored_at(&objects, *index)).collect::<Vec<_>>()))
+                .collect();
+
+            let mut baked = vec![false; objects.len()];
+
+            for index in groups.iter().flatten() {
+                baked[*index] = true;
+                merged += 1;
+            }
+
+            let mut index = 0;
+
+            objects.retain(|_| {
+                index += 1;
+                !baked[index - 1]
+            });
+
+            objects.extend(batches.into_iter().map(|batch| Box::new(batch) as Box<dyn SceneObject>));
+
+            drop(objects);
+            chunk.invalidate_spatial();
+        }
+
+        merged
+    }
+
+    // ids of the objects of the type in all chunks, in chunk order
+    pub fn find_by_type(&self, object_type: ObjectTypes) -> Vec<Uuid> {
+        self.chunks()
+            .flat_map(|chunk| chunk.iter_objects().iter()
+                .filter(|object| object.get_type() == object_type)
+                .map(|object| object.get_id())
+                .collect::<Vec<Uuid>>())
+            .collect()
+    }
+
+    // runs the closure on the object with the given id, searching all chunks
+    pub fn with_object_mut<R, F: FnOnce(&mut dyn SceneObject) -> R>(&self, id: Uuid, f: F) -> Option<R> {
+
+        for chunk in self.chunks() {
+
+            for object in &mut chunk.iter_objects_mut() {
+
+                if object.get_id() == id {
+                    return Some(f(object.as_mut()));
+                }
+
+            }
+
+        }
+
+        None
+    }
+
+    // pairs of objects in the current chunk whose bounding boxes overlap, each pair is reported once
+    pub fn find_collisions(&self) -> Vec<(Uuid, Uuid)> {
+
+        let chunk = match self.get_current_chunk() {
+            Ok(chunk) => chunk,
+            Err(_) => return Vec::new()
+        };
+
+        let entries: Vec<(Uuid, Aabb)> = chunk.iter_objects().iter().map(|object| (object.get_id(), object.bounding_box())).collect();
+
+        let mut pairs = Vec::new();
+
+        for (index, (id, bounds)) in entries.iter().enumerate() {
+
+            for other in chunk.objects_in_aabb(bounds.min, bounds.max) {
+
+                if other > index {
+                    pairs.push((*id, entries[other].0));
+                }
+
+            }
+
+        }
+
+        pairs
+    }
+
+    // ids of objects in the current chunk overlapping bounds, the object with the given id is skipped
+    // so a planned move of that object can be tested against the others
+    pub fn overlaps(&self, id: Uuid, bounds: Aabb) -> Vec<Uuid> {
+
+        let chunk = match self.get_current_chunk() {
+            Ok(chunk) => chunk,
+            Err(_) => return Vec::new()
+        };
+
+        let indices = chunk.objects_in_aabb(bounds.min, bounds.max);
+
+        let objects = chunk.iter_objects();
+
+        indices.into_iter()
+            .map(|index| objects[index].get_id())
+            .filter(|other| *other != id)
+            .collect()
+    }
+
+}
+
+// object at index, known to be a ColoredSceneObject
+fn colored_at(objects: &[Box<dyn SceneObject>], index: usize) -> &ColoredSceneObject {
+    objects[index].as_any().downcast_ref::<ColoredSceneObject>().unwrap()
 }
 
 #[cfg(test)]
 mod tests {
-    use glam::{IVec2, Vec2, Vec3};
-    use crate::renderer::renderer::RenderView;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use glam::{DVec3, IVec2, Vec2, Vec3};
+    use uuid::Uuid;
+    use crate::error::XgError;
+    use crate::renderer::RenderView;
+    use crate::scene::bounds::Aabb;
     use crate::scene::chunk::Chunk;
+    use crate::scene::object::{ColoredSceneObject, ColoredVertex, ImageTexturedSceneObject, ObjectTypes, test_cube, test_shaders};
     use crate::scene::scene::Scene;
+    use crate::shader::ShaderContainer;
 
     #[test]
     fn chunk_test() {
@@ -100,4 +454,355 @@ mod tests {
         assert_eq!(scene.get_chunk(Vec2::new(200.0, 200.0)).is_err(), true);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn camera_bookmark_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(1.5, 2.25, -3.0), Vec3::new(0.1, 0.2, 0.3), Vec3::new(0.0, 1.0, 0.0)));
+
+        scene.save_camera_bookmark("spawn");
+
+        scene.camera.move_eye(4.0, crate::renderer::MoveDirection::FORWARD);
+        scene.camera.set_at(Vec3::new(9.0, 9.0, 9.0));
+        scene.camera.set_up(Vec3::X);
+
+        scene.goto_camera_bookmark("spawn", 0.0).unwrap();
+
+        assert_eq!(scene.camera.eye, Vec3::new(1.5, 2.25, -3.0));
+        assert_eq!(scene.camera.at, Vec3::new(0.1, 0.2, 0.3));
+        assert_eq!(scene.camera.up, Vec3::new(0.0, 1.0, 0.0));
+
+        assert_eq!(scene.goto_camera_bookmark("typo", 0.0), Err(XgError::CameraBookmarkNotFound(String::from("typo"))));
+    }
+
+    #[test]
+    fn camera_flight_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::ZERO, Vec3::Z, Vec3::Y));
+
+        scene.save_camera_bookmark("start");
+        scene.camera.set_eye(Vec3::new(10.0, 0.0, 0.0));
+
+        scene.goto_camera_bookmark("start", 1.0).unwrap();
+
+        // nothing moves before the next update
+        assert_eq!(scene.camera.eye, Vec3::new(10.0, 0.0, 0.0));
+
+        scene.update(0.5);
+        assert_eq!(scene.camera.eye, Vec3::new(5.0, 0.0, 0.0));
+
+        scene.update(0.5);
+        assert_eq!(scene.camera.eye, Vec3::ZERO);
+
+        // the flight is over, the camera can move again
+        scene.camera.set_eye(Vec3::ONE);
+        scene.update(0.5);
+        assert_eq!(scene.camera.eye, Vec3::ONE);
+    }
+
+    #[test]
+    fn chunk_order_test() {
+
+        let coordinates = [IVec2::new(3, 1), IVec2::new(-2, 5), IVec2::new(0, 0), IVec2::new(3, -4), IVec2::new(-2, -1)];
+
+        // insertion order must not matter
+        for rotation in 0..coordinates.len() {
+
+            let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO));
+
+            for index in 0..coordinates.len() {
+                let chunk = coordinates[(index + rotation) % coordinates.len()];
+                scene.add_chunk(Chunk::new(chunk), Vec2::ZERO, Vec2::ZERO);
+            }
+
+            let order: Vec<IVec2> = scene.chunks().map(|chunk| chunk.coordinates).collect();
+
+            assert_eq!(order, vec![IVec2::new(-2, -1), IVec2::new(-2, 5), IVec2::new(0, 0), IVec2::new(3, -4), IVec2::new(3, 1)]);
+        }
+    }
+
+    // unit cube with its 8 corners and 12 triangles
+    fn indexed_cube(shaders: &Rc<RefCell<Box<dyn ShaderContainer>>>, coordinates: Vec3) -> ColoredSceneObject {
+
+        let vertices: Box<[ColoredVertex]> = (0..8)
+            .map(|corner| ColoredVertex { coordinates: Vec3::new((corner & 1) as f32, ((corner >> 1) & 1) as f32, (corner >> 2) as f32), color_rgba: 0xffffffff })
+            .collect();
+
+        let indices: Box<[u16]> = Box::new([
+            0, 2, 1, 1, 2, 3, 4, 5, 6, 5, 7, 6,
+            0, 1, 4, 1, 5, 4, 2, 6, 3, 3, 6, 7,
+            0, 4, 2, 2, 4, 6, 1, 3, 5, 3, 7, 5
+        ]);
+
+        ColoredSceneObject::new(vertices, indices, Rc::clone(shaders), coordinates)
+    }
+
+    #[test]
+    fn bake_static_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        scene.add_chunk(Chunk::new(IVec2::new(0, 0)), Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+
+        let shaders = test_shaders();
+
+        for x in 0..4 {
+            let mut cube = indexed_cube(&shaders, Vec3::new(x as f32 * 2.0, 0.0, 0.0));
+            cube.is_static = true;
+            scene.spawn(Box::new(cube));
+        }
+
+        // moving objects and objects alone with their shader keep their own draw call
+        let dynamic = scene.spawn(Box::new(indexed_cube(&shaders, Vec3::ZERO)));
+
+        let mut alone = test_cube(1.0, Vec3::ZERO);
+        alone.is_static = true;
+        let alone = scene.spawn(Box::new(alone));
+
+        assert_eq!(scene.bake_static(), 4);
+
+        let chunk = scene.get_chunk(Vec2::new(0.0, 0.0)).unwrap();
+        let objects = chunk.iter_objects();
+
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[0].get_id(), dynamic);
+        assert_eq!(objects[1].get_id(), alone);
+
+        let merged = objects[2].as_any().downcast_ref::<ColoredSceneObject>().unwrap();
+
+        assert_eq!(merged.vertices.len(), 4 * 8);
+        assert_eq!(merged.indices.len(), 4 * 36);
+        assert_eq!(merged.indices.iter().max(), Some(&31));
+
+        // vertices are moved to world space
+        assert_eq!(merged.coordinates, Vec3::ZERO);
+        assert_eq!(merged.vertices[8].coordinates, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(merged.vertices[31].coordinates, Vec3::new(7.0, 1.0, 1.0));
+
+        drop(objects);
+
+        // nothing left to merge
+        assert_eq!(scene.bake_static(), 0);
+    }
+
+    #[test]
+    fn find_by_type_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        let textured = |coordinates: Vec3| Box::new(ImageTexturedSceneObject::new(
+            Box::new([]),
+            Box::new([]),
+            image::DynamicImage::new_rgb8(1, 1),
+            test_shaders(),
+            coordinates
+        ));
+
+        let near = scene.spawn(Box::new(test_cube(1.0, Vec3::ZERO)));
+        let sign = scene.spawn(textured(Vec3::new(1.0, 0.0, 1.0)));
+        let far = scene.spawn(Box::new(test_cube(1.0, Vec3::new(250.0, 0.0, 0.0))));
+        let far_sign = scene.spawn(textured(Vec3::new(260.0, 0.0, 0.0)));
+
+        assert_eq!(scene.find_by_type(ObjectTypes::Colored), vec![near, far]);
+        assert_eq!(scene.find_by_type(ObjectTypes::ImageTextured), vec![sign, far_sign]);
+        assert!(scene.find_by_type(ObjectTypes::Particles).is_empty());
+    }
+
+    #[test]
+    fn spawn_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        scene.add_chunk(Chunk::new(IVec2::new(7, 7)), Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+
+        // inside the existing chunk
+        let near = scene.spawn(Box::new(test_cube(1.0, Vec3::new(1.0, 0.0, 1.0))));
+
+        // outside, a chunk is created for the grid cell
+        let far = scene.spawn(Box::new(test_cube(1.0, Vec3::new(250.0, 0.0, -30.0))));
+        let far_neighbour = scene.spawn(Box::new(test_cube(1.0, Vec3::new(260.0, 0.0, -40.0))));
+
+        let existing = scene.get_chunk(Vec2::new(0.0, 0.0)).unwrap();
+        assert_eq!(existing.coordinates, IVec2::new(7, 7));
+        assert_eq!(existing.iter_objects()[0].get_id(), near);
+
+        let created = scene.get_chunk(Vec2::new(250.0, -30.0)).unwrap();
+        assert_eq!(created.coordinates, IVec2::new(2, -1));
+
+        let ids: Vec<Uuid> = created.iter_objects().iter().map(|object| object.get_id()).collect();
+        assert_eq!(ids, vec![far, far_neighbour]);
+    }
+
+    #[test]
+    fn chunk_grid_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        assert_eq!(scene.grid_coordinates(Vec2::new(5.0, 5.0)), None);
+
+        scene.set_chunk_size(16.0).unwrap();
+
+        assert_eq!(scene.grid_coordinates(Vec2::new(5.0, 5.0)), Some(IVec2::new(0, 0)));
+        assert_eq!(scene.grid_coordinates(Vec2::new(-0.5, 40.0)), Some(IVec2::new(-1, 2)));
+
+        let a = scene.spawn(Box::new(test_cube(1.0, Vec3::new(2.0, 0.0, 3.0))));
+        let b = scene.spawn(Box::new(test_cube(1.0, Vec3::new(-1000.0, 0.0, 500.0))));
+        let c = scene.spawn(Box::new(test_cube(1.0, Vec3::new(4000.0, 0.0, -4000.0))));
+
+        let chunk_of = |x: f32, z: f32| scene.get_chunk(Vec2::new(x, z)).unwrap();
+
+        assert_eq!(chunk_of(2.0, 3.0).coordinates, IVec2::new(0, 0));
+        assert_eq!(chunk_of(-1000.0, 500.0).coordinates, IVec2::new(-63, 31));
+        assert_eq!(chunk_of(4000.0, -4000.0).coordinates, IVec2::new(250, -250));
+
+        assert_eq!(chunk_of(2.0, 3.0).iter_objects()[0].get_id(), a);
+        assert_eq!(chunk_of(-1000.0, 500.0).iter_objects()[0].get_id(), b);
+        assert_eq!(chunk_of(4000.0, -4000.0).iter_objects()[0].get_id(), c);
+
+        // cells nothing was spawned in have no chunk yet
+        assert_eq!(scene.get_chunk(Vec2::new(100.0, 100.0)).err(), Some(XgError::ChunkNotFound));
+    }
+
+    #[test]
+    fn invalid_chunk_size_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO));
+
+        for size in [0.0, -16.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(scene.set_chunk_size(size).err(), Some(XgError::InvalidChunkSize(size.to_string())));
+        }
+
+        assert_eq!(scene.chunk_size(), None);
+
+        scene.set_chunk_size(16.0).unwrap();
+        assert!(scene.set_chunk_size(-1.0).is_err());
+        assert_eq!(scene.chunk_size(), Some(16.0));
+    }
+
+    #[test]
+    fn grid_keeps_added_chunks_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO));
+
+        scene.add_chunk(Chunk::new(IVec2::new(0, 0)), Vec2::splat(-50.0), Vec2::splat(50.0));
+        scene.set_chunk_size(100.0).unwrap();
+
+        let inside = scene.spawn(Box::new(test_cube(1.0, Vec3::new(10.0, 0.0, 10.0))));
+
+        // same cell coordinates as the added chunk but outside its bounds
+        let outside = scene.spawn(Box::new(test_cube(1.0, Vec3::new(60.0, 0.0, 60.0))));
+
+        let added = scene.get_chunk(Vec2::new(10.0, 10.0)).unwrap();
+        let created = scene.get_chunk(Vec2::new(60.0, 60.0)).unwrap();
+
+        assert!(!Rc::ptr_eq(&added, &created));
+        assert_eq!(created.coordinates, IVec2::new(0, 0));
+
+        assert_eq!(added.iter_objects().iter().map(|object| object.get_id()).collect::<Vec<Uuid>>(), vec![inside]);
+        assert_eq!(created.iter_objects().iter().map(|object| object.get_id()).collect::<Vec<Uuid>>(), vec![outside]);
+
+        assert_eq!(scene.chunks().count(), 2);
+        assert_eq!(scene.find_by_type(ObjectTypes::Colored), vec![inside, outside]);
+
+        // a cell of the grid created with another size keeps its bounds
+        scene.set_chunk_size(10.0).unwrap();
+
+        let moved = scene.spawn(Box::new(test_cube(1.0, Vec3::new(150.0, 0.0, 5.0))));
+        let small = scene.get_chunk(Vec2::new(150.0, 5.0)).unwrap();
+
+        assert_eq!(small.coordinates, IVec2::new(15, 0));
+        assert_eq!(small.iter_objects()[0].get_id(), moved);
+        assert_eq!(scene.get_chunk(Vec2::new(161.0, 5.0)).err(), Some(XgError::ChunkNotFound));
+    }
+
+    #[test]
+    fn sky_gradient_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        scene.set_sky_gradient(vec![(0.0, 0x000000ff), (4.0, 0xffffffff), (8.0, 0x000000ff)]);
+        assert_eq!(scene.clear_color, 0x000000ff);
+
+        scene.update(2.0);
+        assert_eq!(scene.clear_color, 0x808080ff);
+
+        scene.update(2.0);
+        assert_eq!(scene.clear_color, 0xffffffff);
+
+        // without a gradient the color stays as set
+        scene.clear_sky_gradient();
+        scene.update(2.0);
+        assert_eq!(scene.clear_color, 0xffffffff);
+    }
+
+    #[test]
+    fn large_world_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)));
+
+        let mut object = test_cube(1.0, Vec3::ZERO);
+        object.world_position = Some(DVec3::new(1_000_000.1, 0.0, 1_000_000.3));
+
+        // without an origin the position is truncated to f32
+        let truncated = scene.render_position(&object, object.coordinates);
+        assert!((truncated.x - 1_000_000.1).abs() < 0.1);
+        assert_ne!(truncated.x as f64, 1_000_000.1);
+
+        scene.set_camera_origin(Some(DVec3::new(1_000_000.0, 0.0, 1_000_000.0)));
+
+        let relative = scene.render_position(&object, object.coordinates);
+        assert!(relative.abs_diff_eq(Vec3::new(0.1, 0.0, 0.3), 1e-6));
+
+        // objects without a world position keep their coordinates
+        let plain = test_cube(1.0, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(scene.render_position(&plain, plain.coordinates), Vec3::new(2.0, 0.0, 0.0));
+
+        // rebasing keeps the relative placement of camera and object
+        scene.camera.eye = Vec3::new(0.5, 0.0, 0.0);
+        scene.camera.at = Vec3::new(0.5, 0.0, 1.0);
+        scene.rebase_origin();
+
+        assert_eq!(scene.camera.eye, Vec3::ZERO);
+        assert_eq!(scene.camera.at, Vec3::new(0.0, 0.0, 1.0));
+        assert!(scene.render_position(&object, object.coordinates).abs_diff_eq(Vec3::new(-0.4, 0.0, 0.3), 1e-6));
+    }
+
+    #[test]
+    fn collision_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        let a = test_cube(2.0, Vec3::new(0.0, 0.0, 0.0));
+        let b = test_cube(2.0, Vec3::new(1.0, 1.0, 1.0));
+        let c = test_cube(1.0, Vec3::new(10.0, 0.0, 0.0));
+
+        let (a_id, b_id, c_id) = (a.id, b.id, c.id);
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(Box::new(a));
+        chunk.add_object(Box::new(b));
+        chunk.add_object(Box::new(c));
+
+        scene.add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        assert_eq!(scene.find_collisions(), vec![(a_id, b_id)]);
+
+        assert_eq!(scene.overlaps(a_id, Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.5, 0.5, 0.5))), Vec::<Uuid>::new());
+        assert_eq!(scene.overlaps(c_id, Aabb::new(Vec3::new(2.5, 2.5, 2.5), Vec3::new(10.5, 3.0, 3.0))), vec![b_id]);
+        assert_eq!(scene.overlaps(Uuid::nil(), Aabb::new(Vec3::new(9.0, 0.0, 0.0), Vec3::new(10.5, 0.5, 0.5))), vec![c_id]);
+    }
+
+    #[test]
+    fn no_collision_test() {
+
+        let mut scene = Scene::new(String::from("test"), RenderView::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)));
+
+        let chunk = Chunk::new(IVec2::new(0, 0));
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::new(0.0, 0.0, 0.0))));
+        chunk.add_object(Box::new(test_cube(1.0, Vec3::new(5.0, 0.0, 0.0))));
+
+        scene.add_chunk(chunk, Vec2::new(-50.0, -50.0), Vec2::new(50.0, 50.0));
+
+        assert!(scene.find_collisions().is_empty());
+    }
+
+}
@@ -0,0 +1,140 @@
+// packed 0xRRGGBBAA color, same layout as the vertex colors
+pub type Rgba = u32;
+
+// per channel linear interpolation between two packed colors
+pub fn lerp_rgba(from: Rgba, to: Rgba, factor: f32) -> Rgba {
+
+    let factor = factor.clamp(0.0, 1.0);
+
+    let mut result: Rgba = 0;
+
+    for shift in [24, 16, 8, 0] {
+
+        let a = ((from >> shift) & 0xff) as f32;
+        let b = ((to >> shift) & 0xff) as f32;
+
+        let channel = (a + (b - a) * factor).round() as u32;
+
+        result |= (channel & 0xff) << shift;
+    }
+
+    result
+}
+
+// clear color keyed over time, e.g. dawn/noon/dusk/night.
+// the cycle length is the time of the last key, repeat the first color as the last key for a seamless loop
+pub struct SkyGradient {
+    keys: Vec<(f32, Rgba)>,
+    time: f32
+}
+
+impl SkyGradient {
+
+    // constructor, keys are sorted by time
+    pub fn new(mut keys: Vec<(f32, Rgba)>) -> Self {
+
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self {
+            keys,
+            time: 0.0
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        match self.keys.last() {
+            Some(key) => key.0,
+            None => 0.0
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    // moves the time of day by delta seconds, wrapping at the end of the cycle
+    pub fn advance(&mut self, delta: f32) {
+
+        self.time += delta;
+
+        let duration = self.duration();
+
+        if duration > 0.0 {
+            self.time %= duration;
+        } else {
+            self.time = 0.0;
+        }
+    }
+
+    // interpolated color at time t, clamped to the first and last key
+    pub fn sample(&self, t: f32) -> Option<Rgba> {
+
+        let first = self.keys.first()?;
+
+        if t <= first.0 {
+            return Some(first.1);
+        }
+
+        for pair in self.keys.windows(2) {
+
+            let (from, to) = (pair[0], pair[1]);
+
+            if t < to.0 {
+                return Some(lerp_rgba(from.1, to.1, (t - from.0) / (to.0 - from.0)));
+            }
+
+        }
+
+        self.keys.last().map(|key| key.1)
+    }
+
+    pub fn color(&self) -> Option<Rgba> {
+        self.sample(self.time)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> SkyGradient {
+        SkyGradient::new(vec![
+            (10.0, 0x000000ff),
+            (0.0, 0x000000ff),
+            (5.0, 0x80ff40ff),
+        ])
+    }
+
+    #[test]
+    fn lerp_rgba_test() {
+        assert_eq!(lerp_rgba(0x00000000, 0xffffffff, 0.0), 0x00000000);
+        assert_eq!(lerp_rgba(0x00000000, 0xffffffff, 1.0), 0xffffffff);
+        assert_eq!(lerp_rgba(0x00ff0080, 0xff00ff80, 0.5), 0x80808080);
+    }
+
+    #[test]
+    fn sample_test() {
+
+        let gradient = gradient();
+
+        assert_eq!(gradient.sample(0.0), Some(0x000000ff));
+        assert_eq!(gradient.sample(5.0), Some(0x80ff40ff));
+        assert_eq!(gradient.sample(2.5), Some(0x408020ff));
+        assert_eq!(gradient.sample(7.5), Some(0x408020ff));
+
+        assert_eq!(SkyGradient::new(Vec::new()).sample(1.0), None);
+    }
+
+    #[test]
+    fn advance_wraps_test() {
+
+        let mut gradient = gradient();
+
+        gradient.advance(12.5);
+
+        assert_eq!(gradient.time(), 2.5);
+        assert_eq!(gradient.color(), Some(0x408020ff));
+    }
+
+}
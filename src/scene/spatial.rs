@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use glam::{IVec3, Vec3};
+use crate::scene::bounds::Aabb;
+
+// most cells an object is inserted into, larger objects are kept in a list tested by every query
+pub const MAX_CELLS_PER_OBJECT: usize = 64;
+
+// uniform grid over object bounding boxes, cells store indices into the chunk object list
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+    // objects covering more than MAX_CELLS_PER_OBJECT cells
+    oversized: Vec<usize>,
+    bounds: Vec<Aabb>
+}
+
+impl SpatialGrid {
+
+    // cell size follows the average object extent so most objects cover only a few cells.
+    // boxes with a NaN or infinite corner or with min above max are never found
+    pub fn build(bounds: Vec<Aabb>) -> Self {
+
+        let valid_bounds: Vec<&Aabb> = bounds.iter().filter(|aabb| valid(aabb)).collect();
+
+        let extent: f32 = valid_bounds.iter().map(|aabb| aabb.size().max_element()).sum();
+
+        // the sum of huge finite boxes can still overflow
+        let cell_size = match extent / valid_bounds.len() as f32 {
+            size if size.is_finite() => size.max(1.0),
+            _ => 1.0
+        };
+
+        let mut grid = Self {
+            cell_size,
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            bounds
+        };
+
+        for index in 0..grid.bounds.len() {
+
+            if !valid(&grid.bounds[index]) {
+                continue;
+            }
+
+            let (begin, end) = grid.cell_range(&grid.bounds[index]);
+
+            if cell_count(begin, end) > MAX_CELLS_PER_OBJECT as f64 {
+                grid.oversized.push(index);
+                continue;
+            }
+
+            for x in begin.x..=end.x {
+                for y in begin.y..=end.y {
+                    for z in begin.z..=end.z {
+                        grid.cells.entry(IVec3::new(x, y, z)).or_insert_with(Vec::new).push(index);
+                    }
+                }
+            }
+
+        }
+
+        grid
+    }
+
+    // sorted indices of all boxes intersecting the query box
+    pub fn query(&self, query: &Aabb) -> Vec<usize> {
+
+        if !valid(query) {
+            return Vec::new();
+        }
+
+        let (begin, end) = self.cell_range(query);
+
+        let mut result: Vec<usize> = Vec::new();
+
+        // walking more cells than there are objects is slower than testing every box
+        if cell_count(begin, end) > self.bounds.len() as f64 {
+
+            for (index, aabb) in self.bounds.iter().enumerate() {
+                if valid(aabb) && aabb.intersects(query) {
+                    result.push(index);
+                }
+            }
+
+            return result;
+        }
+
+        for x in begin.x..=end.x {
+            for y in begin.y..=end.y {
+                for z in begin.z..=end.z {
+
+                    if let Some(indices) = self.cells.get(&IVec3::new(x, y, z)) {
+
+                        for index in indices.iter() {
+                            if self.bounds[*index].intersects(query) {
+                                result.push(*index);
+                            }
+                        }
+
+                    }
+
+                }
+            }
+        }
+
+        for index in self.oversized.iter() {
+            if self.bounds[*index].intersects(query) {
+                result.push(*index);
+            }
+        }
+
+        result.sort_unstable();
+        result.dedup();
+
+        result
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> (IVec3, IVec3) {
+        (self.cell_of(aabb.min), self.cell_of(aabb.max))
+    }
+
+    fn cell_of(&self, point: Vec3) -> IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+
+}
+
+fn valid(aabb: &Aabb) -> bool {
+    aabb.min.is_finite() && aabb.max.is_finite() && aabb.min.cmple(aabb.max).all()
+}
+
+// cells between the corners, computed wide since far away boxes saturate the cell coordinates
+fn cell_count(begin: IVec3, end: IVec3) -> f64 {
+
+    let span = |begin: i32, end: i32| (end as i64 - begin as i64 + 1) as f64;
+
+    span(begin.x, end.x) * span(begin.y, end.y) * span(begin.z, end.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(x: f32) -> Aabb {
+        Aabb::new(Vec3::new(x, 0.0, 0.0), Vec3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn invalid_bounds_test() {
+
+        let grid = SpatialGrid::build(vec![
+            unit_box(0.0),
+            Aabb::new(Vec3::splat(f32::NAN), Vec3::ONE),
+            Aabb::new(Vec3::NEG_INFINITY, Vec3::INFINITY),
+            // inverted
+            Aabb::new(Vec3::ONE, Vec3::ZERO),
+            unit_box(5.0)
+        ]);
+
+        assert_eq!(grid.query(&Aabb::new(Vec3::splat(-10.0), Vec3::splat(10.0))), vec![0, 4]);
+        assert!(grid.cells.values().flatten().all(|index| *index == 0 || *index == 4));
+
+        assert!(grid.query(&Aabb::new(Vec3::splat(f32::NAN), Vec3::ONE)).is_empty());
+    }
+
+    #[test]
+    fn oversized_bounds_test() {
+
+        let mut bounds: Vec<Aabb> = (0..100).map(|index| unit_box(index as f32 * 10.0)).collect();
+
+        // spans about a hundred cells per axis next to the small boxes
+        bounds.push(Aabb::new(Vec3::splat(-1.0e30), Vec3::splat(1.0e30)));
+
+        let grid = SpatialGrid::build(bounds);
+
+        // the huge box is in no cell but still found
+        assert_eq!(grid.oversized, vec![100]);
+        assert!(grid.cells.values().flatten().all(|index| *index < 100));
+
+        assert_eq!(grid.query(&unit_box(30.0)), vec![3, 100]);
+        assert_eq!(grid.query(&Aabb::new(Vec3::splat(1.0e20), Vec3::splat(1.0e20 + 1.0))), vec![100]);
+    }
+
+}
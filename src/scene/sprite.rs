@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use glam::{Vec2, Vec3};
+use image::{DynamicImage, GenericImageView};
+use crate::scene::object::{ImageTexturedSceneObject, ImageTexturedVertex};
+use crate::shader::ShaderContainer;
+
+// texture split into a grid of equally sized cells, indexed row by row from the top left.
+// cells can also be looked up by name, see SpriteSheet::name
+pub struct SpriteSheet {
+    pub texture: DynamicImage,
+    pub shaders: Rc<RefCell<Box<dyn ShaderContainer>>>,
+    columns: u32,
+    rows: u32,
+    names: HashMap<String, u32>,
+    // world size of a sprite quad, one unit per cell by default
+    pub size: Vec2
+}
+
+impl SpriteSheet {
+
+    pub fn new(texture: DynamicImage, columns: u32, rows: u32, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>) -> Self {
+        Self {
+            texture, shaders,
+            columns: columns.max(1),
+            rows: rows.max(1),
+            names: HashMap::new(),
+            size: Vec2::ONE
+        }
+    }
+
+    // sheet with cells of the given pixel size, a partial cell at the edge is left out
+    pub fn with_cell_size(texture: DynamicImage, cell_width: u32, cell_height: u32, shaders: Rc<RefCell<Box<dyn ShaderContainer>>>) -> Self {
+
+        let (width, height) = texture.dimensions();
+
+        Self::new(texture, width / cell_width.max(1), height / cell_height.max(1), shaders)
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn len(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // names the cell at index, returns false when the index is outside the sheet
+    pub fn name(&mut self, name: &str, index: u32) -> bool {
+
+        if index >= self.len() {
+            return false;
+        }
+
+        self.names.insert(String::from(name), index);
+        true
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<u32> {
+        self.names.get(name).copied()
+    }
+
+    // top left and bottom right uv of the cell, v grows downwards like the texture rows
+    pub fn uv(&self, index: u32) -> Option<(Vec2, Vec2)> {
+        grid_uv(self.columns, self.rows, index)
+    }
+
+    // textured quad of the cell centered on position, facing +z
+    pub fn sprite(&self, index: u32, position: Vec3) -> Option<ImageTexturedSceneObject> {
+
+        let (min, max) = self.uv(index)?;
+
+        let (u0, v0) = (uv_to_texture(min.x), uv_to_texture(min.y));
+        let (u1, v1) = (uv_to_texture(max.x), uv_to_texture(max.y));
+
+        let half = self.size / 2.0;
+
+        let vertices = Box::new([
+            ImageTexturedVertex { coordinates: Vec3::new(-half.x, -half.y, 0.0), texture_u: u0, texture_v: v1 },
+            ImageTexturedVertex { coordinates: Vec3::new(half.x, -half.y, 0.0), texture_u: u1, texture_v: v1 },
+            ImageTexturedVertex { coordinates: Vec3::new(half.x, half.y, 0.0), texture_u: u1, texture_v: v0 },
+            ImageTexturedVertex { coordinates: Vec3::new(-half.x, half.y, 0.0), texture_u: u0, texture_v: v0 }
+        ]);
+
+        Some(ImageTexturedSceneObject::new(vertices, Box::new([0, 1, 2, 0, 2, 3]), self.texture.clone(), self.shaders.clone(), position))
+    }
+
+    pub fn sprite_named(&self, name: &str, position: Vec3) -> Option<ImageTexturedSceneObject> {
+        self.sprite(self.index_of(name)?, position)
+    }
+
+}
+
+// uv rectangle of a cell in a columns x rows grid covering the whole texture
+pub fn grid_uv(columns: u32, rows: u32, index: u32) -> Option<(Vec2, Vec2)> {
+
+    if columns == 0 || rows == 0 || index >= columns * rows {
+        return None;
+    }
+
+    let cell = Vec2::new(1.0 / columns as f32, 1.0 / rows as f32);
+    let min = Vec2::new((index % columns) as f32, (index / columns) as f32) * cell;
+
+    Some((min, min + cell))
+}
+
+// normalized i16 texture coordinate of the vertex layout, i16::MAX is 1.0
+fn uv_to_texture(value: f32) -> i16 {
+    (value.clamp(0.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_uv_test() {
+
+        // 4 columns, 2 rows
+        assert_eq!(grid_uv(4, 2, 0), Some((Vec2::new(0.0, 0.0), Vec2::new(0.25, 0.5))));
+        assert_eq!(grid_uv(4, 2, 3), Some((Vec2::new(0.75, 0.0), Vec2::new(1.0, 0.5))));
+        assert_eq!(grid_uv(4, 2, 5), Some((Vec2::new(0.25, 0.5), Vec2::new(0.5, 1.0))));
+
+        assert_eq!(grid_uv(4, 2, 8), None);
+        assert_eq!(grid_uv(0, 2, 0), None);
+    }
+
+    #[test]
+    fn uv_to_texture_test() {
+
+        assert_eq!(uv_to_texture(0.0), 0);
+        assert_eq!(uv_to_texture(1.0), i16::MAX);
+        assert_eq!(uv_to_texture(0.5), 16384);
+        assert_eq!(uv_to_texture(2.0), i16::MAX);
+    }
+
+}
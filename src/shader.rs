@@ -1,17 +1,288 @@
 use std::any::Any;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::fmt::{Display, Formatter};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
 use bgfx_rs::bgfx;
-use bgfx_rs::bgfx::{Memory, Program, Shader};
+use bgfx_rs::bgfx::{AddArgs, Attrib, AttribType, Memory, Program, RendererType, Shader, Uniform, VertexLayoutBuilder};
+use glam::{Mat4, Vec4};
+use log::warn;
+use crate::error::XgError;
+use crate::events::ErrorSource;
 
 pub trait ShaderContainer {
 
     fn loaded(&self) -> bool;
-    fn load(&mut self);
+
+    // creates the backend objects for the renderer in the context, the container stays unloaded on error
+    fn load(&mut self, context: &ShaderLoadContext) -> Result<(), ShaderLoadError>;
+
+    // releases the backend objects and keeps the sources, so load can be called again.
+    // call it between frames, never while the renderer is submitting draws with the shader
+    fn unload(&mut self) {}
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
 
+    // name the shader was registered under, if any
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_name(&mut self, _name: String) {}
+
+    // hash of the shader sources used by ShaderManager::add_shader_dedup, none opts out.
+    // containers holding runtime state like pipelines are compared by their sources only
+    fn content_hash(&self) -> Option<u64> {
+        None
+    }
+
+    // true when the other container holds the same sources, add_shader_dedup checks it when the
+    // content hashes match so colliding hashes are never merged
+    fn same_content(&self, _other: &dyn ShaderContainer) -> bool {
+        false
+    }
+
+    // uniforms the shader expects, none for containers without uniform support
+    fn uniforms(&self) -> Option<&ShaderUniforms> {
+        None
+    }
+
+    fn uniforms_mut(&mut self) -> Option<&mut ShaderUniforms> {
+        None
+    }
+
+    fn declare_uniform(&mut self, name: &str, uniform_type: UniformType) {
+        match self.uniforms_mut() {
+            Some(uniforms) => uniforms.declare(name, uniform_type),
+            None => warn!("Shader does not support uniforms, {} is ignored", name)
+        }
+    }
+
+    // value used for the uniform when an object does not override it
+    fn set_default(&mut self, name: &str, value: UniformValue) -> Result<(), XgError> {
+        match self.uniforms_mut() {
+            Some(uniforms) => uniforms.set_default(name, value),
+            None => Err(XgError::UndeclaredUniforms(vec![String::from(name)]))
+        }
+    }
+
+    // set by the renderer once a failed load was reported, it skips the container without trying
+    // and reporting again. cleared by unload and reload. containers without the flag report
+    // their failures every frame
+    fn load_failed(&self) -> bool {
+        false
+    }
+
+    fn set_load_failed(&mut self, _failed: bool) {}
+
+}
+
+// state of the renderer a shader is loaded for, built by the renderer when it loads a shader lazily
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShaderLoadContext {
+    // renderer bgfx selected, vertex layouts and binaries have to match it
+    pub backend: RendererType
+}
+
+impl ShaderLoadContext {
+
+    pub fn new(backend: RendererType) -> Self {
+        Self { backend }
+    }
+
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderLoadError {
+    // name the shader was registered under, if any
+    pub shader: Option<String>,
+    // renderer the shader was loaded for, if known
+    pub backend: Option<RendererType>,
+    pub reason: String
+}
+
+impl Display for ShaderLoadError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.shader {
+            Some(name) => write!(f, "Failed to load shader {}", name)?,
+            None => write!(f, "Failed to load shader")?
+        }
+
+        if let Some(backend) = self.backend {
+            write!(f, " for {:?}", backend)?;
+        }
+
+        write!(f, ": {}", self.reason)
+    }
+
+}
+
+impl std::error::Error for ShaderLoadError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UniformType {
+    Vec4,
+    Mat4,
+    // bound together with a texture by the renderer, it takes no value
+    Sampler
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UniformValue {
+    Vec4(Vec4),
+    Mat4(Mat4)
+}
+
+impl UniformValue {
+
+    pub fn uniform_type(&self) -> UniformType {
+        match self {
+            UniformValue::Vec4(_) => UniformType::Vec4,
+            UniformValue::Mat4(_) => UniformType::Mat4
+        }
+    }
+
+}
+
+// uniform declarations of a shader and their default values
+#[derive(Clone, Debug, Default)]
+pub struct ShaderUniforms {
+    declared: HashMap<String, UniformType>,
+    defaults: HashMap<String, UniformValue>
+}
+
+impl ShaderUniforms {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // redeclaring a uniform with another type drops its default
+    pub fn declare(&mut self, name: &str, uniform_type: UniformType) {
+
+        if self.declared.insert(String::from(name), uniform_type) != Some(uniform_type) {
+            self.defaults.remove(name);
+        }
+    }
+
+    pub fn set_default(&mut self, name: &str, value: UniformValue) -> Result<(), XgError> {
+
+        match self.declared.get(name) {
+            Some(uniform_type) if *uniform_type == value.uniform_type() => {}
+            Some(_) => return Err(XgError::UniformTypeMismatch(String::from(name))),
+            None => return Err(XgError::UndeclaredUniforms(vec![String::from(name)]))
+        }
+
+        self.defaults.insert(String::from(name), value);
+
+        Ok(())
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<UniformType> {
+        self.declared.get(name).copied()
+    }
+
+    // declared names in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.declared.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    // defaults with the object overrides applied on top, sorted by name. overrides of
+    // undeclared uniforms or with the wrong type are reported together, sorted as well
+    pub fn merge(&self, overrides: &HashMap<String, UniformValue>) -> Result<Vec<(String, UniformValue)>, XgError> {
+
+        let mut invalid: Vec<String> = overrides.iter()
+            .filter(|(name, value)| self.get_type(name) != Some(value.uniform_type()))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        if !invalid.is_empty() {
+            invalid.sort();
+            return Err(XgError::UndeclaredUniforms(invalid));
+        }
+
+        let mut values: Vec<(String, UniformValue)> = self.defaults.iter()
+            .filter(|(name, _)| !overrides.contains_key(*name))
+            .chain(overrides.iter())
+            .map(|(name, value)| (name.clone(), *value))
+            .collect();
+
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(values)
+    }
+
+}
+
+fn bgfx_uniform_type(uniform_type: UniformType) -> bgfx::UniformType {
+    match uniform_type {
+        UniformType::Vec4 => bgfx::UniformType::Vec4,
+        UniformType::Mat4 => bgfx::UniformType::Mat4,
+        UniformType::Sampler => bgfx::UniformType::Sampler
+    }
+}
+
+// vertex format consumed by a bgfx shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BgfxShaderVertexType {
+    COLORED,
+    TEXTURED,
+    TGA,
+    // layout built by the function for the renderer type, for vertex formats the engine does not know
+    CUSTOM(fn(RendererType) -> VertexLayoutBuilder)
+}
+
+// vertex layout for the vertex type, begun for the renderer type bgfx actually selected
+pub fn get_vertex_layout(vertex_type: BgfxShaderVertexType, renderer_type: RendererType) -> VertexLayoutBuilder {
+
+    let layout = VertexLayoutBuilder::new();
+
+    match vertex_type {
+
+        // ColoredVertex
+        BgfxShaderVertexType::COLORED => {
+            layout
+                .begin(renderer_type)
+                .add(Attrib::Position, 3, AttribType::Float, AddArgs::default())
+                .add(Attrib::Color0, 4, AttribType::Uint8, AddArgs { normalized: true, as_int: false })
+                .end();
+        }
+
+        // ImageTexturedVertex, u and v are i16 mapped to -1..1
+        BgfxShaderVertexType::TEXTURED => {
+            layout
+                .begin(renderer_type)
+                .add(Attrib::Position, 3, AttribType::Float, AddArgs::default())
+                .add(Attrib::TexCoord0, 2, AttribType::Int16, AddArgs { normalized: true, as_int: false })
+                .end();
+        }
+
+        // TgaTexturedVertex, attributes in struct field order like the bgfx bump example.
+        // the vertex shader gets a_position, a_normal, a_tangent and a_texcoord0, normal and tangent
+        // are packed to 0..1 and need unpacking with * 2.0 - 1.0, and it is expected to pass
+        // v_wpos, v_view, v_normal, v_tangent, v_bitangent and v_texcoord0 to the pixel shader
+        BgfxShaderVertexType::TGA => {
+            layout
+                .begin(renderer_type)
+                .add(Attrib::Position, 3, AttribType::Float, AddArgs::default())
+                .add(Attrib::Normal, 4, AttribType::Uint8, AddArgs { normalized: true, as_int: false })
+                .add(Attrib::Tangent, 4, AttribType::Uint8, AddArgs { normalized: true, as_int: false })
+                .add(Attrib::TexCoord0, 2, AttribType::Int16, AddArgs { normalized: true, as_int: false })
+                .end();
+        }
+
+        BgfxShaderVertexType::CUSTOM(build) => return build(renderer_type)
+
+    }
+
+    layout
 }
 
 pub struct BgfxShaderContainer {
@@ -22,12 +293,25 @@ pub struct BgfxShaderContainer {
     vertex_mem: Option<Memory>,
     pixel: Option<Shader>,
     vertex: Option<Shader>,
-    pub program: Option<Rc<Program>>
+    pub program: Option<Rc<Program>>,
+    pub vertex_type: BgfxShaderVertexType,
+    name: Option<String>,
+    // pixel and vertex binary paths when created from files, used by reload
+    paths: Option<(PathBuf, PathBuf)>,
+    uniforms: ShaderUniforms,
+    // bgfx handles of the declared uniforms, created on load
+    uniform_handles: HashMap<String, Uniform>,
+    // see ShaderContainer::load_failed
+    load_failed: bool
 }
 
 impl BgfxShaderContainer {
 
     pub fn new(pixel_raw: Vec<u8>, vertex_raw: Vec<u8>) -> Self {
+        Self::with_vertex_type(pixel_raw, vertex_raw, BgfxShaderVertexType::COLORED)
+    }
+
+    pub fn with_vertex_type(pixel_raw: Vec<u8>, vertex_raw: Vec<u8>, vertex_type: BgfxShaderVertexType) -> Self {
 
         Self {
             loaded: false,
@@ -37,19 +321,112 @@ impl BgfxShaderContainer {
             vertex_mem: None,
             pixel: None,
             vertex: None,
-            program: None
+            program: None,
+            vertex_type,
+            name: None,
+            paths: None,
+            uniforms: ShaderUniforms::new(),
+            uniform_handles: HashMap::new(),
+            load_failed: false
         }
     }
 
-}
+    // reads compiled shader binaries from disk, the paths are kept for reload
+    pub fn from_files<P: AsRef<Path>>(pixel_path: P, vertex_path: P) -> std::io::Result<Self> {
 
-impl ShaderContainer for BgfxShaderContainer {
+        let pixel_raw = read_binary(pixel_path.as_ref(), b"FSH")?;
+        let vertex_raw = read_binary(vertex_path.as_ref(), b"VSH")?;
 
-    fn loaded(&self) -> bool {
-        self.loaded
+        let mut container = Self::new(pixel_raw, vertex_raw);
+        container.paths = Some((pixel_path.as_ref().to_path_buf(), vertex_path.as_ref().to_path_buf()));
+
+        Ok(container)
+    }
+
+    // compiles .sc sources with shaderc for the target renderer and keeps the resulting binaries
+    #[cfg(feature = "shaderc")]
+    pub fn compile(vertex_src: &str, fragment_src: &str, varying_def: &str, target: RendererType) -> Result<Self, crate::shaderc::ShaderCompileError> {
+        Self::compile_with(&crate::shaderc::Shaderc::default(), vertex_src, fragment_src, varying_def, target)
+    }
+
+    #[cfg(feature = "shaderc")]
+    pub fn compile_with(shaderc: &crate::shaderc::Shaderc, vertex_src: &str, fragment_src: &str, varying_def: &str, target: RendererType) -> Result<Self, crate::shaderc::ShaderCompileError> {
+
+        let (pixel_raw, vertex_raw) = shaderc.compile(vertex_src, fragment_src, varying_def, target)?;
+
+        Ok(Self::new(pixel_raw, vertex_raw))
+    }
+
+    // re-reads the binaries from disk. on success the container is marked as not loaded so the
+    // renderer recreates the program on the next frame, on failure the current program stays in use
+    pub fn reload(&mut self) -> std::io::Result<()> {
+
+        let (pixel_path, vertex_path) = match &self.paths {
+            Some(paths) => paths.clone(),
+            None => return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Shader was not created from files"))
+        };
+
+        let pixel_raw = read_binary(&pixel_path, b"FSH")?;
+        let vertex_raw = read_binary(&vertex_path, b"VSH")?;
+
+        // the memory references point into the old binaries, so they go first
+        self.unload();
+
+        self.pixel_raw = pixel_raw;
+        self.vertex_raw = vertex_raw;
+
+        Ok(())
+    }
+
+    // layout of the vertices this shader consumes, for the active bgfx renderer
+    pub fn get_layout_builder(&self) -> VertexLayoutBuilder {
+        get_vertex_layout(self.vertex_type, bgfx::get_renderer_type())
+    }
+
+    // sets the defaults merged with the object overrides for the next submit, undeclared
+    // overrides are reported and nothing is set
+    pub fn apply_uniforms(&self, overrides: &HashMap<String, UniformValue>) -> Result<(), XgError> {
+
+        for (name, value) in self.uniforms.merge(overrides)? {
+
+            let handle = match self.uniform_handles.get(&name) {
+                Some(handle) => handle,
+                None => continue
+            };
+
+            match value {
+                UniformValue::Vec4(value) => bgfx::set_uniform(handle, &value.to_array(), 1),
+                UniformValue::Mat4(value) => bgfx::set_uniform(handle, &value.to_cols_array(), 1)
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_uniform_handles(&mut self) {
+
+        for name in self.uniforms.names() {
+
+            if self.uniform_handles.contains_key(name) {
+                continue;
+            }
+
+            let uniform_type = bgfx_uniform_type(self.uniforms.get_type(name).unwrap());
+
+            self.uniform_handles.insert(String::from(name), bgfx::create_uniform(name, uniform_type, 1));
+        }
     }
 
-    fn load(&mut self) {
+}
+
+// creates the program for a cache miss, bgfx containers create it from their own binaries
+trait ProgramFactory<P> {
+    fn create_program(&mut self) -> P;
+}
+
+impl ProgramFactory<Program> for BgfxShaderContainer {
+
+    fn create_program(&mut self) -> Program {
 
         self.pixel_mem = Option::from(unsafe { Memory::reference(&self.pixel_raw) });
         self.vertex_mem = Option::from(unsafe { Memory::reference(&self.vertex_raw) });
@@ -59,10 +436,217 @@ impl ShaderContainer for BgfxShaderContainer {
         self.vertex = Option::from(unsafe { bgfx::create_shader(&self.vertex_mem.unwrap()) });
 
         // create program with bgfx
-        self.program = Some(Rc::new(unsafe { bgfx::create_program(&self.vertex.clone().unwrap(), &self.pixel.clone().unwrap(), true) }));
+        unsafe { bgfx::create_program(&self.vertex.clone().unwrap(), &self.pixel.clone().unwrap(), true) }
+    }
+
+}
+
+// programs by the hash of their binaries, an entry lives as long as a container holds the program.
+// entries keep the binaries, so binaries with colliding hashes never share a program
+struct ProgramCache<P> {
+    programs: HashMap<u64, Vec<CachedProgram<P>>>
+}
+
+struct CachedProgram<P> {
+    pixel_raw: Vec<u8>,
+    vertex_raw: Vec<u8>,
+    program: Weak<P>
+}
+
+impl<P> ProgramCache<P> {
+
+    fn new() -> Self {
+        Self {
+            programs: HashMap::new()
+        }
+    }
+
+    fn get_or_create<F: ProgramFactory<P>>(&mut self, pixel_raw: Vec<u8>, vertex_raw: Vec<u8>, factory: &mut F) -> Rc<P> {
+
+        for entries in self.programs.values_mut() {
+            entries.retain(|entry| entry.program.strong_count() > 0);
+        }
+
+        self.programs.retain(|_, entries| !entries.is_empty());
+
+        let entries = self.programs.entry(source_hash((&pixel_raw, &vertex_raw))).or_insert_with(Vec::new);
+
+        let cached = entries.iter()
+            .find(|entry| entry.pixel_raw == pixel_raw && entry.vertex_raw == vertex_raw)
+            .and_then(|entry| entry.program.upgrade());
+
+        if let Some(program) = cached {
+            return program;
+        }
+
+        let program = Rc::new(factory.create_program());
+        entries.push(CachedProgram { pixel_raw, vertex_raw, program: Rc::downgrade(&program) });
+
+        program
+    }
+
+}
+
+thread_local! {
+    // bgfx handles are only used on the render thread
+    static PROGRAM_CACHE: RefCell<ProgramCache<Program>> = RefCell::new(ProgramCache::new());
+}
+
+// hash of shader sources and whatever else tells them apart, for the program cache and content_hash
+fn source_hash<T: Hash>(sources: T) -> u64 {
+
+    let mut hasher = DefaultHasher::new();
+
+    sources.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+// bgfx shader binaries start with a three byte magic naming the stage, anything else is rejected by bgfx
+fn check_binary(raw: &[u8], magic: &[u8; 3]) -> std::io::Result<()> {
+
+    if raw.len() < 4 || &raw[..3] != magic {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Not a bgfx {} shader binary", String::from_utf8_lossy(magic))));
+    }
+
+    Ok(())
+}
+
+// containers ShaderManager::reload can re-read from disk
+fn reloadable(shader: &dyn ShaderContainer) -> bool {
+    match shader.as_any().downcast_ref::<BgfxShaderContainer>() {
+        Some(shader) => shader.paths.is_some(),
+        None => false
+    }
+}
+
+// reads and checks a shader binary, errors keep their kind and name the path
+fn read_binary(path: &Path, magic: &[u8; 3]) -> std::io::Result<Vec<u8>> {
+
+    let result = fs::read(path).and_then(|raw| check_binary(&raw, magic).map(|_| raw));
+
+    result.map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+// pixel and vertex binaries of the bgfx cubes shader, only metal and opengl are shipped.
+// other renderers get None, there are no dx11, spirv or essl binaries in resources/shaders
+#[cfg(feature = "builtin-shaders")]
+fn builtin_colored(renderer_type: RendererType) -> Option<(&'static [u8], &'static [u8])> {
+    match renderer_type {
+        RendererType::Metal => Some((
+            include_bytes!("../resources/shaders/metal/fs_cubes.bin"),
+            include_bytes!("../resources/shaders/metal/vs_cubes.bin")
+        )),
+        RendererType::OpenGL => Some((
+            include_bytes!("../resources/shaders/opengl/fs_cubes.bin"),
+            include_bytes!("../resources/shaders/opengl/vs_cubes.bin")
+        )),
+        _ => None
+    }
+}
+
+// directory holding the shaderc output for a renderer, as laid out in resources/shaders/<backend>.
+// the bgfx examples name the opengl directory glsl instead
+pub fn backend_dir(backend: RendererType) -> Option<&'static str> {
+    match backend {
+        RendererType::Direct3D11 | RendererType::Direct3D12 => Some("dx11"),
+        RendererType::Metal => Some("metal"),
+        RendererType::OpenGL => Some("opengl"),
+        RendererType::OpenGLES => Some("essl"),
+        RendererType::Vulkan => Some("spirv"),
+        _ => None
+    }
+}
+
+// loads <base_dir>/<backend_dir>/vs_<name>.bin and fs_<name>.bin, pass the renderer bgfx
+// actually selected (bgfx::get_renderer_type) rather than guessing from the target os
+pub fn load_for_backend(base_dir: &Path, name: &str, backend: RendererType, vertex_type: BgfxShaderVertexType) -> std::io::Result<BgfxShaderContainer> {
+
+    let directory = match backend_dir(backend) {
+        Some(directory) => base_dir.join(directory),
+        None => return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("No shader binaries for {:?}", backend)))
+    };
+
+    let mut container = BgfxShaderContainer::from_files(directory.join(format!("fs_{}.bin", name)), directory.join(format!("vs_{}.bin", name)))?;
+    container.vertex_type = vertex_type;
+
+    Ok(container)
+}
+
+// fs_<name>.bin and vs_<name>.bin in the backend subdirectory of shader_dir, the directory
+// has to hold a single shader, load_for_backend picks one by name otherwise
+pub fn auto_shader_paths(shader_dir: &Path, backend: RendererType) -> std::io::Result<(PathBuf, PathBuf)> {
+
+    let directory = match backend_dir(backend) {
+        Some(directory) => shader_dir.join(directory),
+        None => return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("No shader binaries for {:?}", backend)))
+    };
+
+    let mut names: Vec<String> = Vec::new();
+
+    for entry in fs::read_dir(&directory)? {
+
+        let file_name = entry?.file_name();
+
+        let name = file_name.to_str()
+            .and_then(|file_name| file_name.strip_prefix("fs_"))
+            .and_then(|file_name| file_name.strip_suffix(".bin"));
+
+        if let Some(name) = name {
+            names.push(String::from(name));
+        }
+    }
+
+    names.sort();
+
+    match names.as_slice() {
+        [name] => Ok((directory.join(format!("fs_{}.bin", name)), directory.join(format!("vs_{}.bin", name)))),
+        [] => Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("No fragment shader in {}", directory.display()))),
+        _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Several shaders in {}: {}", directory.display(), names.join(", "))))
+    }
+}
+
+impl ShaderContainer for BgfxShaderContainer {
+
+    fn loaded(&self) -> bool {
+        self.loaded
+    }
+
+    fn load(&mut self, context: &ShaderLoadContext) -> Result<(), ShaderLoadError> {
+
+        // bgfx aborts on binaries it cannot parse, so obviously broken ones never reach it
+        let checked = check_binary(&self.pixel_raw, b"FSH").and_then(|_| check_binary(&self.vertex_raw, b"VSH"));
+
+        if let Err(e) = checked {
+            return Err(ShaderLoadError {
+                shader: self.name.clone(),
+                backend: Some(context.backend),
+                reason: e.to_string()
+            });
+        }
+
+        // containers with the same binaries share one program
+        let (pixel_raw, vertex_raw) = (self.pixel_raw.clone(), self.vertex_raw.clone());
+        self.program = Some(PROGRAM_CACHE.with(|cache| cache.borrow_mut().get_or_create(pixel_raw, vertex_raw, self)));
+
+        self.create_uniform_handles();
 
         self.loaded = true;
 
+        Ok(())
+    }
+
+    // drops the bgfx program, shaders and uniforms, which destroys their handles. bgfx
+    // destroys them once the frame already submitted is done
+    fn unload(&mut self) {
+        self.uniform_handles.clear();
+        self.program = None;
+        self.pixel = None;
+        self.vertex = None;
+        self.pixel_mem = None;
+        self.vertex_mem = None;
+        self.loaded = false;
+        self.load_failed = false;
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -72,31 +656,1405 @@ impl ShaderContainer for BgfxShaderContainer {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
-}
 
-pub struct ShaderManager {
-    pub shaders: HashMap<i32, Rc<RefCell<Box<dyn ShaderContainer>>>>
-}
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 
-impl ShaderManager {
+    fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
 
-    pub fn new() -> Self {
-        Self {
-            shaders: HashMap::new()
+    fn load_failed(&self) -> bool {
+        self.load_failed
+    }
+
+    fn set_load_failed(&mut self, failed: bool) {
+        self.load_failed = failed;
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+        Some(source_hash((&self.pixel_raw, &self.vertex_raw, &self.vertex_type)))
+    }
+
+    fn same_content(&self, other: &dyn ShaderContainer) -> bool {
+        match other.as_any().downcast_ref::<BgfxShaderContainer>() {
+            Some(other) => self.pixel_raw == other.pixel_raw && self.vertex_raw == other.vertex_raw && self.vertex_type == other.vertex_type,
+            None => false
         }
     }
 
-    pub fn add_shader(&mut self, shader: Box<dyn ShaderContainer>) -> i32 {
-        let index: i32 = self.shaders.len() as i32;
-        self.shaders.insert(index, Rc::new(RefCell::new(shader)));
-        index
+    fn uniforms(&self) -> Option<&ShaderUniforms> {
+        Some(&self.uniforms)
     }
 
-    pub fn get_shader(&self, index: i32) -> Option<Rc<RefCell<Box<dyn ShaderContainer>>>> {
-        match self.shaders.get(&index) {
-            Some(shader) => Some(Rc::clone(shader)),
-            None => None
+    fn uniforms_mut(&mut self) -> Option<&mut ShaderUniforms> {
+        Some(&mut self.uniforms)
+    }
+
+    fn declare_uniform(&mut self, name: &str, uniform_type: UniformType) {
+
+        if self.uniforms.get_type(name).is_some_and(|declared| declared != uniform_type) {
+            self.uniform_handles.remove(name);
+        }
+
+        self.uniforms.declare(name, uniform_type);
+
+        // a loaded program gets the handle right away, otherwise load creates it
+        if self.loaded {
+            self.create_uniform_handles();
         }
     }
+}
+
+// the bgfx container a renderer submits with, multi backend containers expose the
+// variant they loaded and permuted ones the last selected permutation
+pub fn bgfx_container(shader: &dyn ShaderContainer) -> Option<&BgfxShaderContainer> {
+
+    if let Some(container) = shader.as_any().downcast_ref::<BgfxShaderContainer>() {
+        return Some(container);
+    }
 
-}
\ No newline at end of file
+    if let Some(container) = shader.as_any().downcast_ref::<PermutedShaderContainer>() {
+        return container.permutations.get(&container.selected);
+    }
+
+    shader.as_any().downcast_ref::<MultiBackendShaderContainer>().and_then(|container| container.active.as_ref())
+}
+
+// renderers with shaderc output of their own, direct3d 12 loads the dx11 binaries
+const SHADER_BACKENDS: [RendererType; 6] = [
+    RendererType::Direct3D11,
+    RendererType::Direct3D12,
+    RendererType::Metal,
+    RendererType::OpenGL,
+    RendererType::OpenGLES,
+    RendererType::Vulkan
+];
+
+// pixel and vertex binaries for several renderers, load picks the one for the renderer
+// bgfx selected so the same container works on every platform
+pub struct MultiBackendShaderContainer {
+    // pixel and vertex binaries keyed by renderer, at most one entry per renderer
+    variants: Vec<(RendererType, (Vec<u8>, Vec<u8>))>,
+    pub vertex_type: BgfxShaderVertexType,
+    name: Option<String>,
+    uniforms: ShaderUniforms,
+    // container of the selected variant while loaded
+    active: Option<BgfxShaderContainer>,
+    // see ShaderContainer::load_failed
+    load_failed: bool
+}
+
+impl MultiBackendShaderContainer {
+
+    pub fn new(vertex_type: BgfxShaderVertexType) -> Self {
+        Self {
+            variants: Vec::new(),
+            vertex_type,
+            name: None,
+            uniforms: ShaderUniforms::new(),
+            active: None,
+            load_failed: false
+        }
+    }
+
+    // pixel and vertex binaries for the renderer, replaces an earlier variant
+    pub fn with_variant(mut self, backend: RendererType, pixel_raw: Vec<u8>, vertex_raw: Vec<u8>) -> Self {
+        self.variants.retain(|(variant, _)| *variant != backend);
+        self.variants.push((backend, (pixel_raw, vertex_raw)));
+        self
+    }
+
+    // every <base_dir>/<backend_dir>/fs_<name>.bin and vs_<name>.bin pair that exists,
+    // fails if no renderer has binaries
+    pub fn from_dir(base_dir: &Path, name: &str, vertex_type: BgfxShaderVertexType) -> std::io::Result<Self> {
+
+        let mut container = Self::new(vertex_type);
+
+        for backend in SHADER_BACKENDS {
+
+            let directory = base_dir.join(backend_dir(backend).unwrap());
+            let pixel_path = directory.join(format!("fs_{}.bin", name));
+            let vertex_path = directory.join(format!("vs_{}.bin", name));
+
+            if !pixel_path.exists() || !vertex_path.exists() {
+                continue;
+            }
+
+            container = container.with_variant(backend, read_binary(&pixel_path, b"FSH")?, read_binary(&vertex_path, b"VSH")?);
+        }
+
+        if container.variants.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("No shader binaries for {} in {}", name, base_dir.display())));
+        }
+
+        Ok(container)
+    }
+
+    // renderers with a variant, sorted so error messages are stable
+    pub fn backends(&self) -> Vec<RendererType> {
+        let mut backends: Vec<RendererType> = self.variants.iter().map(|(backend, _)| *backend).collect();
+        backends.sort_by_key(|backend| *backend as u32);
+        backends
+    }
+
+    fn variant(&self, backend: RendererType) -> Result<&(Vec<u8>, Vec<u8>), String> {
+        self.variants.iter().find(|(variant, _)| *variant == backend).map(|(_, raw)| raw).ok_or_else(|| {
+            let available: Vec<String> = self.backends().iter().map(|backend| format!("{:?}", backend)).collect();
+            format!("No variant for {:?}, available: {}", backend, if available.is_empty() { String::from("none") } else { available.join(", ") })
+        })
+    }
+
+}
+
+impl ShaderContainer for MultiBackendShaderContainer {
+
+    fn loaded(&self) -> bool {
+        self.active.as_ref().is_some_and(|active| active.loaded())
+    }
+
+    fn load(&mut self, context: &ShaderLoadContext) -> Result<(), ShaderLoadError> {
+
+        let error = |reason: String| ShaderLoadError {
+            shader: self.name.clone(),
+            backend: Some(context.backend),
+            reason
+        };
+
+        let (pixel_raw, vertex_raw) = self.variant(context.backend).map_err(error)?.clone();
+
+        let mut active = BgfxShaderContainer::with_vertex_type(pixel_raw, vertex_raw, self.vertex_type);
+        active.name = self.name.clone();
+        active.uniforms = self.uniforms.clone();
+
+        active.load(context)?;
+
+        self.active = Some(active);
+
+        Ok(())
+    }
+
+    fn unload(&mut self) {
+        self.active = None;
+        self.load_failed = false;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
+    fn load_failed(&self) -> bool {
+        self.load_failed
+    }
+
+    fn set_load_failed(&mut self, failed: bool) {
+        self.load_failed = failed;
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+
+        let variants: Vec<(u32, &(Vec<u8>, Vec<u8>))> = self.backends().into_iter()
+            .map(|backend| (backend as u32, self.variant(backend).unwrap()))
+            .collect();
+
+        Some(source_hash((variants, &self.vertex_type)))
+    }
+
+    fn same_content(&self, other: &dyn ShaderContainer) -> bool {
+
+        let other = match other.as_any().downcast_ref::<MultiBackendShaderContainer>() {
+            Some(other) => other,
+            None => return false
+        };
+
+        self.vertex_type == other.vertex_type
+            && self.backends() == other.backends()
+            && self.backends().into_iter().all(|backend| self.variant(backend) == other.variant(backend))
+    }
+
+    fn uniforms(&self) -> Option<&ShaderUniforms> {
+        Some(&self.uniforms)
+    }
+
+    fn uniforms_mut(&mut self) -> Option<&mut ShaderUniforms> {
+        Some(&mut self.uniforms)
+    }
+
+    // kept on the container so a reload for another renderer starts with them
+    fn declare_uniform(&mut self, name: &str, uniform_type: UniformType) {
+
+        self.uniforms.declare(name, uniform_type);
+
+        if let Some(active) = self.active.as_mut() {
+            active.declare_uniform(name, uniform_type);
+        }
+    }
+
+    fn set_default(&mut self, name: &str, value: UniformValue) -> Result<(), XgError> {
+
+        self.uniforms.set_default(name, value)?;
+
+        if let Some(active) = self.active.as_mut() {
+            active.set_default(name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+// optional code paths of a shader, each set of features is a separate permutation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderFeatures(u32);
+
+impl ShaderFeatures {
+
+    pub const NONE: ShaderFeatures = ShaderFeatures(0);
+    pub const FOG: ShaderFeatures = ShaderFeatures(1);
+    pub const SKINNING: ShaderFeatures = ShaderFeatures(1 << 1);
+
+    // preprocessor define enabling each feature in the shader source
+    const DEFINES: [(ShaderFeatures, &'static str); 2] = [
+        (ShaderFeatures::FOG, "FOG"),
+        (ShaderFeatures::SKINNING, "SKINNING")
+    ];
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: ShaderFeatures) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    // shaderc defines for the enabled features
+    pub fn defines(&self) -> Vec<(String, String)> {
+        Self::DEFINES.iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| (String::from(*name), String::from("1")))
+            .collect()
+    }
+
+}
+
+impl std::ops::BitOr for ShaderFeatures {
+
+    type Output = ShaderFeatures;
+
+    fn bitor(self, rhs: ShaderFeatures) -> ShaderFeatures {
+        ShaderFeatures(self.0 | rhs.0)
+    }
+
+}
+
+impl std::ops::BitOrAssign for ShaderFeatures {
+
+    fn bitor_assign(&mut self, rhs: ShaderFeatures) {
+        self.0 |= rhs.0;
+    }
+
+}
+
+// permutations a single container keeps loaded, a new permutation beyond that is refused
+pub const MAX_PERMUTATIONS: usize = 16;
+
+enum PermutationSource {
+    // pixel and vertex binaries per feature set, built offline
+    Binaries(HashMap<ShaderFeatures, (Vec<u8>, Vec<u8>)>),
+    // sources compiled with the feature defines when a permutation is first used
+    #[cfg(feature = "shaderc")]
+    Sources {
+        shaderc: crate::shaderc::Shaderc,
+        vertex_src: String,
+        fragment_src: String,
+        varying_def: String
+    }
+}
+
+// one shader with variants selected by ShaderFeatures, the renderer asks for the program
+// matching the scene and object state on every draw
+pub struct PermutedShaderContainer {
+    source: PermutationSource,
+    pub vertex_type: BgfxShaderVertexType,
+    name: Option<String>,
+    uniforms: ShaderUniforms,
+    // loaded permutations
+    permutations: HashMap<ShaderFeatures, BgfxShaderContainer>,
+    // permutation of the last program_for call, its uniforms are applied on submit
+    selected: ShaderFeatures,
+    // see ShaderContainer::load_failed
+    load_failed: bool
+}
+
+impl PermutedShaderContainer {
+
+    pub fn new(vertex_type: BgfxShaderVertexType) -> Self {
+        Self::with_source(PermutationSource::Binaries(HashMap::new()), vertex_type)
+    }
+
+    // compiles every permutation from the sources for the renderer bgfx selected
+    #[cfg(feature = "shaderc")]
+    pub fn from_sources(shaderc: crate::shaderc::Shaderc, vertex_src: &str, fragment_src: &str, varying_def: &str, vertex_type: BgfxShaderVertexType) -> Self {
+
+        let source = PermutationSource::Sources {
+            shaderc,
+            vertex_src: String::from(vertex_src),
+            fragment_src: String::from(fragment_src),
+            varying_def: String::from(varying_def)
+        };
+
+        Self::with_source(source, vertex_type)
+    }
+
+    fn with_source(source: PermutationSource, vertex_type: BgfxShaderVertexType) -> Self {
+        Self {
+            source,
+            vertex_type,
+            name: None,
+            uniforms: ShaderUniforms::new(),
+            permutations: HashMap::new(),
+            selected: ShaderFeatures::NONE,
+            load_failed: false
+        }
+    }
+
+    // precompiled binaries for a feature set, ignored by containers compiling from sources
+    pub fn with_permutation(mut self, features: ShaderFeatures, pixel_raw: Vec<u8>, vertex_raw: Vec<u8>) -> Self {
+
+        match &mut self.source {
+            PermutationSource::Binaries(binaries) => {
+                binaries.insert(features, (pixel_raw, vertex_raw));
+            }
+            #[cfg(feature = "shaderc")]
+            PermutationSource::Sources { .. } => warn!("Shader compiles its permutations, binaries for {:?} are ignored", features)
+        }
+
+        self
+    }
+
+    // program of the permutation, loaded on first use
+    pub fn program_for(&mut self, context: &ShaderLoadContext, features: ShaderFeatures) -> Result<Rc<Program>, ShaderLoadError> {
+
+        if !self.permutations.contains_key(&features) {
+
+            let error = |reason: String| ShaderLoadError {
+                shader: self.name.clone(),
+                backend: Some(context.backend),
+                reason
+            };
+
+            if self.permutations.len() >= MAX_PERMUTATIONS {
+                return Err(error(format!("Permutation {:?} exceeds the limit of {} permutations", features, MAX_PERMUTATIONS)));
+            }
+
+            let (pixel_raw, vertex_raw) = self.permutation_binaries(context, features).map_err(error)?;
+
+            let mut permutation = BgfxShaderContainer::with_vertex_type(pixel_raw, vertex_raw, self.vertex_type);
+            permutation.name = self.name.clone();
+            permutation.uniforms = self.uniforms.clone();
+
+            permutation.load(context)?;
+
+            self.permutations.insert(features, permutation);
+        }
+
+        self.selected = features;
+
+        Ok(self.permutations[&features].program.clone().unwrap())
+    }
+
+    #[cfg_attr(not(feature = "shaderc"), allow(unused_variables))]
+    fn permutation_binaries(&self, context: &ShaderLoadContext, features: ShaderFeatures) -> Result<(Vec<u8>, Vec<u8>), String> {
+
+        match &self.source {
+
+            PermutationSource::Binaries(binaries) => binaries.get(&features).cloned().ok_or_else(|| {
+                let mut available: Vec<u32> = binaries.keys().map(|features| features.bits()).collect();
+                available.sort();
+                format!("No permutation for features {:#x}, available: {:x?}", features.bits(), available)
+            }),
+
+            #[cfg(feature = "shaderc")]
+            PermutationSource::Sources { shaderc, vertex_src, fragment_src, varying_def } => {
+
+                warn!("Compiling permutation {:?} of shader {} during the frame", features, self.name.as_deref().unwrap_or("unnamed"));
+
+                shaderc.compile_with_defines(vertex_src, fragment_src, varying_def, context.backend, &features.defines()).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+}
+
+impl ShaderContainer for PermutedShaderContainer {
+
+    fn loaded(&self) -> bool {
+        self.permutations.contains_key(&self.selected)
+    }
+
+    fn load(&mut self, context: &ShaderLoadContext) -> Result<(), ShaderLoadError> {
+        self.program_for(context, self.selected).map(|_| ())
+    }
+
+    fn unload(&mut self) {
+        self.permutations.clear();
+        self.load_failed = false;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
+    fn load_failed(&self) -> bool {
+        self.load_failed
+    }
+
+    fn set_load_failed(&mut self, failed: bool) {
+        self.load_failed = failed;
+    }
+
+    fn uniforms(&self) -> Option<&ShaderUniforms> {
+        Some(&self.uniforms)
+    }
+
+    fn uniforms_mut(&mut self) -> Option<&mut ShaderUniforms> {
+        Some(&mut self.uniforms)
+    }
+
+    fn declare_uniform(&mut self, name: &str, uniform_type: UniformType) {
+
+        self.uniforms.declare(name, uniform_type);
+
+        for permutation in self.permutations.values_mut() {
+            permutation.declare_uniform(name, uniform_type);
+        }
+    }
+
+    fn set_default(&mut self, name: &str, value: UniformValue) -> Result<(), XgError> {
+
+        self.uniforms.set_default(name, value)?;
+
+        for permutation in self.permutations.values_mut() {
+            permutation.set_default(name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+// kind of container a shader is, as far as the engine knows it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderKind {
+    Bgfx,
+    MultiBackend,
+    Permuted,
+    // container implemented outside the engine
+    Other
+}
+
+impl ShaderKind {
+
+    pub fn of(shader: &dyn ShaderContainer) -> Self {
+
+        let shader = shader.as_any();
+
+        if shader.is::<BgfxShaderContainer>() {
+            ShaderKind::Bgfx
+        } else if shader.is::<MultiBackendShaderContainer>() {
+            ShaderKind::MultiBackend
+        } else if shader.is::<PermutedShaderContainer>() {
+            ShaderKind::Permuted
+        } else {
+            ShaderKind::Other
+        }
+    }
+
+}
+
+// snapshot of a registered shader for debug output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderInfo {
+    pub id: i32,
+    pub name: Option<String>,
+    pub backend_kind: ShaderKind,
+    pub loaded: bool
+}
+
+pub struct ShaderManager {
+    shaders: HashMap<i32, Rc<RefCell<Box<dyn ShaderContainer>>>>,
+    names: HashMap<String, i32>,
+    // content hashes of shaders added with add_shader_dedup, several ids when hashes collide
+    hashes: HashMap<u64, Vec<i32>>,
+    // id of the built-in colored shader once registered
+    default_colored: Option<i32>,
+    next_id: i32
+}
+
+impl ShaderManager {
+
+    pub fn new() -> Self {
+        Self {
+            shaders: HashMap::new(),
+            names: HashMap::new(),
+            hashes: HashMap::new(),
+            default_colored: None,
+            next_id: 0
+        }
+    }
+
+    // ids are never reused, a removed id stays empty
+    pub fn add_shader(&mut self, shader: Box<dyn ShaderContainer>) -> i32 {
+        let index: i32 = self.next_id;
+        self.next_id += 1;
+        self.shaders.insert(index, Rc::new(RefCell::new(shader)));
+        index
+    }
+
+    // returns the id of an identical shader added the same way before instead of adding
+    // the shader again, shaders without a content hash are always added
+    pub fn add_shader_dedup(&mut self, shader: Box<dyn ShaderContainer>) -> i32 {
+
+        let hash = match shader.content_hash() {
+            Some(hash) => hash,
+            None => return self.add_shader(shader)
+        };
+
+        let candidates = self.hashes.get(&hash).cloned().unwrap_or_default();
+
+        for index in candidates {
+
+            // a shader borrowed elsewhere right now is not compared
+            let same = match self.shaders.get(&index).map(|existing| existing.try_borrow()) {
+                Some(Ok(existing)) => existing.name() == shader.name() && existing.same_content(&*shader),
+                _ => false
+            };
+
+            if same {
+                return index;
+            }
+
+        }
+
+        let index = self.add_shader(shader);
+        self.hashes.entry(hash).or_insert_with(Vec::new).push(index);
+
+        index
+    }
+
+    // the shader is no longer offered to add_shader_dedup
+    fn forget_hash(&mut self, index: i32) {
+
+        for ids in self.hashes.values_mut() {
+            ids.retain(|id| *id != index);
+        }
+
+        self.hashes.retain(|_, ids| !ids.is_empty());
+    }
+
+    // built-in shader for ColoredVertex, registered on first use for the renderer bgfx selected
+    #[cfg(feature = "builtin-shaders")]
+    pub fn default_colored(&mut self) -> Result<i32, XgError> {
+        self.default_colored_for(bgfx::get_renderer_type())
+    }
+
+    // only metal and opengl have builtin binaries, other renderers get XgError::NoBuiltinShader
+    #[cfg(feature = "builtin-shaders")]
+    pub fn default_colored_for(&mut self, renderer_type: RendererType) -> Result<i32, XgError> {
+
+        if let Some(index) = self.default_colored {
+            return Ok(index);
+        }
+
+        let (pixel_raw, vertex_raw) = match builtin_colored(renderer_type) {
+            Some(binaries) => binaries,
+            None => return Err(XgError::NoBuiltinShader(format!("{:?}", renderer_type)))
+        };
+
+        // tint of ColoredSceneObject, white until an object sets one
+        let mut container = BgfxShaderContainer::new(pixel_raw.to_vec(), vertex_raw.to_vec());
+        container.declare_uniform(crate::scene::object::TINT_UNIFORM, UniformType::Vec4);
+        container.set_default(crate::scene::object::TINT_UNIFORM, UniformValue::Vec4(Vec4::ONE))?;
+
+        let index = self.add_shader(Box::new(container));
+        self.default_colored = Some(index);
+
+        Ok(index)
+    }
+
+    // loads the shader binaries built for the renderer bgfx selected, see auto_shader_paths
+    pub fn add_bgfx_shader_auto(&mut self, shader_dir: &str) -> std::io::Result<i32> {
+        self.add_bgfx_shader_auto_for(shader_dir, bgfx::get_renderer_type())
+    }
+
+    pub fn add_bgfx_shader_auto_for(&mut self, shader_dir: &str, renderer_type: RendererType) -> std::io::Result<i32> {
+
+        let (pixel_path, vertex_path) = auto_shader_paths(Path::new(shader_dir), renderer_type)?;
+
+        Ok(self.add_shader(Box::new(BgfxShaderContainer::from_files(pixel_path, vertex_path)?)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.is_empty()
+    }
+
+    // same as len, kept for existing callers
+    pub fn shader_count(&self) -> usize {
+        self.len()
+    }
+
+    // registered shaders in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (i32, Rc<RefCell<Box<dyn ShaderContainer>>>)> + '_ {
+        self.shaders.iter().map(|(id, shader)| (*id, Rc::clone(shader)))
+    }
+
+    // every registered shader sorted by id, shaders borrowed for writing at the time are reported unloaded
+    pub fn describe_all(&self) -> Vec<ShaderInfo> {
+
+        let mut infos: Vec<ShaderInfo> = self.shaders.iter().map(|(id, shader)| {
+
+            match shader.try_borrow() {
+                Ok(shader) => ShaderInfo {
+                    id: *id,
+                    name: shader.name().map(String::from),
+                    backend_kind: ShaderKind::of(&**shader),
+                    loaded: shader.loaded()
+                },
+                Err(_) => ShaderInfo {
+                    id: *id,
+                    name: None,
+                    backend_kind: ShaderKind::Other,
+                    loaded: false
+                }
+            }
+        }).collect();
+
+        infos.sort_by_key(|info| info.id);
+
+        infos
+    }
+
+    // registers the shader under a unique name, the numeric id is returned as well
+    pub fn add_named_shader(&mut self, name: &str, mut shader: Box<dyn ShaderContainer>) -> Result<i32, XgError> {
+
+        if self.names.contains_key(name) {
+            return Err(XgError::DuplicateShaderName(String::from(name)));
+        }
+
+        shader.set_name(String::from(name));
+
+        let index = self.add_shader(shader);
+        self.names.insert(String::from(name), index);
+
+        Ok(index)
+    }
+
+    pub fn get_shader_by_name(&self, name: &str) -> Option<Rc<RefCell<Box<dyn ShaderContainer>>>> {
+        match self.names.get(name) {
+            Some(index) => self.get_shader(*index),
+            None => None
+        }
+    }
+
+    pub fn get_shader(&self, index: i32) -> Option<Rc<RefCell<Box<dyn ShaderContainer>>>> {
+        match self.shaders.get(&index) {
+            Some(shader) => Some(Rc::clone(shader)),
+            None => None
+        }
+    }
+
+    // reloads a shader created with BgfxShaderContainer::from_files, objects keep their container
+    // and pick up the new program on the next frame
+    pub fn reload(&mut self, index: i32) -> std::io::Result<()> {
+
+        let shader = match self.shaders.get(&index) {
+            Some(shader) => shader,
+            None => return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Shader not found"))
+        };
+
+        // the borrow ends before the error is reported, handlers may look at the shader
+        let result = match shader.borrow_mut().as_any_mut().downcast_mut::<BgfxShaderContainer>() {
+            Some(shader) => shader.reload(),
+            None => Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Shader does not support reloading"))
+        };
+
+        match &result {
+            Ok(()) => self.forget_hash(index),
+            // the previous program keeps being used
+            Err(e) => crate::report_error(ErrorSource::Shader, format!("Failed to reload shader {}: {}", index, e), true)
+        }
+
+        result
+    }
+
+    // reloads every shader created from files, returns how many were reloaded
+    pub fn reload_all(&mut self) -> usize {
+
+        // collected up front, no shader is borrowed while reloading
+        let mut ids: Vec<i32> = self.shaders.iter()
+            .filter(|(_, shader)| reloadable(&**shader.borrow()))
+            .map(|(id, _)| *id)
+            .collect();
+
+        ids.sort();
+
+        ids.into_iter().filter(|id| self.reload(*id).is_ok()).count()
+    }
+
+    // removes the shader and unloads it. a shader still referenced
+    // by scene objects is kept registered and None is returned
+    pub fn remove_shader(&mut self, index: i32) -> Option<Box<dyn ShaderContainer>> {
+
+        match self.shaders.get(&index) {
+            Some(shader) if Rc::strong_count(shader) > 1 => {
+                warn!("Shader {} is still in use and cannot be removed", index);
+                return None;
+            }
+            Some(_) => {}
+            None => return None
+        }
+
+        let shader = self.shaders.remove(&index)?;
+
+        self.names.retain(|_, id| *id != index);
+        self.forget_hash(index);
+
+        if self.default_colored == Some(index) {
+            self.default_colored = None;
+        }
+
+        let mut shader = match Rc::try_unwrap(shader) {
+            Ok(shader) => shader.into_inner(),
+            Err(_) => return None
+        };
+
+        shader.unload();
+
+        Some(shader)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scene::object::TestShaderContainer;
+    use super::*;
+
+    #[test]
+    fn textured_layout_test() {
+
+        let container = BgfxShaderContainer::with_vertex_type(Vec::new(), Vec::new(), BgfxShaderVertexType::TEXTURED);
+
+        assert_eq!(container.vertex_type, BgfxShaderVertexType::TEXTURED);
+
+        get_vertex_layout(container.vertex_type, RendererType::Noop);
+        get_vertex_layout(BgfxShaderVertexType::COLORED, RendererType::Noop);
+    }
+
+    #[test]
+    fn tga_layout_test() {
+
+        let container = BgfxShaderContainer::with_vertex_type(Vec::new(), Vec::new(), BgfxShaderVertexType::TGA);
+
+        get_vertex_layout(container.vertex_type, RendererType::Noop);
+    }
+
+    #[test]
+    fn uniform_merge_test() {
+
+        let mut container = BgfxShaderContainer::new(Vec::new(), Vec::new());
+
+        container.declare_uniform("u_tint", UniformType::Vec4);
+        container.declare_uniform("u_model", UniformType::Mat4);
+        container.declare_uniform("s_texColor", UniformType::Sampler);
+
+        container.set_default("u_tint", UniformValue::Vec4(Vec4::ONE)).unwrap();
+        assert_eq!(container.set_default("u_model", UniformValue::Vec4(Vec4::ONE)), Err(XgError::UniformTypeMismatch(String::from("u_model"))));
+        assert_eq!(container.set_default("u_missing", UniformValue::Vec4(Vec4::ONE)), Err(XgError::UndeclaredUniforms(vec![String::from("u_missing")])));
+
+        let uniforms = container.uniforms().unwrap();
+
+        assert_eq!(uniforms.merge(&HashMap::new()).unwrap(), vec![(String::from("u_tint"), UniformValue::Vec4(Vec4::ONE))]);
+
+        let overrides = HashMap::from([
+            (String::from("u_tint"), UniformValue::Vec4(Vec4::ZERO)),
+            (String::from("u_model"), UniformValue::Mat4(Mat4::IDENTITY))
+        ]);
+
+        assert_eq!(uniforms.merge(&overrides).unwrap(), vec![
+            (String::from("u_model"), UniformValue::Mat4(Mat4::IDENTITY)),
+            (String::from("u_tint"), UniformValue::Vec4(Vec4::ZERO))
+        ]);
+    }
+
+    #[test]
+    fn undeclared_uniforms_test() {
+
+        let mut uniforms = ShaderUniforms::new();
+        uniforms.declare("u_tint", UniformType::Vec4);
+
+        let overrides = HashMap::from([
+            (String::from("u_tnit"), UniformValue::Vec4(Vec4::ONE)),
+            (String::from("u_tint"), UniformValue::Mat4(Mat4::IDENTITY)),
+            (String::from("u_color"), UniformValue::Vec4(Vec4::ONE))
+        ]);
+
+        assert_eq!(uniforms.merge(&overrides), Err(XgError::UndeclaredUniforms(vec![String::from("u_color"), String::from("u_tint"), String::from("u_tnit")])));
+
+        // containers without uniform support reject defaults
+        let mut container = TestShaderContainer {};
+        container.declare_uniform("u_tint", UniformType::Vec4);
+        assert!(container.set_default("u_tint", UniformValue::Vec4(Vec4::ONE)).is_err());
+    }
+
+    // counts load and unload calls
+    #[derive(Default)]
+    struct CountingShaderContainer {
+        loaded: bool,
+        loads: u32,
+        unloads: u32
+    }
+
+    impl ShaderContainer for CountingShaderContainer {
+
+        fn loaded(&self) -> bool {
+            self.loaded
+        }
+
+        fn load(&mut self, _context: &ShaderLoadContext) -> Result<(), ShaderLoadError> {
+            self.loaded = true;
+            self.loads += 1;
+            Ok(())
+        }
+
+        fn unload(&mut self) {
+            self.loaded = false;
+            self.unloads += 1;
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn load_unload_test() {
+
+        let mut container = CountingShaderContainer::default();
+
+        container.load(&ShaderLoadContext::new(RendererType::Noop)).unwrap();
+        container.unload();
+        assert!(!container.loaded());
+
+        container.load(&ShaderLoadContext::new(RendererType::Noop)).unwrap();
+        assert!(container.loaded());
+        assert_eq!((container.loads, container.unloads), (2, 1));
+
+        // removing a shader unloads it
+        let mut manager = ShaderManager::new();
+
+        let id = manager.add_shader(Box::new(container));
+        let removed = manager.remove_shader(id).unwrap();
+        let removed = removed.as_any().downcast_ref::<CountingShaderContainer>().unwrap();
+
+        assert!(!removed.loaded());
+        assert_eq!(removed.unloads, 2);
+    }
+
+    #[test]
+    fn invalid_binary_load_test() {
+
+        let mut container = BgfxShaderContainer::new(b"garbage".to_vec(), b"VSH\x0b1".to_vec());
+        container.set_name(String::from("cubes"));
+
+        let error = container.load(&ShaderLoadContext::new(RendererType::OpenGL)).unwrap_err();
+
+        assert_eq!(error.shader, Some(String::from("cubes")));
+        assert_eq!(error.backend, Some(RendererType::OpenGL));
+        assert_eq!(error.to_string(), "Failed to load shader cubes for OpenGL: Not a bgfx FSH shader binary");
+        assert_eq!(error.reason, "Not a bgfx FSH shader binary");
+        assert!(!container.loaded());
+        assert!(container.program.is_none());
+
+        // truncated vertex binary
+        let mut container = BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VS".to_vec());
+
+        assert_eq!(container.load(&ShaderLoadContext::new(RendererType::OpenGL)).unwrap_err().reason, "Not a bgfx VSH shader binary");
+    }
+
+    #[test]
+    fn dedup_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let first = manager.add_shader_dedup(Box::new(BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec())));
+        let second = manager.add_shader_dedup(Box::new(BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec())));
+
+        assert_eq!(first, second);
+        assert_eq!(manager.shader_count(), 1);
+
+        // another layout or other bytes make a different shader
+        let textured = manager.add_shader_dedup(Box::new(BgfxShaderContainer::with_vertex_type(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec(), BgfxShaderVertexType::TEXTURED)));
+        let other = manager.add_shader_dedup(Box::new(BgfxShaderContainer::new(b"FSH\x0b2".to_vec(), b"VSH\x0b1".to_vec())));
+
+        assert_ne!(textured, first);
+        assert_ne!(other, first);
+        assert_eq!(manager.shader_count(), 3);
+
+        // shaders without a hash and removed shaders are not deduplicated
+        manager.add_shader_dedup(Box::new(TestShaderContainer {}));
+        manager.add_shader_dedup(Box::new(TestShaderContainer {}));
+        assert_eq!(manager.shader_count(), 5);
+
+        assert!(manager.remove_shader(first).is_some());
+        assert_ne!(manager.add_shader_dedup(Box::new(BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec()))), first);
+    }
+
+    #[cfg(feature = "builtin-shaders")]
+    #[test]
+    fn default_colored_test() {
+
+        let mut manager = ShaderManager::new();
+
+        assert_eq!(manager.default_colored_for(RendererType::Noop), Err(XgError::NoBuiltinShader(String::from("Noop"))));
+
+        let id = manager.default_colored_for(RendererType::OpenGL).unwrap();
+
+        assert_eq!(manager.default_colored_for(RendererType::OpenGL), Ok(id));
+        assert_eq!(manager.shader_count(), 1);
+
+        // removing it registers a fresh one on the next call
+        assert!(manager.remove_shader(id).is_some());
+        assert_ne!(manager.default_colored_for(RendererType::OpenGL), Ok(id));
+    }
+
+    #[test]
+    fn dedup_collision_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let first = manager.add_shader_dedup(Box::new(BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec())));
+
+        // pretend another shader collides with the first one
+        let colliding = BgfxShaderContainer::new(b"FSH\x0b2".to_vec(), b"VSH\x0b2".to_vec());
+        let hash = colliding.content_hash().unwrap();
+        manager.hashes.entry(hash).or_insert_with(Vec::new).push(first);
+
+        let second = manager.add_shader_dedup(Box::new(colliding));
+
+        assert_ne!(second, first);
+        assert_eq!(manager.shader_count(), 2);
+
+        // the same sources under another name are kept apart
+        let mut named = BgfxShaderContainer::new(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec());
+        named.set_name("named".to_string());
+
+        assert_ne!(manager.add_shader_dedup(Box::new(named)), first);
+        assert_eq!(manager.shader_count(), 3);
+    }
+
+    #[test]
+    fn unique_ids_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let first = manager.add_shader(Box::new(TestShaderContainer {}));
+        let second = manager.add_shader(Box::new(TestShaderContainer {}));
+
+        assert!(manager.remove_shader(first).is_some());
+
+        let third = manager.add_shader(Box::new(TestShaderContainer {}));
+
+        assert_ne!(third, first);
+        assert_ne!(third, second);
+        assert!(manager.get_shader(second).is_some());
+        assert!(manager.get_shader(third).is_some());
+    }
+
+    #[test]
+    fn removed_shader_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let id = manager.add_shader(Box::new(TestShaderContainer {}));
+
+        assert!(manager.remove_shader(id).is_some());
+        assert!(manager.get_shader(id).is_none());
+        assert!(manager.remove_shader(id).is_none());
+    }
+
+    #[test]
+    fn named_shader_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let id = manager.add_named_shader("colored", Box::new(BgfxShaderContainer::new(Vec::new(), Vec::new()))).unwrap();
+
+        let shader = manager.get_shader_by_name("colored").unwrap();
+        assert_eq!(shader.borrow().name(), Some("colored"));
+        assert!(Rc::ptr_eq(&shader, &manager.get_shader(id).unwrap()));
+
+        assert!(manager.get_shader_by_name("missing").is_none());
+    }
+
+    #[test]
+    fn describe_all_test() {
+
+        let mut manager = ShaderManager::new();
+
+        assert!(manager.is_empty());
+
+        let test = manager.add_shader(Box::new(TestShaderContainer {}));
+        let colored = manager.add_named_shader("colored", Box::new(BgfxShaderContainer::new(Vec::new(), Vec::new()))).unwrap();
+
+        let mut ids: Vec<i32> = manager.iter().map(|(id, _)| id).collect();
+        ids.sort();
+
+        assert_eq!(ids, vec![test, colored]);
+        assert_eq!(manager.len(), 2);
+
+        assert_eq!(manager.describe_all(), vec![
+            ShaderInfo { id: test, name: None, backend_kind: ShaderKind::Other, loaded: false },
+            ShaderInfo { id: colored, name: Some(String::from("colored")), backend_kind: ShaderKind::Bgfx, loaded: false }
+        ]);
+    }
+
+    #[test]
+    fn duplicate_name_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let id = manager.add_named_shader("colored", Box::new(TestShaderContainer {})).unwrap();
+
+        let result = manager.add_named_shader("colored", Box::new(TestShaderContainer {}));
+        assert_eq!(result.unwrap_err(), XgError::DuplicateShaderName(String::from("colored")));
+
+        // the first registration is untouched and the name frees up on removal
+        assert_eq!(manager.shaders.len(), 1);
+        assert!(manager.remove_shader(id).is_some());
+        assert!(manager.get_shader_by_name("colored").is_none());
+        assert!(manager.add_named_shader("colored", Box::new(TestShaderContainer {})).is_ok());
+    }
+
+    fn write_binaries(name: &str, pixel: &[u8], vertex: &[u8]) -> (PathBuf, PathBuf) {
+
+        let directory = std::env::temp_dir().join(format!("xgengine_shader_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let (pixel_path, vertex_path) = (directory.join("fs.bin"), directory.join("vs.bin"));
+
+        fs::write(&pixel_path, pixel).unwrap();
+        fs::write(&vertex_path, vertex).unwrap();
+
+        (pixel_path, vertex_path)
+    }
+
+    #[test]
+    fn reload_test() {
+
+        let (pixel_path, vertex_path) = write_binaries("reload", b"FSH\x0b1", b"VSH\x0b1");
+
+        let mut manager = ShaderManager::new();
+
+        let mut container = BgfxShaderContainer::from_files(&pixel_path, &vertex_path).unwrap();
+        container.loaded = true;
+
+        let id = manager.add_shader(Box::new(container));
+        let reference = manager.get_shader(id).unwrap();
+
+        fs::write(&pixel_path, b"FSH\x0b2").unwrap();
+
+        assert!(manager.reload(id).is_ok());
+        assert_eq!(manager.reload_all(), 1);
+
+        let shader = reference.borrow();
+        let shader = shader.as_any().downcast_ref::<BgfxShaderContainer>().unwrap();
+
+        assert!(!shader.loaded());
+        assert_eq!(shader.pixel_raw, b"FSH\x0b2".to_vec());
+
+        fs::remove_dir_all(pixel_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_reload_test() {
+
+        let (pixel_path, vertex_path) = write_binaries("failed", b"FSH\x0b1", b"VSH\x0b1");
+
+        let mut manager = ShaderManager::new();
+
+        let mut container = BgfxShaderContainer::from_files(&pixel_path, &vertex_path).unwrap();
+        container.loaded = true;
+
+        let id = manager.add_shader(Box::new(container));
+
+        // a blob bgfx would reject and a missing file both keep the previous binaries
+        fs::write(&pixel_path, b"garbage").unwrap();
+        assert_eq!(manager.reload(id).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+
+        fs::remove_file(&vertex_path).unwrap();
+        assert_eq!(manager.reload(id).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        let shader = manager.get_shader(id).unwrap();
+        let shader = shader.borrow();
+        let shader = shader.as_any().downcast_ref::<BgfxShaderContainer>().unwrap();
+
+        assert!(shader.loaded());
+        assert_eq!(shader.pixel_raw, b"FSH\x0b1".to_vec());
+
+        // containers built from memory cannot be reloaded
+        let id = manager.add_shader(Box::new(TestShaderContainer {}));
+        assert!(manager.reload(id).is_err());
+        assert_eq!(manager.reload_all(), 0);
+
+        fs::remove_dir_all(pixel_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_for_backend_test() {
+
+        let base_dir = std::env::temp_dir().join(format!("xgengine_backend_{}", std::process::id()));
+        let directory = base_dir.join("opengl");
+        fs::create_dir_all(&directory).unwrap();
+
+        fs::write(directory.join("fs_cubes.bin"), b"FSH\x0b1").unwrap();
+        fs::write(directory.join("vs_cubes.bin"), b"VSH\x0b1").unwrap();
+
+        let container = load_for_backend(&base_dir, "cubes", RendererType::OpenGL, BgfxShaderVertexType::TEXTURED).unwrap();
+
+        assert_eq!(container.vertex_type, BgfxShaderVertexType::TEXTURED);
+        assert_eq!(container.vertex_raw, b"VSH\x0b1".to_vec());
+
+        // the error names the file that was tried
+        let error = load_for_backend(&base_dir, "cubes", RendererType::Metal, BgfxShaderVertexType::COLORED).err().unwrap();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(error.to_string().contains(&base_dir.join("metal").join("fs_cubes.bin").display().to_string()));
+
+        assert_eq!(load_for_backend(&base_dir, "cubes", RendererType::Noop, BgfxShaderVertexType::COLORED).err().unwrap().kind(), std::io::ErrorKind::Unsupported);
+
+        fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[test]
+    fn auto_shader_paths_test() {
+
+        let shader_dir = std::env::temp_dir().join(format!("xgengine_auto_{}", std::process::id()));
+
+        for directory in ["metal", "spirv"] {
+            fs::create_dir_all(shader_dir.join(directory)).unwrap();
+            fs::write(shader_dir.join(directory).join("fs_cubes.bin"), b"FSH\x0b1").unwrap();
+            fs::write(shader_dir.join(directory).join("vs_cubes.bin"), b"VSH\x0b1").unwrap();
+        }
+
+        assert_eq!(auto_shader_paths(&shader_dir, RendererType::Metal).unwrap(), (shader_dir.join("metal").join("fs_cubes.bin"), shader_dir.join("metal").join("vs_cubes.bin")));
+        assert_eq!(auto_shader_paths(&shader_dir, RendererType::Vulkan).unwrap().0, shader_dir.join("spirv").join("fs_cubes.bin"));
+
+        // missing backend directory and renderers without binaries
+        assert_eq!(auto_shader_paths(&shader_dir, RendererType::OpenGL).err().unwrap().kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(auto_shader_paths(&shader_dir, RendererType::Noop).err().unwrap().kind(), std::io::ErrorKind::Unsupported);
+
+        // ambiguous directory
+        fs::write(shader_dir.join("metal").join("fs_sky.bin"), b"FSH\x0b1").unwrap();
+        assert_eq!(auto_shader_paths(&shader_dir, RendererType::Metal).err().unwrap().kind(), std::io::ErrorKind::InvalidInput);
+
+        let mut manager = ShaderManager::new();
+        let id = manager.add_bgfx_shader_auto_for(shader_dir.to_str().unwrap(), RendererType::Vulkan).unwrap();
+        assert!(manager.get_shader(id).is_some());
+
+        fs::remove_dir_all(&shader_dir).unwrap();
+    }
+
+    struct CountingFactory {
+        created: u32
+    }
+
+    impl ProgramFactory<u32> for CountingFactory {
+        fn create_program(&mut self) -> u32 {
+            self.created += 1;
+            self.created
+        }
+    }
+
+    #[test]
+    fn program_cache_test() {
+
+        let mut cache = ProgramCache::new();
+        let mut factory = CountingFactory { created: 0 };
+
+        let binaries = || (b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec());
+
+        let first = cache.get_or_create(binaries().0, binaries().1, &mut factory);
+        let second = cache.get_or_create(binaries().0, binaries().1, &mut factory);
+
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(factory.created, 1);
+
+        let other = cache.get_or_create(b"FSH\x0b2".to_vec(), b"VSH\x0b1".to_vec(), &mut factory);
+
+        assert_eq!(*other, 2);
+
+        // the entry goes away with the last container holding the program
+        drop(first);
+        drop(second);
+        drop(other);
+
+        let third = cache.get_or_create(binaries().0, binaries().1, &mut factory);
+
+        assert_eq!(*third, 3);
+        assert_eq!(cache.programs.len(), 1);
+    }
+
+    #[test]
+    fn program_cache_collision_test() {
+
+        let mut cache = ProgramCache::new();
+        let mut factory = CountingFactory { created: 0 };
+
+        let held = Rc::new(0u32);
+
+        // other binaries stored under the hash of the requested ones, as a collision would
+        let hash = source_hash((&b"FSH\x0b1".to_vec(), &b"VSH\x0b1".to_vec()));
+        cache.programs.insert(hash, vec![CachedProgram { pixel_raw: b"FSH\x0b2".to_vec(), vertex_raw: b"VSH\x0b2".to_vec(), program: Rc::downgrade(&held) }]);
+
+        let program = cache.get_or_create(b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec(), &mut factory);
+
+        assert_eq!(*program, 1);
+        assert_eq!(cache.programs[&hash].len(), 2);
+    }
+
+    #[test]
+    fn shader_features_test() {
+
+        let features = ShaderFeatures::FOG | ShaderFeatures::SKINNING;
+
+        assert!(features.contains(ShaderFeatures::FOG));
+        assert!(!ShaderFeatures::FOG.contains(features));
+        assert!(features.contains(ShaderFeatures::NONE));
+
+        assert_eq!(ShaderFeatures::NONE.defines(), Vec::new());
+        assert_eq!(features.defines(), vec![(String::from("FOG"), String::from("1")), (String::from("SKINNING"), String::from("1"))]);
+    }
+
+    #[test]
+    fn permuted_shader_test() {
+
+        let mut container = PermutedShaderContainer::new(BgfxShaderVertexType::COLORED)
+            .with_permutation(ShaderFeatures::NONE, b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec())
+            .with_permutation(ShaderFeatures::FOG, b"FSH\x0b2".to_vec(), b"VSH\x0b2".to_vec());
+
+        let context = ShaderLoadContext::new(RendererType::OpenGL);
+
+        assert_eq!(container.permutation_binaries(&context, ShaderFeatures::FOG).unwrap().0, b"FSH\x0b2".to_vec());
+
+        let error = container.program_for(&context, ShaderFeatures::SKINNING).err().unwrap();
+
+        assert_eq!(error.reason, "No permutation for features 0x2, available: [0, 1]");
+        assert!(!container.loaded());
+        assert!(bgfx_container(&container).is_none());
+
+        // the permutation count stays bounded
+        for bits in 0..MAX_PERMUTATIONS as u32 {
+            container.permutations.insert(ShaderFeatures(bits), BgfxShaderContainer::new(Vec::new(), Vec::new()));
+        }
+
+        let error = container.program_for(&context, ShaderFeatures(MAX_PERMUTATIONS as u32)).err().unwrap();
+
+        assert_eq!(error.reason, format!("Permutation ShaderFeatures({}) exceeds the limit of {} permutations", MAX_PERMUTATIONS, MAX_PERMUTATIONS));
+        assert!(bgfx_container(&container).is_some());
+    }
+
+    #[test]
+    fn multi_backend_test() {
+
+        let mut container = MultiBackendShaderContainer::new(BgfxShaderVertexType::COLORED)
+            .with_variant(RendererType::OpenGL, b"FSH\x0b1".to_vec(), b"VSH\x0b1".to_vec())
+            .with_variant(RendererType::Metal, b"FSH\x0b2".to_vec(), b"VSH\x0b2".to_vec());
+
+        assert_eq!(container.backends(), vec![RendererType::Metal, RendererType::OpenGL]);
+        assert_eq!(container.variant(RendererType::OpenGL).unwrap().0, b"FSH\x0b1".to_vec());
+        assert_eq!(container.variant(RendererType::Vulkan).unwrap_err(), "No variant for Vulkan, available: Metal, OpenGL");
+
+        // the variant follows the renderer in the load context
+        container.set_name(String::from("cubes"));
+
+        let error = container.load(&ShaderLoadContext::new(RendererType::Vulkan)).unwrap_err();
+
+        assert_eq!(error.backend, Some(RendererType::Vulkan));
+        assert_eq!(error.reason, "No variant for Vulkan, available: Metal, OpenGL");
+        assert!(!container.loaded());
+        assert!(bgfx_container(&container).is_none());
+
+        container.declare_uniform("u_tint", UniformType::Vec4);
+        assert_eq!(container.uniforms().unwrap().get_type("u_tint"), Some(UniformType::Vec4));
+    }
+
+    #[test]
+    fn multi_backend_from_dir_test() {
+
+        let base_dir = std::env::temp_dir().join(format!("xgengine_multi_{}", std::process::id()));
+
+        for directory in ["metal", "spirv"] {
+            fs::create_dir_all(base_dir.join(directory)).unwrap();
+            fs::write(base_dir.join(directory).join("fs_cubes.bin"), b"FSH\x0b1").unwrap();
+            fs::write(base_dir.join(directory).join("vs_cubes.bin"), b"VSH\x0b1").unwrap();
+        }
+
+        let container = MultiBackendShaderContainer::from_dir(&base_dir, "cubes", BgfxShaderVertexType::COLORED).unwrap();
+
+        assert_eq!(container.backends(), vec![RendererType::Metal, RendererType::Vulkan]);
+
+        assert_eq!(MultiBackendShaderContainer::from_dir(&base_dir, "missing", BgfxShaderVertexType::COLORED).err().unwrap().kind(), std::io::ErrorKind::NotFound);
+
+        fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[test]
+    fn referenced_shader_test() {
+
+        let mut manager = ShaderManager::new();
+
+        let id = manager.add_shader(Box::new(TestShaderContainer {}));
+
+        let reference = manager.get_shader(id).unwrap();
+
+        assert!(manager.remove_shader(id).is_none());
+        assert!(manager.get_shader(id).is_some());
+
+        drop(reference);
+
+        assert!(manager.remove_shader(id).is_some());
+    }
+
+}
@@ -0,0 +1,203 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use bgfx_rs::bgfx::RendererType;
+
+#[derive(Debug)]
+pub enum ShaderCompileError {
+    // sources could not be written, the output could not be read or shaderc could not be started
+    Io(std::io::Error),
+    // bgfx has no shaderc target for the renderer type
+    UnsupportedTarget(RendererType),
+    // shaderc ran and rejected the source, carries the compiler output
+    Compiler { stage: &'static str, stderr: String }
+}
+
+impl Display for ShaderCompileError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderCompileError::Io(e) => write!(f, "Failed to run shaderc: {}", e),
+            ShaderCompileError::UnsupportedTarget(target) => write!(f, "Cannot compile shaders for {:?}", target),
+            ShaderCompileError::Compiler { stage, stderr } => write!(f, "Failed to compile {} shader:\n{}", stage, stderr)
+        }
+    }
+
+}
+
+impl std::error::Error for ShaderCompileError {}
+
+impl From<std::io::Error> for ShaderCompileError {
+    fn from(e: std::io::Error) -> Self {
+        ShaderCompileError::Io(e)
+    }
+}
+
+// shaderc binary and the directories searched for includes, bgfx_shader.sh lives in bgfx/src
+pub struct Shaderc {
+    pub binary: PathBuf,
+    pub include_dirs: Vec<PathBuf>
+}
+
+impl Default for Shaderc {
+
+    // SHADERC and SHADERC_INCLUDE override the binary and the include directory,
+    // otherwise shaderc is looked up on PATH
+    fn default() -> Self {
+
+        let binary = match std::env::var_os("SHADERC") {
+            Some(path) => PathBuf::from(path),
+            None => PathBuf::from("shaderc")
+        };
+
+        let include_dirs = match std::env::var_os("SHADERC_INCLUDE") {
+            Some(paths) => std::env::split_paths(&paths).collect(),
+            None => Vec::new()
+        };
+
+        Self { binary, include_dirs }
+    }
+
+}
+
+impl Shaderc {
+
+    pub fn new<P: AsRef<Path>>(binary: P) -> Self {
+        Self {
+            binary: binary.as_ref().to_path_buf(),
+            include_dirs: Vec::new()
+        }
+    }
+
+    pub fn include<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.include_dirs.push(directory.as_ref().to_path_buf());
+        self
+    }
+
+    // compiles a vertex and fragment source pair, returns the pixel and vertex binaries
+    // in the order BgfxShaderContainer::new takes them
+    pub fn compile(&self, vertex_src: &str, fragment_src: &str, varying_def: &str, target: RendererType) -> Result<(Vec<u8>, Vec<u8>), ShaderCompileError> {
+        self.compile_with_defines(vertex_src, fragment_src, varying_def, target, &[])
+    }
+
+    // compiles with preprocessor defines given as name and value, each define set is a
+    // separate permutation of the shader
+    pub fn compile_with_defines(&self, vertex_src: &str, fragment_src: &str, varying_def: &str, target: RendererType, defines: &[(String, String)]) -> Result<(Vec<u8>, Vec<u8>), ShaderCompileError> {
+
+        let profile = target_profile(target)?;
+        let defines = define_arg(defines);
+
+        let directory = std::env::temp_dir().join(format!("xgengine_shaderc_{}_{}", std::process::id(), uuid::Uuid::new_v4()));
+        fs::create_dir_all(&directory)?;
+
+        let result = self.compile_in(&directory, vertex_src, fragment_src, varying_def, &defines, profile);
+
+        let _ = fs::remove_dir_all(&directory);
+
+        result
+    }
+
+    fn compile_in(&self, directory: &Path, vertex_src: &str, fragment_src: &str, varying_def: &str, defines: &str, profile: (&str, &str)) -> Result<(Vec<u8>, Vec<u8>), ShaderCompileError> {
+
+        let varying_path = directory.join("varying.def.sc");
+        fs::write(&varying_path, varying_def)?;
+
+        let vertex_raw = self.compile_stage(directory, "vertex", vertex_src, &varying_path, defines, profile)?;
+        let pixel_raw = self.compile_stage(directory, "fragment", fragment_src, &varying_path, defines, profile)?;
+
+        Ok((pixel_raw, vertex_raw))
+    }
+
+    fn compile_stage(&self, directory: &Path, stage: &'static str, source: &str, varying_path: &Path, defines: &str, (platform, profile): (&str, &str)) -> Result<Vec<u8>, ShaderCompileError> {
+
+        let source_path = directory.join(format!("{}.sc", stage));
+        let output_path = directory.join(format!("{}.bin", stage));
+
+        fs::write(&source_path, source)?;
+
+        let mut command = Command::new(&self.binary);
+
+        command
+            .arg("-f").arg(&source_path)
+            .arg("-o").arg(&output_path)
+            .arg("--type").arg(stage)
+            .arg("--platform").arg(platform)
+            .arg("-p").arg(profile)
+            .arg("--varyingdef").arg(varying_path);
+
+        if !defines.is_empty() {
+            command.arg("--define").arg(defines);
+        }
+
+        for include in &self.include_dirs {
+            command.arg("-i").arg(include);
+        }
+
+        let output = command.output()?;
+
+        if !output.status.success() {
+
+            // shaderc prints most of its diagnostics to stdout
+            let mut stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            stderr.push_str(&String::from_utf8_lossy(&output.stdout));
+
+            return Err(ShaderCompileError::Compiler { stage, stderr });
+        }
+
+        Ok(fs::read(&output_path)?)
+    }
+
+}
+
+// shaderc --define value, semicolon separated name=value pairs
+fn define_arg(defines: &[(String, String)]) -> String {
+    defines.iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+// shaderc --platform and --profile for the renderer the binary will be loaded by
+pub fn target_profile(target: RendererType) -> Result<(&'static str, &'static str), ShaderCompileError> {
+    match target {
+        RendererType::Direct3D11 | RendererType::Direct3D12 => Ok(("windows", "s_5_0")),
+        RendererType::Metal => Ok(("osx", "metal")),
+        RendererType::OpenGL => Ok(("linux", "120")),
+        RendererType::OpenGLES => Ok(("android", "100_es")),
+        RendererType::Vulkan => Ok(("linux", "spirv")),
+        _ => Err(ShaderCompileError::UnsupportedTarget(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_profile_test() {
+
+        assert_eq!(target_profile(RendererType::Metal).unwrap(), ("osx", "metal"));
+        assert_eq!(target_profile(RendererType::Vulkan).unwrap(), ("linux", "spirv"));
+
+        assert!(matches!(target_profile(RendererType::Noop), Err(ShaderCompileError::UnsupportedTarget(RendererType::Noop))));
+    }
+
+    #[test]
+    fn define_arg_test() {
+
+        assert_eq!(define_arg(&[]), "");
+        assert_eq!(define_arg(&[(String::from("FOG"), String::from("1")), (String::from("BONES"), String::from("64"))]), "FOG=1;BONES=64");
+    }
+
+    #[test]
+    fn missing_binary_test() {
+
+        let shaderc = Shaderc::new("xgengine-missing-shaderc");
+
+        let result = shaderc.compile("", "", "", RendererType::OpenGL);
+
+        assert!(matches!(result, Err(ShaderCompileError::Io(_))));
+    }
+
+}
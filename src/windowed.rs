@@ -1,25 +1,381 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use event_bus::dispatch_event;
 use glfw::FAIL_ON_ERRORS;
 use raw_window_handle::HasRawWindowHandle;
 use crate::ENGINE;
-use crate::events::{Action, ActionEvent, InteractEvent, InteractType};
-use crate::renderer::renderer::{BgfxRenderer, Renderer, RenderPerspective};
+use crate::events::{Action, ActionEvent, DragPhase, FileDropEvent, FrameEvent, InteractEvent, InteractType, PressAction, ResizeEvent, WindowState, WindowStateEvent};
+use crate::{DEFAULT_SCENE, XgError};
+use crate::renderer::{BgfxRenderer, Renderer, RenderPerspective};
+
+// modifiers a key binding can require, lock keys never count
+const BINDING_MODIFIERS: glfw::Modifiers = glfw::Modifiers::from_bits_truncate(
+    glfw::Modifiers::Shift.bits() | glfw::Modifiers::Control.bits() | glfw::Modifiers::Alt.bits() | glfw::Modifiers::Super.bits()
+);
+
+// change of the running window, queued by the engine for Action::SetWindowTitle,
+// Action::SetWindowSize and Action::SetCursorMode and applied by Windowed::run every frame
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowRequest {
+    Title(String),
+    Size(u32, u32),
+    CursorMode(glfw::CursorMode)
+}
 
 pub struct WindowedKeyHandler {
     key: glfw::Key,
-    action: glfw::Action
+    // physical key matched instead of key when set, the same key on every keyboard layout
+    scancode: Option<glfw::Scancode>,
+    action: glfw::Action,
+    // exactly these have to be held, none passes the key with any modifiers
+    modifiers: Option<glfw::Modifiers>
+}
+
+impl WindowedKeyHandler {
+
+    // press registrations also pass repeats, so held keys keep reporting like they did when
+    // keys were polled every frame
+    fn matches(&self, key: glfw::Key, scancode: glfw::Scancode, action: glfw::Action, modifiers: glfw::Modifiers) -> bool {
+
+        let key_match = match self.scancode {
+            Some(bound) => bound == scancode,
+            None => self.key == key
+        };
+
+        let modifiers_match = match self.modifiers {
+            Some(required) => modifiers & BINDING_MODIFIERS == required & BINDING_MODIFIERS,
+            None => true
+        };
+
+        key_match && modifiers_match && (self.action == action || (self.action == glfw::Action::Press && action == glfw::Action::Repeat))
+    }
+
+}
+
+// turns cursor positions into per frame mouse deltas. the first position after a reset
+// only sets the origin, so a jump of the cursor never reaches the camera
+#[derive(Default)]
+struct CursorDelta {
+    last: Option<(f64, f64)>,
+    // cursor is outside the window, deltas are zero until it enters again
+    paused: bool,
+    // share of the previous delta kept each frame, 0 passes raw deltas through
+    smoothing: f64,
+    smoothed: (f64, f64)
+}
+
+impl CursorDelta {
+
+    fn reset(&mut self) {
+        self.last = None;
+        self.smoothed = (0.0, 0.0);
+    }
+
+    // the cursor left or entered the window, entering measures from the next position on
+    fn set_inside(&mut self, inside: bool) {
+        self.paused = !inside;
+        self.reset();
+    }
+
+    // measures from the cursor position after the change, so releasing and capturing again does
+    // not move the camera by the distance travelled meanwhile. a captured cursor counts as inside
+    fn set_cursor_mode(&mut self, mode: glfw::CursorMode) {
+        self.set_inside(!self.paused || mode == glfw::CursorMode::Disabled);
+    }
+
+    fn update(&mut self, cursor: (f64, f64)) -> (f64, f64) {
+
+        if self.paused {
+            return (0.0, 0.0);
+        }
+
+        let delta = match self.last {
+            Some(last) => (cursor.0 - last.0, cursor.1 - last.1),
+            None => (0.0, 0.0)
+        };
+
+        self.last = Some(cursor);
+
+        self.smooth(delta)
+    }
+
+    // exponential moving average of the deltas
+    fn smooth(&mut self, delta: (f64, f64)) -> (f64, f64) {
+
+        if self.smoothing == 0.0 {
+            return delta;
+        }
+
+        let mut smoothed = (
+            self.smoothed.0 * self.smoothing + delta.0 * (1.0 - self.smoothing),
+            self.smoothed.1 * self.smoothing + delta.1 * (1.0 - self.smoothing)
+        );
+
+        // the average only approaches zero, stop sending mouse events once it is below a hundredth of a pixel
+        if smoothed.0.abs() < 0.01 && smoothed.1.abs() < 0.01 {
+            smoothed = (0.0, 0.0);
+        }
+
+        self.smoothed = smoothed;
+
+        smoothed
+    }
+
+}
+
+// longest delta a FrameEvent reports unless set, a debugger pause or a dragged window
+// would otherwise make everything jump
+pub const DEFAULT_MAX_FRAME_DELTA: f32 = 0.25;
+
+// measures the time between loop iterations with a monotonic clock
+struct FrameClock {
+    last: Option<Instant>,
+    frame: u64,
+    max_delta: f32
+}
+
+impl Default for FrameClock {
+
+    fn default() -> Self {
+        Self {
+            last: None,
+            frame: 0,
+            max_delta: DEFAULT_MAX_FRAME_DELTA
+        }
+    }
+
+}
+
+impl FrameClock {
+
+    // the first tick has no previous iteration and reports a zero delta
+    fn tick(&mut self, now: Instant) -> FrameEvent {
+
+        let delta = match self.last {
+            Some(last) => now.saturating_duration_since(last).as_secs_f32().min(self.max_delta),
+            None => 0.0
+        };
+
+        self.last = Some(now);
+
+        let event = FrameEvent::new(delta, self.frame);
+        self.frame += 1;
+
+        event
+    }
+
+}
+
+// pixels the cursor has to travel with a button held before the press turns into a drag
+pub const DEFAULT_DRAG_THRESHOLD: f64 = 4.0;
+
+// turns button presses and cursor positions into drags. a press only becomes a drag once the
+// cursor left the threshold around where it went down, so plain clicks stay clicks
+struct DragTracker {
+    threshold: f64,
+    // button held and where it went down
+    pressed: Option<(glfw::MouseButton, (f64, f64))>,
+    dragging: bool,
+    last: (f64, f64)
+}
+
+impl Default for DragTracker {
+
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_DRAG_THRESHOLD,
+            pressed: None,
+            dragging: false,
+            last: (0.0, 0.0)
+        }
+    }
+
+}
+
+impl DragTracker {
+
+    // a second button going down during a drag is ignored
+    fn press(&mut self, button: glfw::MouseButton, cursor: (f64, f64)) {
+
+        if self.pressed.is_none() {
+            self.pressed = Some((button, cursor));
+            self.dragging = false;
+            self.last = cursor;
+        }
+    }
+
+    fn motion(&mut self, cursor: (f64, f64)) -> Option<InteractType> {
+
+        let (button, start) = self.pressed?;
+
+        if cursor == self.last {
+            return None;
+        }
+
+        let phase = if self.dragging {
+            DragPhase::Move
+        } else if (cursor.0 - start.0).hypot(cursor.1 - start.1) > self.threshold {
+            self.dragging = true;
+            DragPhase::Begin
+        } else {
+            return None;
+        };
+
+        Some(self.drag(button, start, cursor, phase))
+    }
+
+    // ends the drag of the button, none when the press never turned into one
+    fn release(&mut self, button: glfw::MouseButton, cursor: (f64, f64)) -> Option<InteractType> {
+
+        let (pressed, start) = self.pressed?;
+
+        if pressed != button {
+            return None;
+        }
+
+        self.pressed = None;
+
+        if !std::mem::replace(&mut self.dragging, false) {
+            return None;
+        }
+
+        Some(self.drag(button, start, cursor, DragPhase::End))
+    }
+
+    fn drag(&mut self, button: glfw::MouseButton, start: (f64, f64), current: (f64, f64), phase: DragPhase) -> InteractType {
+
+        let delta = (current.0 - self.last.0, current.1 - self.last.1);
+        self.last = current;
+
+        InteractType::Drag { button, start, current, delta, phase }
+    }
+
+}
+
+// longest time between the presses of a double click unless set
+pub const DEFAULT_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+// farthest the cursor may move between the presses of a double click unless set, in pixels
+pub const DEFAULT_DOUBLE_CLICK_RADIUS: f64 = 4.0;
+
+// remembers the last press of every button to tell double clicks
+struct ClickTracker {
+    interval: Duration,
+    radius: f64,
+    last: Vec<(glfw::MouseButton, Instant, (f64, f64))>
+}
+
+impl Default for ClickTracker {
+
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_DOUBLE_CLICK_INTERVAL,
+            radius: DEFAULT_DOUBLE_CLICK_RADIUS,
+            last: Vec::new()
+        }
+    }
+
+}
+
+impl ClickTracker {
+
+    // true when the press completes a double click. the press of a double click does not
+    // start another one, so a triple click is a double click and a single press
+    fn press(&mut self, button: glfw::MouseButton, cursor: (f64, f64), now: Instant) -> bool {
+
+        let index = self.last.iter().position(|last| last.0 == button);
+
+        if let Some(index) = index {
+
+            let (_, time, position) = self.last.remove(index);
+
+            let close = now.saturating_duration_since(time) <= self.interval
+                && (cursor.0 - position.0).hypot(cursor.1 - position.1) <= self.radius;
+
+            if close {
+                return true;
+            }
+        }
+
+        self.last.push((button, now, cursor));
+
+        false
+    }
+
+}
+
+// dispatches a drag the tracker reported
+fn dispatch_drag(drag: Option<InteractType>) {
+
+    let drag = match drag {
+        Some(drag) => drag,
+        None => return
+    };
+
+    let mut event = InteractEvent::new(drag);
+
+    if let InteractType::Drag { current, delta, .. } = event.interact {
+        event.data.cursor = current;
+        event.data.delta = delta;
+    }
+
+    dispatch_event!("engine", &mut event);
+}
+
+// framebuffer size and content scale last reported. the size is both polled every frame and
+// reported by FramebufferSize events, only the first report of a change gets through
+#[derive(Default)]
+struct ResizeTracker {
+    size: (u32, u32),
+    scale: (f32, f32)
+}
+
+impl ResizeTracker {
+
+    fn update(&mut self, size: (u32, u32), scale: (f32, f32)) -> Option<ResizeEvent> {
+
+        if size == self.size && scale == self.scale {
+            return None;
+        }
+
+        let old = std::mem::replace(&mut self.size, size);
+        self.scale = scale;
+
+        Some(ResizeEvent::new(old, size, scale))
+    }
+
+}
+
+// dispatches the resize followed by the resolution update older handlers listen for
+fn dispatch_resize(tracker: &mut ResizeTracker, size: (i32, i32), scale: (f32, f32)) {
+
+    // glfw reports zero for a minimized window, never a negative size, clamped anyway so it cannot wrap
+    let mut event = match tracker.update((size.0.max(0) as u32, size.1.max(0) as u32), scale) {
+        Some(event) => event,
+        None => return
+    };
+
+    dispatch_event!("engine", &mut event);
+
+    let mut event = ActionEvent::new(Action::UpdateResolution(event.new.0, event.new.1));
+
+    dispatch_event!("engine", &mut event);
 }
 
 pub struct Windowed {
     width: u32,
     height: u32,
     title: String,
-    disable_cursor: bool,
+    cursor_mode: glfw::CursorMode,
+    cursor_delta: CursorDelta,
+    frame_clock: FrameClock,
+    drag: DragTracker,
+    clicks: ClickTracker,
     fps: i32,
     key_handlers: Vec<WindowedKeyHandler>,
-    window: Option<glfw::Window>
+    window: Option<glfw::Window>,
+    initial_scene: String
 }
 
 impl Windowed {
@@ -27,24 +383,101 @@ impl Windowed {
     // constructor
     pub fn new(width: u32, height: u32, title: &str, disable_cursor: bool, fps: i32) -> Self {
         Self {
-            width, height, title: title.to_string(), disable_cursor, fps,
+            width, height, title: title.to_string(), fps,
+            cursor_mode: if disable_cursor { glfw::CursorMode::Disabled } else { glfw::CursorMode::Normal },
+            cursor_delta: CursorDelta::default(),
+            frame_clock: FrameClock::default(),
+            drag: DragTracker::default(),
+            clicks: ClickTracker::default(),
             key_handlers: Vec::new(),
             window: None,
+            initial_scene: String::from(DEFAULT_SCENE)
         }
     }
 
-    // adds key handler
+    // name of the empty scene the engine starts in, "default" unless set
+    pub fn set_initial_scene(&mut self, name: &str) {
+        self.initial_scene = String::from(name);
+    }
+
+    // dispatches Keyboard events for the key with the action, register Release as well
+    // to be told when a held key goes up
     pub fn add_key_handler(&mut self, key: glfw::Key, action: glfw::Action) {
-        self.key_handlers.push(WindowedKeyHandler { key, action });
+        self.key_handlers.push(WindowedKeyHandler { key, scancode: None, action, modifiers: None });
+    }
+
+    // like add_key_handler, but only while exactly the modifiers are held, e.g. S with Control
+    // for saving. a key gets a single Keyboard event however many bindings match, so with both
+    // ctrl+s and plain s registered ctrl+s arrives once with Control in event.modifiers and
+    // handlers of the plain binding have to skip events with modifiers themselves
+    pub fn add_key_handler_with_mods(&mut self, key: glfw::Key, action: glfw::Action, modifiers: glfw::Modifiers) {
+        self.key_handlers.push(WindowedKeyHandler { key, scancode: None, action, modifiers: Some(modifiers) });
+    }
+
+    // binds the physical key with the scancode whatever the layout maps it to, so the W position
+    // moves forward on azerty too. glfw::get_key_scancode(Some(glfw::Key::W)) gives the scancode of
+    // the key at the W position of a us keyboard once glfw runs, events carry the layout's name
+    // of the key in key_name for showing the binding
+    pub fn add_scancode_handler(&mut self, scancode: glfw::Scancode, action: glfw::Action) {
+        self.key_handlers.push(WindowedKeyHandler { key: glfw::Key::Unknown, scancode: Some(scancode), action, modifiers: None });
+    }
+
+    // longest delta in seconds a FrameEvent reports, longer iterations are clamped to it
+    pub fn set_max_frame_delta(&mut self, seconds: f32) {
+        self.frame_clock.max_delta = seconds.max(0.0);
+    }
+
+    // pixels the cursor has to move with a button held before Drag events start
+    pub fn set_drag_threshold(&mut self, pixels: f64) {
+        self.drag.threshold = pixels.max(0.0);
+    }
+
+    // longest time between two presses of a button that makes them a DoubleClick
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.clicks.interval = interval;
+    }
+
+    // farthest in pixels the cursor may move between the presses of a DoubleClick
+    pub fn set_double_click_radius(&mut self, pixels: f64) {
+        self.clicks.radius = pixels.max(0.0);
+    }
+
+    // title the window is created with. while run is running, change it with
+    // crate::set_window_title, e.g. to show the fps
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
     }
 
-    // closes window
+    // size the window is created with. while run is running, change it with crate::set_window_size
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    // closes window once no ShutdownEvent handler vetoes it
     pub fn close_window(&mut self) {
         self.window.as_mut().unwrap().set_should_close(true);
     }
 
-    // creates window, create renderer and run
-    pub fn run(&mut self, default_perspective: RenderPerspective, before_cycle: &dyn Fn()) {
+    // cursor mode the window is created with, see Action::SetCursorMode. while run is running,
+    // change it with crate::set_cursor_mode
+    pub fn set_cursor_mode(&mut self, mode: glfw::CursorMode) {
+        self.cursor_mode = mode;
+        self.cursor_delta.set_cursor_mode(mode);
+    }
+
+    pub fn get_cursor_mode(&self) -> glfw::CursorMode {
+        self.cursor_mode
+    }
+
+    // smooths the mouse delta over frames, 0 gives the raw delta and values closer to 1
+    // smooth more but lag behind the mouse. clamped to 0..=0.99
+    pub fn set_mouse_smoothing(&mut self, factor: f32) {
+        self.cursor_delta.smoothing = factor.clamp(0.0, 0.99) as f64;
+    }
+
+    // creates window, create renderer and run, returns when the window closes or the renderer fails
+    pub fn run(&mut self, default_perspective: RenderPerspective, before_cycle: &dyn Fn()) -> Result<(), XgError> {
 
         let mut glfw = glfw::init(FAIL_ON_ERRORS).unwrap();
 
@@ -52,6 +485,13 @@ impl Windowed {
 
         glfw.window_hint(glfw::WindowHint::ClientApi(glfw::ClientApiHint::NoApi));
         window.set_key_polling(true);
+        window.set_mouse_button_polling(true);
+        window.set_char_polling(true);
+        window.set_focus_polling(true);
+        window.set_iconify_polling(true);
+        window.set_maximize_polling(true);
+        window.set_cursor_enter_polling(true);
+        window.set_drag_and_drop_polling(true);
 
         // set window
         self.window = Some(window);
@@ -61,9 +501,7 @@ impl Windowed {
 
         //window.set_cursor_pos_polling(true);
 
-        if self.disable_cursor {
-            window.set_cursor_mode(glfw::CursorMode::Disabled);
-        }
+        window.set_cursor_mode(self.cursor_mode);
 
         let mut raw_window_handle = Rc::new(RefCell::new(window.raw_window_handle()));
 
@@ -75,39 +513,68 @@ impl Windowed {
             default_perspective
         ));
 
-        crate::create_engine(renderer);
+        crate::create_engine(renderer, &self.initial_scene);
 
-        crate::init();
+        crate::init()?;
 
         before_cycle();
 
-        let mut old = (0, 0);
+        let mut resize = ResizeTracker::default();
 
-        let mut cursor_old: (f64, f64) = (0.0, 0.0);
+        let mut result = Ok(());
 
-        while !window.should_close() {
+        loop {
 
-            glfw.poll_events();
+            // closing was requested by the user or close_window, handlers get a chance to keep the window open
+            if window.should_close() {
 
-            let current_res = window.get_framebuffer_size();
+                if crate::confirm_shutdown() {
+                    break;
+                }
 
-            if current_res != old {
+                window.set_should_close(false);
+            }
 
-                let mut event = ActionEvent::new(Action::UpdateResolution(current_res.0 as u32, current_res.1 as u32));
+            // events other threads queued since the last frame
+            crate::dispatch_queued();
 
-                dispatch_event!("engine", &mut event);
+            // window changes requested with actions since the last frame
+            for request in crate::take_window_requests() {
+                match request {
+                    WindowRequest::Title(title) => {
+                        window.set_title(&title);
+                        self.title = title;
+                    }
+                    // the framebuffer size polled below reports the change to the renderer
+                    WindowRequest::Size(width, height) => {
+                        window.set_size(width as i32, height as i32);
+                        self.width = width;
+                        self.height = height;
+                    }
+                    WindowRequest::CursorMode(mode) => {
+                        window.set_cursor_mode(mode);
+                        self.cursor_mode = mode;
+                        self.cursor_delta.set_cursor_mode(mode);
+                    }
+                }
+            }
 
-                old = current_res;
+            let mut event = self.frame_clock.tick(Instant::now());
 
-            }
+            dispatch_event!("engine", &mut event);
+
+            // objects advance by the same clamped delta
+            crate::set_frame_delta(event.delta_seconds);
+
+            glfw.poll_events();
+
+            dispatch_resize(&mut resize, window.get_framebuffer_size(), window.get_content_scale());
 
             // get cursor position
             let cursor = window.get_cursor_pos();
 
             // calculate delta
-            let delta = (cursor.0 - cursor_old.0, cursor.1 - cursor_old.1);
-
-            cursor_old = cursor;
+            let delta = self.cursor_delta.update(cursor);
 
             if delta.0 != 0.0 || delta.1 != 0.0 {
 
@@ -120,23 +587,69 @@ impl Windowed {
 
             }
 
-            // handle key events
-            for key_handler in self.key_handlers.iter() {
-                if window.get_key(key_handler.key) == key_handler.action {
-                    unsafe {
-
-                        let mut event = InteractEvent::new(InteractType::Keyboard(key_handler.key));
-
-                        dispatch_event!("engine", &mut event);
-                    }
-                }
-            }
+            dispatch_drag(self.drag.motion(cursor));
 
             for (_, event) in glfw::flush_messages(&events) {
                 match event {
                     glfw::WindowEvent::FramebufferSize(width, height) => {
+                        dispatch_resize(&mut resize, (width, height), window.get_content_scale());
+                    },
+                    glfw::WindowEvent::MouseButton(button, action, _) => {
 
-                        let mut event = ActionEvent::new(Action::UpdateResolution(width as u32, height as u32));
+                        let cursor = window.get_cursor_pos();
+
+                        if let Some(mut event) = mouse_button_event(button, action, cursor) {
+                            dispatch_event!("engine", &mut event);
+                        }
+
+                        if action == glfw::Action::Press && self.clicks.press(button, cursor, Instant::now()) {
+
+                            let mut event = InteractEvent::new(InteractType::DoubleClick(button));
+                            event.data.cursor = cursor;
+
+                            dispatch_event!("engine", &mut event);
+                        }
+
+                        match action {
+                            glfw::Action::Press => self.drag.press(button, cursor),
+                            glfw::Action::Release => dispatch_drag(self.drag.release(button, cursor)),
+                            glfw::Action::Repeat => {}
+                        }
+                    },
+                    glfw::WindowEvent::Key(key, scancode, action, modifiers) => {
+
+                        if let Some(mut event) = key_event(&self.key_handlers, key, scancode, action, modifiers, crate::text_input()) {
+
+                            event.key_name = glfw::get_key_name(Some(key), Some(scancode));
+
+                            dispatch_event!("engine", &mut event);
+                        }
+                    },
+                    glfw::WindowEvent::Focus(_) | glfw::WindowEvent::Iconify(_) | glfw::WindowEvent::Maximize(_) => {
+
+                        if let Some(mut event) = window_state_event(&event) {
+                            dispatch_event!("engine", &mut event);
+                        }
+                    },
+                    glfw::WindowEvent::Char(character) => {
+
+                        let mut event = InteractEvent::new(InteractType::Char(character));
+
+                        dispatch_event!("engine", &mut event);
+                    },
+                    glfw::WindowEvent::FileDrop(_) => {
+
+                        if let Some(mut event) = file_drop_event(event, window.get_cursor_pos()) {
+                            dispatch_event!("engine", &mut event);
+                        }
+                    },
+                    glfw::WindowEvent::CursorEnter(entered) => {
+
+                        // mouse-look pauses outside the window unless the cursor is captured
+                        self.cursor_delta.set_inside(entered || self.cursor_mode == glfw::CursorMode::Disabled);
+
+                        let mut event = InteractEvent::new(InteractType::CursorEnter(entered));
+                        event.data.cursor = window.get_cursor_pos();
 
                         dispatch_event!("engine", &mut event);
                     },
@@ -144,7 +657,10 @@ impl Windowed {
                 }
             }
 
-            crate::do_frame();
+            if let Err(e) = crate::do_frame() {
+                result = Err(e.into());
+                break;
+            }
 
             // spleep in order to limit fps
             std::thread::sleep(std::time::Duration::from_millis((1000 / self.fps) as u64));
@@ -158,6 +674,407 @@ impl Windowed {
             renderer.shutdown()
         }
 
+        result
+    }
+
+}
+
+// keys that type a character, glfw numbers them from space to the grave accent, the two
+// non-us keys and the keypad digits and operators
+fn printable(key: glfw::Key) -> bool {
+    matches!(key as i32, 32..=96 | 161..=162 | 320..=336)
+}
+
+// keyboard event for a key the handlers are registered for, none for other keys and for
+// printable keys while text is typed
+fn key_event(handlers: &[WindowedKeyHandler], key: glfw::Key, scancode: glfw::Scancode, action: glfw::Action, modifiers: glfw::Modifiers, text_input: bool) -> Option<InteractEvent> {
+
+    if text_input && printable(key) {
+        return None;
     }
 
-}
\ No newline at end of file
+    if !handlers.iter().any(|handler| handler.matches(key, scancode, action, modifiers)) {
+        return None;
+    }
+
+    let mut event = InteractEvent::new(InteractType::Keyboard(key, action));
+    event.modifiers = modifiers;
+    event.scancode = scancode;
+
+    Some(event)
+}
+
+// window state change for focus, iconify and maximize events, maximizing itself is not reported
+fn window_state_event(event: &glfw::WindowEvent) -> Option<WindowStateEvent> {
+
+    let state = match event {
+        glfw::WindowEvent::Focus(focused) => WindowState::Focused(*focused),
+        glfw::WindowEvent::Iconify(iconified) => WindowState::Iconified(*iconified),
+        glfw::WindowEvent::Maximize(false) => WindowState::Restored,
+        _ => return None
+    };
+
+    Some(WindowStateEvent::new(state))
+}
+
+// one event for all files of a drop, none for other window events
+fn file_drop_event(event: glfw::WindowEvent, cursor: (f64, f64)) -> Option<FileDropEvent> {
+    match event {
+        glfw::WindowEvent::FileDrop(paths) => Some(FileDropEvent::new(paths, cursor)),
+        _ => None
+    }
+}
+
+// mouse event for a button going down or up at the cursor position
+fn mouse_button_event(button: glfw::MouseButton, action: glfw::Action, cursor: (f64, f64)) -> Option<InteractEvent> {
+
+    let pressed = match action {
+        glfw::Action::Press => PressAction::PRESSED(button),
+        glfw::Action::Release => PressAction::RELEASED(button),
+        glfw::Action::Repeat => return None
+    };
+
+    let mut event = InteractEvent::new(InteractType::Mouse());
+
+    event.data.cursor = cursor;
+    event.data.pressed = pressed;
+
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::*;
+
+    #[test]
+    fn mouse_button_event_test() {
+
+        let event = mouse_button_event(glfw::MouseButton::Button1, glfw::Action::Press, (120.0, 80.0)).unwrap();
+
+        assert!(matches!(event.interact, InteractType::Mouse()));
+        assert!(matches!(event.data.pressed, PressAction::PRESSED(glfw::MouseButton::Button1)));
+        assert_eq!(event.data.cursor, (120.0, 80.0));
+        assert_eq!(event.data.delta, (0.0, 0.0));
+
+        let event = mouse_button_event(glfw::MouseButton::Button2, glfw::Action::Release, (4.0, 2.0)).unwrap();
+
+        assert!(matches!(event.data.pressed, PressAction::RELEASED(glfw::MouseButton::Button2)));
+
+        assert!(mouse_button_event(glfw::MouseButton::Button1, glfw::Action::Repeat, (0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn key_event_test() {
+
+        let handlers = vec![
+            WindowedKeyHandler { key: glfw::Key::W, scancode: None, action: glfw::Action::Press, modifiers: None },
+            WindowedKeyHandler { key: glfw::Key::W, scancode: None, action: glfw::Action::Release, modifiers: None },
+            WindowedKeyHandler { key: glfw::Key::Escape, scancode: None, action: glfw::Action::Press, modifiers: None }
+        ];
+
+        for action in [glfw::Action::Press, glfw::Action::Repeat, glfw::Action::Release] {
+            let event = key_event(&handlers, glfw::Key::W, 0, action, glfw::Modifiers::empty(), false).unwrap();
+            assert!(matches!(event.interact, InteractType::Keyboard(glfw::Key::W, reported) if reported == action));
+        }
+
+        // escape is only registered for presses, unregistered keys never get through
+        assert!(key_event(&handlers, glfw::Key::Escape, 0, glfw::Action::Repeat, glfw::Modifiers::empty(), false).is_some());
+        assert!(key_event(&handlers, glfw::Key::Escape, 0, glfw::Action::Release, glfw::Modifiers::empty(), false).is_none());
+        assert!(key_event(&handlers, glfw::Key::A, 0, glfw::Action::Press, glfw::Modifiers::empty(), false).is_none());
+    }
+
+    fn phase(drag: Option<InteractType>) -> Option<(DragPhase, (f64, f64), (f64, f64))> {
+        match drag {
+            Some(InteractType::Drag { phase, current, delta, .. }) => Some((phase, current, delta)),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn drag_tracker_test() {
+
+        let mut drag = DragTracker::default();
+
+        // moving without a button is no drag
+        assert!(drag.motion((10.0, 10.0)).is_none());
+
+        drag.press(glfw::MouseButton::Button3, (100.0, 100.0));
+
+        // inside the threshold
+        assert!(drag.motion((102.0, 101.0)).is_none());
+
+        assert_eq!(phase(drag.motion((110.0, 100.0))), Some((DragPhase::Begin, (110.0, 100.0), (10.0, 0.0))));
+        assert_eq!(phase(drag.motion((112.0, 95.0))), Some((DragPhase::Move, (112.0, 95.0), (2.0, -5.0))));
+
+        // no movement, no event
+        assert!(drag.motion((112.0, 95.0)).is_none());
+
+        // other buttons neither start a drag nor end this one
+        drag.press(glfw::MouseButton::Button1, (112.0, 95.0));
+        assert!(drag.release(glfw::MouseButton::Button1, (112.0, 95.0)).is_none());
+
+        match drag.release(glfw::MouseButton::Button3, (113.0, 95.0)) {
+            Some(InteractType::Drag { button, start, phase, delta, .. }) => {
+                assert_eq!(button, glfw::MouseButton::Button3);
+                assert_eq!(start, (100.0, 100.0));
+                assert_eq!(phase, DragPhase::End);
+                assert_eq!(delta, (1.0, 0.0));
+            }
+            _ => panic!("drag did not end")
+        }
+
+        assert!(drag.motion((150.0, 150.0)).is_none());
+    }
+
+    #[test]
+    fn click_test() {
+
+        let mut drag = DragTracker::default();
+
+        drag.press(glfw::MouseButton::Button1, (50.0, 50.0));
+
+        // jitter below the threshold keeps it a click
+        assert!(drag.motion((51.0, 52.0)).is_none());
+        assert!(drag.release(glfw::MouseButton::Button1, (51.0, 52.0)).is_none());
+
+        drag.threshold = 0.0;
+        drag.press(glfw::MouseButton::Button1, (50.0, 50.0));
+
+        assert_eq!(phase(drag.motion((50.5, 50.0))).map(|drag| drag.0), Some(DragPhase::Begin));
+    }
+
+    #[test]
+    fn double_click_test() {
+
+        let mut clicks = ClickTracker::default();
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        // valid
+        assert!(!clicks.press(glfw::MouseButton::Button1, (100.0, 100.0), at(0)));
+        assert!(clicks.press(glfw::MouseButton::Button1, (102.0, 101.0), at(250)));
+
+        // a third press starts over
+        assert!(!clicks.press(glfw::MouseButton::Button1, (102.0, 101.0), at(300)));
+
+        // too slow, the late press counts as the first of the next pair
+        assert!(!clicks.press(glfw::MouseButton::Button1, (102.0, 101.0), at(1000)));
+        assert!(clicks.press(glfw::MouseButton::Button1, (102.0, 101.0), at(1100)));
+
+        // too far
+        assert!(!clicks.press(glfw::MouseButton::Button1, (0.0, 0.0), at(2000)));
+        assert!(!clicks.press(glfw::MouseButton::Button1, (30.0, 0.0), at(2100)));
+
+        // buttons are tracked separately
+        assert!(!clicks.press(glfw::MouseButton::Button2, (30.0, 0.0), at(2200)));
+        assert!(!clicks.press(glfw::MouseButton::Button1, (300.0, 0.0), at(2250)));
+        assert!(clicks.press(glfw::MouseButton::Button2, (30.0, 0.0), at(2300)));
+
+        clicks.interval = Duration::from_millis(100);
+        assert!(!clicks.press(glfw::MouseButton::Button1, (300.0, 0.0), at(2400)));
+    }
+
+    #[test]
+    fn frame_clock_test() {
+
+        let mut clock = FrameClock::default();
+        let start = Instant::now();
+
+        let event = clock.tick(start);
+        assert_eq!((event.delta_seconds, event.frame), (0.0, 0));
+
+        let event = clock.tick(start + std::time::Duration::from_millis(20));
+        assert!((event.delta_seconds - 0.02).abs() < 1e-6);
+        assert_eq!(event.frame, 1);
+
+        // resumed after a breakpoint
+        let event = clock.tick(start + std::time::Duration::from_secs(30));
+        assert_eq!(event.delta_seconds, DEFAULT_MAX_FRAME_DELTA);
+
+        clock.max_delta = 0.05;
+        let event = clock.tick(start + std::time::Duration::from_secs(31));
+        assert_eq!((event.delta_seconds, event.frame), (0.05, 3));
+    }
+
+    #[test]
+    fn resize_tracker_test() {
+
+        let mut tracker = ResizeTracker::default();
+
+        let event = tracker.update((1280, 720), (1.0, 1.0)).unwrap();
+        assert_eq!((event.old, event.new, event.scale), ((0, 0), (1280, 720), (1.0, 1.0)));
+
+        // polled and reported by the framebuffer event in the same frame
+        assert!(tracker.update((1280, 720), (1.0, 1.0)).is_none());
+
+        let event = tracker.update((1920, 1080), (1.0, 1.0)).unwrap();
+        assert_eq!((event.old, event.new), ((1280, 720), (1920, 1080)));
+
+        // moved to a HiDPI monitor without a size change
+        let event = tracker.update((1920, 1080), (2.0, 2.0)).unwrap();
+        assert_eq!((event.old, event.new, event.scale), ((1920, 1080), (1920, 1080), (2.0, 2.0)));
+
+        assert!(tracker.update((1920, 1080), (2.0, 2.0)).is_none());
+    }
+
+    #[test]
+    fn window_state_event_test() {
+
+        let event = window_state_event(&glfw::WindowEvent::Focus(false)).unwrap();
+        assert!(matches!(event.state, WindowState::Focused(false)));
+
+        let event = window_state_event(&glfw::WindowEvent::Iconify(true)).unwrap();
+        assert!(matches!(event.state, WindowState::Iconified(true)));
+
+        let event = window_state_event(&glfw::WindowEvent::Maximize(false)).unwrap();
+        assert!(matches!(event.state, WindowState::Restored));
+
+        assert!(window_state_event(&glfw::WindowEvent::Maximize(true)).is_none());
+        assert!(window_state_event(&glfw::WindowEvent::Close).is_none());
+    }
+
+    #[test]
+    fn file_drop_event_test() {
+
+        let paths = vec![PathBuf::from("levels/intro.scene"), PathBuf::from("textures/stone.png"), PathBuf::from("levels/boss.scene")];
+
+        let event = file_drop_event(glfw::WindowEvent::FileDrop(paths.clone()), (320.0, 200.0)).unwrap();
+
+        // one event for the whole drop, in the order of the drop
+        assert_eq!(event.paths, paths);
+        assert_eq!(event.cursor, (320.0, 200.0));
+
+        assert!(file_drop_event(glfw::WindowEvent::Close, (0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn key_modifiers_test() {
+
+        let handlers = vec![WindowedKeyHandler { key: glfw::Key::S, scancode: None, action: glfw::Action::Press, modifiers: None }];
+
+        let event = key_event(&handlers, glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control | glfw::Modifiers::Shift, false).unwrap();
+
+        assert!(event.modifiers.contains(glfw::Modifiers::Control));
+        assert!(event.modifiers.contains(glfw::Modifiers::Shift));
+        assert!(!event.modifiers.contains(glfw::Modifiers::Alt));
+
+        // plain s stays distinguishable from ctrl+s
+        let event = key_event(&handlers, glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::empty(), false).unwrap();
+        assert!(event.modifiers.is_empty());
+
+        assert!(InteractEvent::new(InteractType::Mouse()).modifiers.is_empty());
+    }
+
+    #[test]
+    fn key_binding_modifiers_test() {
+
+        let save = WindowedKeyHandler { key: glfw::Key::S, scancode: None, action: glfw::Action::Press, modifiers: Some(glfw::Modifiers::Control) };
+        let back = WindowedKeyHandler { key: glfw::Key::S, scancode: None, action: glfw::Action::Press, modifiers: None };
+
+        assert!(save.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control));
+        assert!(save.matches(glfw::Key::S, 0, glfw::Action::Repeat, glfw::Modifiers::Control));
+
+        // lock keys are ignored, other modifiers have to match exactly
+        assert!(save.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control | glfw::Modifiers::NumLock));
+        assert!(!save.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::empty()));
+        assert!(!save.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control | glfw::Modifiers::Shift));
+
+        // the plain binding passes any modifiers
+        assert!(back.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::empty()));
+        assert!(back.matches(glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control));
+
+        let handlers = vec![save];
+
+        assert!(key_event(&handlers, glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Control, false).is_some());
+        assert!(key_event(&handlers, glfw::Key::S, 0, glfw::Action::Press, glfw::Modifiers::Alt, false).is_none());
+    }
+
+    #[test]
+    fn scancode_binding_test() {
+
+        // the physical key at the W position, whatever the layout calls it
+        let handlers = vec![WindowedKeyHandler { key: glfw::Key::Unknown, scancode: Some(17), action: glfw::Action::Press, modifiers: None }];
+
+        let event = key_event(&handlers, glfw::Key::Z, 17, glfw::Action::Press, glfw::Modifiers::empty(), false).unwrap();
+
+        assert!(matches!(event.interact, InteractType::Keyboard(glfw::Key::Z, glfw::Action::Press)));
+        assert_eq!(event.scancode, 17);
+
+        // the W key of another position does not match
+        assert!(key_event(&handlers, glfw::Key::W, 44, glfw::Action::Press, glfw::Modifiers::empty(), false).is_none());
+    }
+
+    #[test]
+    fn text_input_test() {
+
+        let handlers = vec![
+            WindowedKeyHandler { key: glfw::Key::W, scancode: None, action: glfw::Action::Press, modifiers: None },
+            WindowedKeyHandler { key: glfw::Key::Escape, scancode: None, action: glfw::Action::Press, modifiers: None }
+        ];
+
+        // typing w into a text field does not move the camera, escape still closes it
+        assert!(key_event(&handlers, glfw::Key::W, 0, glfw::Action::Press, glfw::Modifiers::empty(), true).is_none());
+        assert!(key_event(&handlers, glfw::Key::Escape, 0, glfw::Action::Press, glfw::Modifiers::empty(), true).is_some());
+        assert!(key_event(&handlers, glfw::Key::W, 0, glfw::Action::Press, glfw::Modifiers::empty(), false).is_some());
+    }
+
+    #[test]
+    fn cursor_delta_test() {
+
+        let mut cursor_delta = CursorDelta::default();
+
+        // the first frame has no previous position
+        assert_eq!(cursor_delta.update((500.0, 300.0)), (0.0, 0.0));
+        assert_eq!(cursor_delta.update((510.0, 295.0)), (10.0, -5.0));
+
+        // released and captured again far away, the jump is skipped for one frame
+        cursor_delta.reset();
+
+        assert_eq!(cursor_delta.update((1500.0, 900.0)), (0.0, 0.0));
+        assert_eq!(cursor_delta.update((1502.0, 900.0)), (2.0, 0.0));
+    }
+
+    #[test]
+    fn cursor_enter_test() {
+
+        let mut cursor_delta = CursorDelta::default();
+
+        cursor_delta.update((100.0, 100.0));
+        assert_eq!(cursor_delta.update((120.0, 100.0)), (20.0, 0.0));
+
+        // outside the window mouse-look pauses
+        cursor_delta.set_inside(false);
+        assert_eq!(cursor_delta.update((900.0, -40.0)), (0.0, 0.0));
+        assert_eq!(cursor_delta.update((1200.0, -80.0)), (0.0, 0.0));
+
+        // re-entering far from where it left does not jump
+        cursor_delta.set_inside(true);
+        assert_eq!(cursor_delta.update((5.0, 400.0)), (0.0, 0.0));
+        assert_eq!(cursor_delta.update((8.0, 396.0)), (3.0, -4.0));
+    }
+
+    #[test]
+    fn smoothing_test() {
+
+        let mut cursor_delta = CursorDelta::default();
+        cursor_delta.smoothing = 0.5;
+
+        // each delta is averaged with the previous result
+        assert_eq!(cursor_delta.smooth((8.0, -4.0)), (4.0, -2.0));
+        assert_eq!(cursor_delta.smooth((8.0, -4.0)), (6.0, -3.0));
+        assert_eq!(cursor_delta.smooth((0.0, 0.0)), (3.0, -1.5));
+
+        // decays to exactly zero once the mouse stops
+        for _ in 0..16 {
+            cursor_delta.smooth((0.0, 0.0));
+        }
+
+        assert_eq!(cursor_delta.smooth((0.0, 0.0)), (0.0, 0.0));
+
+        // no smoothing passes the delta through
+        cursor_delta.smoothing = 0.0;
+        assert_eq!(cursor_delta.smooth((0.005, 3.0)), (0.005, 3.0));
+    }
+
+}